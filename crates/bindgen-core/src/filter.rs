@@ -0,0 +1,390 @@
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use wit_component::ComponentInterfaces;
+use wit_parser::*;
+
+/// Selects the subset of functions and types of interfaces which bindings are
+/// generated for.
+///
+/// Rules are given per interface. Interfaces without any rules are left as-is,
+/// but for an interface with rules only its allowed functions, or all of its
+/// functions if none are explicitly allowed, minus its denied functions are
+/// kept. Its types are then pruned to those used by the kept functions plus
+/// any explicitly allowed types.
+///
+/// Filtering happens before bindings are generated, so any component type
+/// embedded in the bindings also reflects only the selected subset.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    interfaces: HashMap<String, InterfaceFilter>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct InterfaceFilter {
+    allow_functions: Vec<String>,
+    deny_functions: Vec<String>,
+    allow_types: Vec<String>,
+    deny_types: Vec<String>,
+}
+
+impl Filter {
+    /// Generates bindings for the function `name` of `interface`, and no other
+    /// functions of `interface` unless also allowed.
+    pub fn allow_function(&mut self, interface: &str, name: &str) {
+        self.rules(interface).allow_functions.push(name.to_string());
+    }
+
+    /// Doesn't generate bindings for the function `name` of `interface`.
+    pub fn deny_function(&mut self, interface: &str, name: &str) {
+        self.rules(interface).deny_functions.push(name.to_string());
+    }
+
+    /// Generates bindings for the type `name` of `interface` even if it isn't
+    /// used by any of the selected functions.
+    pub fn allow_type(&mut self, interface: &str, name: &str) {
+        self.rules(interface).allow_types.push(name.to_string());
+    }
+
+    /// Doesn't generate bindings for the type `name` of `interface`.
+    ///
+    /// It's an error for a denied type to be used by a selected function.
+    pub fn deny_type(&mut self, interface: &str, name: &str) {
+        self.rules(interface).deny_types.push(name.to_string());
+    }
+
+    fn rules(&mut self, interface: &str) -> &mut InterfaceFilter {
+        self.interfaces.entry(interface.to_string()).or_default()
+    }
+
+    /// Returns whether this filter has no rules, selecting everything.
+    pub fn is_empty(&self) -> bool {
+        self.interfaces.is_empty()
+    }
+
+    /// Parses an item named by `INTERFACE#NAME`, as accepted by the CLI and
+    /// the procedural macros, returning the interface and item names.
+    pub fn parse_item(s: &str) -> Result<(&str, &str)> {
+        match s.split_once('#') {
+            Some((interface, name)) if !interface.is_empty() && !name.is_empty() => {
+                Ok((interface, name))
+            }
+            _ => bail!("expected an item of the form `INTERFACE#NAME` but found `{s}`"),
+        }
+    }
+
+    /// Applies this filter to all of the `interfaces` of a world.
+    pub fn apply(&self, interfaces: &mut ComponentInterfaces) -> Result<()> {
+        self.apply_all(
+            interfaces
+                .imports
+                .values_mut()
+                .chain(interfaces.exports.values_mut())
+                .chain(interfaces.default.as_mut()),
+        )
+    }
+
+    /// Applies this filter to each of the `interfaces`.
+    pub fn apply_all<'a>(
+        &self,
+        interfaces: impl IntoIterator<Item = &'a mut Interface>,
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+        for interface in interfaces {
+            if let Some(rules) = self.interfaces.get(&interface.name) {
+                *interface = rules.apply(interface)?;
+                seen.insert(interface.name.clone());
+            }
+        }
+        let mut unknown = self
+            .interfaces
+            .keys()
+            .filter(|name| !seen.contains(*name))
+            .collect::<Vec<_>>();
+        unknown.sort();
+        if let Some(name) = unknown.first() {
+            bail!("bindings are not being generated for an interface named `{name}` to filter");
+        }
+        Ok(())
+    }
+}
+
+impl InterfaceFilter {
+    fn apply(&self, iface: &Interface) -> Result<Interface> {
+        for name in self.allow_functions.iter().chain(&self.deny_functions) {
+            if !iface.functions.iter().any(|f| f.name == *name) {
+                bail!(
+                    "interface `{}` does not have a function named `{name}`",
+                    iface.name
+                );
+            }
+        }
+        let mut allowed_types = HashSet::new();
+        let mut denied_types = HashSet::new();
+        for (names, ids) in [
+            (&self.allow_types, &mut allowed_types),
+            (&self.deny_types, &mut denied_types),
+        ] {
+            for name in names {
+                match iface
+                    .types
+                    .iter()
+                    .find(|(_, ty)| ty.name.as_deref() == Some(name.as_str()))
+                {
+                    Some((id, _)) => ids.insert(id),
+                    None => bail!(
+                        "interface `{}` does not have a type named `{name}`",
+                        iface.name
+                    ),
+                };
+            }
+        }
+
+        let functions = iface
+            .functions
+            .iter()
+            .filter(|f| {
+                (self.allow_functions.is_empty() || self.allow_functions.contains(&f.name))
+                    && !self.deny_functions.contains(&f.name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Types are reachable from the selected functions, globals, and
+        // explicitly allowed types. Visiting types in reverse topological
+        // order visits every type before the types it uses, so reachability
+        // is propagated in a single pass.
+        let mut reachable = allowed_types;
+        let roots = functions
+            .iter()
+            .flat_map(|f| {
+                f.params
+                    .iter()
+                    .map(|(_, ty)| ty)
+                    .chain(f.results.iter_types())
+            })
+            .chain(iface.globals.iter().map(|g| &g.ty));
+        for ty in roots {
+            if let Type::Id(id) = ty {
+                reachable.insert(*id);
+            }
+        }
+        let order = iface.topological_types();
+        for id in order.iter().rev() {
+            if !reachable.contains(id) {
+                continue;
+            }
+            map_types(&iface.types[*id].kind, &mut |ty| {
+                if let Type::Id(id) = ty {
+                    reachable.insert(id);
+                }
+                ty
+            });
+        }
+        for id in denied_types {
+            if reachable.contains(&id) {
+                bail!(
+                    "type `{}` of interface `{}` is denied but used by the selected functions",
+                    iface.types[id].name.as_deref().unwrap(),
+                    iface.name
+                );
+            }
+        }
+
+        let mut ret = Interface {
+            name: iface.name.clone(),
+            interfaces: iface.interfaces.clone(),
+            interface_lookup: iface.interface_lookup.clone(),
+            ..Interface::default()
+        };
+        let mut map = HashMap::new();
+        for id in order {
+            if !reachable.contains(&id) {
+                continue;
+            }
+            let ty = &iface.types[id];
+            let kind = map_types(&ty.kind, &mut |ty| remap(&map, ty));
+            let new_id = ret.types.alloc(TypeDef {
+                docs: ty.docs.clone(),
+                kind,
+                name: ty.name.clone(),
+                foreign_module: ty.foreign_module.clone(),
+            });
+            if let Some(name) = &ty.name {
+                ret.type_lookup.insert(name.clone(), new_id);
+            }
+            map.insert(id, new_id);
+        }
+        ret.functions = functions
+            .into_iter()
+            .map(|mut f| {
+                for (_, ty) in f.params.iter_mut() {
+                    *ty = remap(&map, *ty);
+                }
+                f.results = match f.results {
+                    Results::Named(params) => Results::Named(
+                        params
+                            .into_iter()
+                            .map(|(name, ty)| (name, remap(&map, ty)))
+                            .collect(),
+                    ),
+                    Results::Anon(ty) => Results::Anon(remap(&map, ty)),
+                };
+                f
+            })
+            .collect();
+        ret.globals = iface
+            .globals
+            .iter()
+            .map(|g| Global {
+                docs: g.docs.clone(),
+                name: g.name.clone(),
+                ty: remap(&map, g.ty),
+            })
+            .collect();
+        Ok(ret)
+    }
+}
+
+fn remap(map: &HashMap<TypeId, TypeId>, ty: Type) -> Type {
+    match ty {
+        Type::Id(id) => Type::Id(map[&id]),
+        ty => ty,
+    }
+}
+
+/// Returns a copy of `kind` with each type it directly uses replaced by `f`.
+fn map_types(kind: &TypeDefKind, f: &mut dyn FnMut(Type) -> Type) -> TypeDefKind {
+    match kind {
+        TypeDefKind::Record(r) => TypeDefKind::Record(Record {
+            fields: r
+                .fields
+                .iter()
+                .map(|field| Field {
+                    ty: f(field.ty),
+                    ..field.clone()
+                })
+                .collect(),
+        }),
+        TypeDefKind::Flags(flags) => TypeDefKind::Flags(flags.clone()),
+        TypeDefKind::Enum(e) => TypeDefKind::Enum(e.clone()),
+        TypeDefKind::Tuple(t) => TypeDefKind::Tuple(Tuple {
+            types: t.types.iter().map(|ty| f(*ty)).collect(),
+        }),
+        TypeDefKind::Variant(v) => TypeDefKind::Variant(Variant {
+            cases: v
+                .cases
+                .iter()
+                .map(|case| Case {
+                    ty: case.ty.map(&mut *f),
+                    ..case.clone()
+                })
+                .collect(),
+        }),
+        TypeDefKind::Option(ty) => TypeDefKind::Option(f(*ty)),
+        TypeDefKind::Result(r) => TypeDefKind::Result(Result_ {
+            ok: r.ok.map(&mut *f),
+            err: r.err.map(&mut *f),
+        }),
+        TypeDefKind::Union(u) => TypeDefKind::Union(Union {
+            cases: u
+                .cases
+                .iter()
+                .map(|case| UnionCase {
+                    ty: f(case.ty),
+                    ..case.clone()
+                })
+                .collect(),
+        }),
+        TypeDefKind::List(ty) => TypeDefKind::List(f(*ty)),
+        TypeDefKind::Future(ty) => TypeDefKind::Future(ty.map(&mut *f)),
+        TypeDefKind::Stream(s) => TypeDefKind::Stream(Stream {
+            element: s.element.map(&mut *f),
+            end: s.end.map(&mut *f),
+        }),
+        TypeDefKind::Type(ty) => TypeDefKind::Type(f(*ty)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+    use wit_parser::Interface;
+
+    const WIT: &str = "
+        record point { x: u32, y: u32 }
+        record line { a: point, b: point }
+        enum color { red, green }
+        type unused = list<color>
+
+        a: func(p: point) -> u32
+        b: func(l: line)
+        c: func() -> color
+    ";
+
+    fn names(iface: &Interface) -> (Vec<&str>, Vec<&str>) {
+        let functions = iface.functions.iter().map(|f| f.name.as_str()).collect();
+        let mut types = iface
+            .types
+            .iter()
+            .filter_map(|(_, ty)| ty.name.as_deref())
+            .collect::<Vec<_>>();
+        types.sort();
+        (functions, types)
+    }
+
+    fn filter(f: impl FnOnce(&mut Filter)) -> anyhow::Result<Interface> {
+        let mut iface = Interface::parse("foo", WIT)?;
+        let mut filter = Filter::default();
+        f(&mut filter);
+        filter.apply_all([&mut iface])?;
+        Ok(iface)
+    }
+
+    #[test]
+    fn empty_filter_keeps_everything() {
+        let iface = filter(|_| {}).unwrap();
+        assert_eq!(iface, Interface::parse("foo", WIT).unwrap());
+    }
+
+    #[test]
+    fn allow_prunes_unused_types() {
+        let iface = filter(|f| f.allow_function("foo", "a")).unwrap();
+        assert_eq!(names(&iface), (vec!["a"], vec!["point"]));
+
+        let iface = filter(|f| {
+            f.allow_function("foo", "b");
+            f.allow_type("foo", "unused");
+        })
+        .unwrap();
+        assert_eq!(
+            names(&iface),
+            (vec!["b"], vec!["color", "line", "point", "unused"])
+        );
+    }
+
+    #[test]
+    fn deny() {
+        let iface = filter(|f| f.deny_function("foo", "c")).unwrap();
+        assert_eq!(names(&iface), (vec!["a", "b"], vec!["line", "point"]));
+
+        let err = filter(|f| f.deny_type("foo", "point")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type `point` of interface `foo` is denied but used by the selected functions"
+        );
+    }
+
+    #[test]
+    fn unknown_names() {
+        let err = filter(|f| f.allow_function("foo", "d")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "interface `foo` does not have a function named `d`"
+        );
+        let err = filter(|f| f.deny_type("bar", "point")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bindings are not being generated for an interface named `bar` to filter"
+        );
+    }
+}
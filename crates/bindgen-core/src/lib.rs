@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::collections::{btree_map::Entry, BTreeMap, HashMap};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Write};
 use std::ops::Deref;
 use std::path::Path;
@@ -7,8 +7,10 @@ use wit_component::ComponentInterfaces;
 use wit_parser::*;
 
 pub use wit_parser;
+mod filter;
 mod ns;
 
+pub use filter::Filter;
 pub use ns::Ns;
 
 #[cfg(feature = "component-generator")]
@@ -381,6 +383,7 @@ impl Types {
 #[derive(Default)]
 pub struct Files {
     files: BTreeMap<String, Vec<u8>>,
+    stubs: BTreeSet<String>,
 }
 
 impl Files {
@@ -395,13 +398,27 @@ impl Files {
         }
     }
 
+    /// Adds a file of implementation stubs meant to be edited, which must not
+    /// overwrite an existing file of the same name.
+    pub fn push_stub(&mut self, name: &str, contents: &[u8]) {
+        self.stubs.insert(name.to_owned());
+        self.push(name, contents);
+    }
+
+    /// Returns whether the file `name` was added with `push_stub`.
+    pub fn is_stub(&self, name: &str) -> bool {
+        self.stubs.contains(name)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ [u8])> {
         self.files.iter().map(|p| (p.0.as_str(), p.1.as_slice()))
     }
 }
 
+/// Loads the interface at `path`, which is either a `*.wit` file or a binary
+/// WIT package containing a single interface.
 pub fn load(path: impl AsRef<Path>) -> Result<Interface> {
-    Interface::parse_file(path)
+    wit_component::read_interface(path, None)
 }
 
 #[derive(Default)]
@@ -563,6 +580,13 @@ mod tests {
 }
 
 pub trait WorldGenerator {
+    /// Checks that the options of this generator can be used with
+    /// `interfaces`, before any bindings are generated for them.
+    fn validate(&self, interfaces: &ComponentInterfaces) -> Result<()> {
+        let _ = interfaces;
+        Ok(())
+    }
+
     fn generate(&mut self, name: &str, interfaces: &ComponentInterfaces, files: &mut Files) {
         for (name, import) in interfaces.imports.iter() {
             self.import(name, import, files);
@@ -605,12 +629,22 @@ pub trait InterfaceGenerator<'a> {
     fn type_list(&mut self, id: TypeId, name: &str, ty: &Type, docs: &Docs);
     fn type_builtin(&mut self, id: TypeId, name: &str, ty: &Type, docs: &Docs);
 
+    /// Returns whether the type `id` is already defined outside of the
+    /// generated bindings, in which case no definition is generated for it.
+    fn type_defined_elsewhere(&self, id: TypeId) -> bool {
+        let _ = id;
+        false
+    }
+
     fn types(&mut self) {
         for (id, ty) in self.iface().types.iter() {
             let name = match &ty.name {
                 Some(name) => name,
                 None => continue,
             };
+            if self.type_defined_elsewhere(id) {
+                continue;
+            }
             match &ty.kind {
                 TypeDefKind::Record(record) => self.type_record(id, name, record, &ty.docs),
                 TypeDefKind::Flags(flags) => self.type_flags(id, name, flags, &ty.docs),
@@ -40,6 +40,9 @@ pub struct C {
     needs_string: bool,
 
     direction: Direction,
+
+    // Skeleton definitions of exported functions for `--stubs`.
+    stubs: String,
 }
 
 struct Func {
@@ -52,6 +55,12 @@ pub struct Opts {
     /// Skip emitting component allocation helper functions
     #[cfg_attr(feature = "clap", arg(long))]
     no_helpers: bool,
+
+    /// Also generate `<interface>_stubs.c`, a skeleton defining every
+    /// exported function with a placeholder body, which is never overwritten
+    /// once it exists.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub stubs: bool,
 }
 
 impl Opts {
@@ -1105,6 +1114,14 @@ impl Generator for C {
         // Print the actual header for this function into the header file, and
         // it's what we'll be calling.
         let c_sig = self.print_sig(iface, func);
+        if self.opts.stubs {
+            if let Some(docs) = &func.docs.contents {
+                for line in docs.trim().lines() {
+                    uwriteln!(self.stubs, "// {line}");
+                }
+            }
+            uwriteln!(self.stubs, "{} {{\n  abort();\n}}\n", c_sig.sig);
+        }
 
         // Generate, in the C source file, the raw wasm signature that has the
         // canonical ABI.
@@ -1363,6 +1380,24 @@ impl Generator for C {
             &format!("{}.h", iface.name.to_kebab_case()),
             h_str.as_bytes(),
         );
+        if !self.stubs.is_empty() {
+            let name = iface.name.to_kebab_case();
+            let stubs = mem::take(&mut self.stubs);
+            files.push_stub(
+                &format!("{name}_stubs.c"),
+                format!(
+                    "// Skeleton definitions of the functions exported by `{name}`, generated\n\
+                     // with `--stubs`. This file isn't overwritten when the bindings are\n\
+                     // regenerated.\n\
+                     \n\
+                     #include <stdlib.h>\n\
+                     #include \"{name}.h\"\n\
+                     \n\
+                     {stubs}"
+                )
+                .as_bytes(),
+            );
+        }
         files.push(
             &format!("{}_component_type.o", iface.name.to_kebab_case()),
             component_type_object::object(iface, self.direction)
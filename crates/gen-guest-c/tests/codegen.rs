@@ -36,11 +36,49 @@ mod imports {
     test_helpers::codegen_tests!("*.wit");
 }
 
+mod stubs {
+    macro_rules! codegen_test {
+        ($name:ident $test:tt) => {
+            #[test]
+            fn $name() {
+                test_helpers::run_codegen_test(
+                    "guest-c-stubs",
+                    std::path::Path::new($test)
+                        .file_stem()
+                        .unwrap()
+                        .to_str()
+                        .unwrap(),
+                    include_str!($test),
+                    test_helpers::Direction::Export,
+                    wit_bindgen_gen_guest_c::Opts {
+                        stubs: true,
+                        ..Default::default()
+                    }
+                    .build(),
+                    super::verify_stubs,
+                )
+            }
+        };
+    }
+    test_helpers::codegen_tests!("*.wit");
+}
+
 fn verify(dir: &Path, name: &str) {
+    compile(dir, &format!("{name}.c"));
+}
+
+fn verify_stubs(dir: &Path, name: &str) {
+    verify(dir, name);
+    if dir.join(format!("{name}_stubs.c")).exists() {
+        compile(dir, &format!("{name}_stubs.c"));
+    }
+}
+
+fn compile(dir: &Path, file: &str) {
     let path = PathBuf::from(env::var_os("WASI_SDK_PATH").unwrap());
     let mut cmd = Command::new(path.join("bin/clang"));
     cmd.arg("--sysroot").arg(path.join("share/wasi-sysroot"));
-    cmd.arg(dir.join(format!("{}.c", name)));
+    cmd.arg(dir.join(file));
     cmd.arg("-I").arg(dir);
     cmd.arg("-Wall")
         .arg("-Wextra")
@@ -9,7 +9,8 @@ use wit_bindgen_core::{
     WorldGenerator,
 };
 use wit_bindgen_gen_rust_lib::{
-    int_repr, wasm_type, FnSig, RustFlagsRepr, RustFunctionGenerator, RustGenerator, TypeMode,
+    int_repr, to_rust_ident, wasm_type, FnSig, RustFlagsRepr, RustFunctionGenerator, RustGenerator,
+    TypeMode,
 };
 use wit_component::ComponentInterfaces;
 
@@ -18,6 +19,7 @@ struct RustWasm {
     src: Source,
     opts: Opts,
     exports: Vec<Source>,
+    stubs: Source,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -62,6 +64,91 @@ pub struct Opts {
     /// format `export_{world_name}!`.
     #[cfg_attr(feature = "clap", arg(long))]
     pub export_macro_name: Option<String>,
+
+    /// Additional traits to derive for all generated records, variants,
+    /// unions and enums, for example `Hash` or `serde::Serialize`. Can be
+    /// specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(long = "additional-derive", value_name = "DERIVE")
+    )]
+    pub additional_derives: Vec<String>,
+
+    /// Additional attributes to add to the generated type of a WIT type,
+    /// given as `TYPE=ATTRIBUTE` where `TYPE` may be qualified as
+    /// `INTERFACE#TYPE`, for example `point=#[non_exhaustive]`. Can be
+    /// specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "type-attribute",
+            value_name = "TYPE=ATTRIBUTE",
+            value_parser = wit_bindgen_gen_rust_lib::parse_type_attribute
+        )
+    )]
+    pub type_attributes: Vec<(String, String)>,
+
+    /// Existing Rust types to use in place of generating WIT types, given as
+    /// `MODULE.TYPE=PATH`, for example `types.timestamp=crate::Timestamp`.
+    ///
+    /// `MODULE` is the interface which defines the type, so every interface
+    /// which `use`s the type refers to the same Rust type. The Rust type must
+    /// have the same shape as the type which would otherwise be generated.
+    /// Can be specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "with",
+            value_name = "MODULE.TYPE=PATH",
+            value_parser = wit_bindgen_gen_rust_lib::parse_with
+        )
+    )]
+    pub with: Vec<(String, String)>,
+
+    /// If true, export traits take `&mut self` and the generated `export_*!`
+    /// macro takes an expression constructing the implementing type, which is
+    /// evaluated once on the first call to an export to create the single
+    /// instance used by all exports.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub stateful_exports: bool,
+
+    /// If true, bindings can be used natively, for example in unit tests, in
+    /// addition to on wasm.
+    ///
+    /// When not compiled to wasm, imports call a mock implementation of the
+    /// `{Interface}Mock` trait set with `set_mock` in each imported
+    /// interface's module, and the `export_*!` macro doesn't generate any
+    /// canonical ABI glue, so tests call their implementation of exports
+    /// directly with Rust values. Native use requires `std`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub native_testing: bool,
+
+    /// If true, malformed values lifted from the canonical ABI, such as an
+    /// invalid `char` or enum discriminant, are reported as a
+    /// `wit_bindgen_guest_rust::LiftError` instead of panicking.
+    ///
+    /// Each import with results also gets a `try_*` function returning the
+    /// error, which frees the memory of the results lifted so far but leaks
+    /// the strings and lists within those after the malformed value, since
+    /// they're never lifted. Otherwise the handler set with
+    /// `wit_bindgen_guest_rust::set_lift_error_handler` is called, and the
+    /// instance traps if there isn't one. Implies checked lifts even if
+    /// `unchecked` is set.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub validate_lifts: bool,
+
+    /// If true, exports whose only result is a `string` or a list of
+    /// primitive values return a `Cow<'static, str>` or `Cow<'static, [T]>`,
+    /// so static data can be returned without copying it into a `String` or
+    /// `Vec` first. Only owned results are freed once returned.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub borrowed_export_results: bool,
+
+    /// If true, also generates `{name}_stubs.rs`, a skeleton implementing
+    /// every export with `todo!()` for filling in. Unlike the bindings it's
+    /// never overwritten once it exists.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub stubs: bool,
 }
 
 impl Opts {
@@ -100,6 +187,45 @@ impl RustWasm {
             return_pointer_area_align: 0,
         }
     }
+
+    fn export_macro_name(&self, name: &str) -> String {
+        if let Some(name) = self.opts.export_macro_name.as_ref() {
+            name.to_snake_case()
+        } else {
+            format!("export_{}", name.to_snake_case())
+        }
+    }
+
+    fn finish_stubs(&mut self, name: &str, files: &mut Files) {
+        let snake = name.to_snake_case();
+        let macro_name = self.export_macro_name(name);
+        let (component, export) = if self.opts.stateful_exports {
+            (
+                "#[derive(Default)]\npub struct Component;",
+                format!("{macro_name}!(Component, Component::default())"),
+            )
+        } else {
+            ("pub struct Component;", format!("{macro_name}!(Component)"))
+        };
+        let stubs = mem::take(&mut self.stubs);
+        let src = format!(
+            "\
+// Skeleton implementations of the exports of `{name}`, generated by
+// `wit-bindgen guest rust --stubs`. This file isn't overwritten when the
+// bindings are regenerated.
+//
+// The bindings are expected to be the `crate::{snake}` module, and
+// `Component` exported with `{export};`.
+
+use crate::{snake} as bindings;
+
+/// The implementation of the exports of `{name}`.
+{component}
+{stubs}",
+            stubs = &stubs[..],
+        );
+        files.push_stub(&format!("{snake}_stubs.rs"), src.as_bytes());
+    }
 }
 
 impl WorldGenerator for RustWasm {
@@ -110,6 +236,9 @@ impl WorldGenerator for RustWasm {
         for func in iface.functions.iter() {
             gen.generate_guest_import(func);
         }
+        if gen.gen.opts.native_testing {
+            gen.generate_import_mock(name);
+        }
 
         gen.append_submodule(name);
     }
@@ -125,27 +254,53 @@ impl WorldGenerator for RustWasm {
     }
 
     fn finish(&mut self, name: &str, interfaces: &ComponentInterfaces, files: &mut Files) {
+        if self.opts.stubs && !self.stubs.is_empty() {
+            self.finish_stubs(name, files);
+        }
         if !self.exports.is_empty() {
-            let macro_name = if let Some(name) = self.opts.export_macro_name.as_ref() {
-                name.to_snake_case()
-            } else {
-                format!("export_{}", name.to_snake_case())
-            };
+            let macro_name = self.export_macro_name(name);
             let macro_export = if self.opts.macro_export {
                 "#[macro_export]"
             } else {
                 ""
             };
-            uwrite!(
-                self.src,
-                "
-                    /// Declares the export of the component's world for the
-                    /// given type.
-                    {macro_export}
-                    macro_rules! {macro_name}(($t:ident) => {{
-                        const _: () = {{
-                "
-            );
+            // Natively there's no canonical ABI to export functions through.
+            let glue_cfg = if self.opts.native_testing {
+                "#[cfg(target_arch = \"wasm32\")]"
+            } else {
+                ""
+            };
+            if self.opts.stateful_exports {
+                uwrite!(
+                    self.src,
+                    "
+                        /// Declares the export of the component's world for the
+                        /// given type, whose single instance is created with the
+                        /// given expression on the first call to an export.
+                        {macro_export}
+                        macro_rules! {macro_name}(($t:ident, $init:expr) => {{
+                            {glue_cfg}
+                            const _: () = {{
+                                #[doc(hidden)]
+                                unsafe fn __instance() -> &'static mut $t {{
+                                    static mut INSTANCE: Option<$t> = None;
+                                    INSTANCE.get_or_insert_with(|| $init)
+                                }}
+                    "
+                );
+            } else {
+                uwrite!(
+                    self.src,
+                    "
+                        /// Declares the export of the component's world for the
+                        /// given type.
+                        {macro_export}
+                        macro_rules! {macro_name}(($t:ident) => {{
+                            {glue_cfg}
+                            const _: () = {{
+                    "
+                );
+            }
             for src in self.exports.iter() {
                 self.src.push_str(src);
             }
@@ -255,7 +410,10 @@ impl InterfaceGenerator<'_> {
         for func in self.iface.functions.iter() {
             let mut sig = FnSig::default();
             sig.private = true;
-            self.print_signature(func, TypeMode::Owned, &sig);
+            if self.gen.opts.stateful_exports {
+                sig.self_arg = Some("&mut self".to_string());
+            }
+            self.print_export_signature(func, &sig);
             self.src.push_str(";\n");
         }
         uwriteln!(self.src, "}}");
@@ -263,10 +421,85 @@ impl InterfaceGenerator<'_> {
         for func in self.iface.functions.iter() {
             self.generate_guest_export(name, func, default_export);
         }
+        if self.gen.opts.stubs {
+            self.generate_stubs(name);
+        }
 
         self.append_submodule(name);
     }
 
+    fn print_export_signature(&mut self, func: &Function, sig: &FnSig) {
+        match self.borrowed_export_result(func) {
+            Some(ty) => {
+                self.print_docs_and_params(func, TypeMode::Owned, sig);
+                uwrite!(self.src, " -> {ty}");
+            }
+            None => {
+                self.print_signature(func, TypeMode::Owned, sig);
+            }
+        }
+    }
+
+    /// Returns the `Cow` type `func` returns with `borrowed_export_results`,
+    /// if its result is a `string` or a list of primitive values.
+    fn borrowed_export_result(&mut self, func: &Function) -> Option<String> {
+        if !self.gen.opts.borrowed_export_results || func.results.len() != 1 {
+            return None;
+        }
+        let cow = "wit_bindgen_guest_rust::rt::borrow::Cow";
+        let element = match func.results.iter_types().next().unwrap() {
+            Type::String if self.gen.opts.raw_strings => Type::U8,
+            Type::String => return Some(format!("{cow}<'static, str>")),
+            Type::Id(id) => match &self.iface.types[*id] {
+                TypeDef {
+                    name: None,
+                    kind: TypeDefKind::List(element),
+                    ..
+                } if !matches!(element, Type::Id(_)) && self.iface.all_bits_valid(element) => {
+                    *element
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let prev = mem::take(&mut self.src);
+        self.print_ty(&element, TypeMode::Owned);
+        let element = mem::replace(&mut self.src, prev);
+        Some(format!("{cow}<'static, [{}]>", &element[..]))
+    }
+
+    /// Generates the skeleton implementation of the exported interface `name`
+    /// for the stubs file.
+    fn generate_stubs(&mut self, name: &str) {
+        let prev = mem::take(&mut self.src);
+        for func in self.iface.functions.iter() {
+            let mut sig = FnSig::default();
+            sig.private = true;
+            if self.gen.opts.stateful_exports {
+                sig.self_arg = Some("&mut self".to_string());
+            }
+            self.print_export_signature(func, &sig);
+            self.src.push_str(" {\ntodo!()\n}\n");
+        }
+        let funcs = mem::replace(&mut self.src, prev);
+        let snake = name.to_snake_case();
+        let camel = name.to_upper_camel_case();
+        uwriteln!(
+            self.gen.stubs,
+            "
+                #[allow(unused_variables)]
+                mod {snake} {{
+                    use super::bindings::{snake}::*;
+
+                    impl {camel} for super::Component {{
+                        {funcs}
+                    }}
+                }}
+            ",
+            funcs = &funcs[..],
+        );
+    }
+
     fn append_submodule(mut self, name: &str) {
         if self.return_pointer_area_align > 0 {
             let camel = name.to_upper_camel_case();
@@ -304,11 +537,66 @@ impl InterfaceGenerator<'_> {
         match &func.kind {
             FunctionKind::Freestanding => {}
         }
-        let params = self.print_signature(func, param_mode, &sig);
+        if self.gen.opts.validate_lifts && func.results.len() > 0 {
+            // The import itself becomes a wrapper around `try_*`, which is
+            // what's mocked natively.
+            let try_func = Function {
+                name: format!("try-{}", func.name),
+                ..func.clone()
+            };
+            let params = self.print_signature(func, param_mode, &sig);
+            uwriteln!(
+                self.src,
+                "{{
+                    match {}({}) {{
+                        Ok(ret) => ret,
+                        Err(e) => wit_bindgen_guest_rust::rt::invalid_lift(e),
+                    }}
+                }}",
+                to_rust_ident(&try_func.name),
+                params.join(", ")
+            );
+            self.generate_guest_import_body(func, &try_func, LiftErrors::Return);
+        } else {
+            self.generate_guest_import_body(func, func, LiftErrors::default_for(&self.gen.opts));
+        }
+
+        match &func.kind {
+            FunctionKind::Freestanding => {}
+        }
+    }
+
+    /// Generates the function `sig_func` calling the import `func`, which
+    /// reports invalid lifted results according to `lift_errors`.
+    fn generate_guest_import_body(
+        &mut self,
+        func: &Function,
+        sig_func: &Function,
+        lift_errors: LiftErrors,
+    ) {
+        let sig = FnSig::default();
+        let param_mode = TypeMode::AllBorrowed("'_");
+        if self.gen.opts.native_testing {
+            // Natively the import is instead forwarded to the mock.
+            self.src.push_str("#[cfg(not(target_arch = \"wasm32\"))]\n");
+            let params = self.print_import_signature(sig_func, param_mode, &sig, lift_errors);
+            let call = format!(
+                "__with_mock(|mock| mock.{}({}))",
+                to_rust_ident(&func.name),
+                params.join(", ")
+            );
+            match lift_errors {
+                LiftErrors::Return => uwriteln!(self.src, "{{ Ok({call}) }}"),
+                _ => uwriteln!(self.src, "{{ {call} }}"),
+            }
+            self.src.push_str("#[cfg(target_arch = \"wasm32\")]\n");
+        }
+        let params = self.print_import_signature(sig_func, param_mode, &sig, lift_errors);
         self.src.push_str("{\n");
         self.src.push_str("unsafe {\n");
 
         let mut f = FunctionBindgen::new(self, params);
+        f.lift_errors = lift_errors;
         f.gen.iface.call(
             AbiVariant::GuestImport,
             LiftLower::LowerArgsLiftResults,
@@ -328,10 +616,75 @@ impl InterfaceGenerator<'_> {
 
         self.src.push_str("}\n");
         self.src.push_str("}\n");
+    }
 
-        match &func.kind {
-            FunctionKind::Freestanding => {}
+    fn print_import_signature(
+        &mut self,
+        func: &Function,
+        param_mode: TypeMode,
+        sig: &FnSig,
+        lift_errors: LiftErrors,
+    ) -> Vec<String> {
+        if lift_errors != LiftErrors::Return {
+            return self.print_signature(func, param_mode, sig);
+        }
+        let params = self.print_docs_and_params(func, param_mode, sig);
+        self.push_str(" -> Result<");
+        self.print_result_params(&func.results, TypeMode::Owned);
+        self.push_str(", wit_bindgen_guest_rust::LiftError>");
+        params
+    }
+
+    /// Generates the trait implemented by mocks of the imported interface
+    /// `name` when not compiled to wasm, and the storage of the mock.
+    fn generate_import_mock(&mut self, name: &str) {
+        let camel = name.to_upper_camel_case();
+        uwriteln!(
+            self.src,
+            "
+                /// An implementation of the `{name}` interface used in place of
+                /// the actual imports when not compiled to wasm.
+                #[cfg(not(target_arch = \"wasm32\"))]
+                pub trait {camel}Mock {{
+            "
+        );
+        for func in self.iface.functions.iter() {
+            let mut sig = FnSig::default();
+            sig.private = true;
+            sig.self_arg = Some("&mut self".to_string());
+            self.print_signature(func, TypeMode::AllBorrowed("'_"), &sig);
+            self.src.push_str(";\n");
         }
+        uwriteln!(
+            self.src,
+            "
+                }}
+
+                #[cfg(not(target_arch = \"wasm32\"))]
+                std::thread_local! {{
+                    static MOCK: core::cell::RefCell<Option<Box<dyn {camel}Mock>>> =
+                        core::cell::RefCell::new(None);
+                }}
+
+                /// Sets the mock which imports of the `{name}` interface call on
+                /// this thread when not compiled to wasm.
+                #[cfg(not(target_arch = \"wasm32\"))]
+                pub fn set_mock(mock: impl {camel}Mock + 'static) {{
+                    MOCK.with(|m| *m.borrow_mut() = Some(Box::new(mock)));
+                }}
+
+                #[cfg(not(target_arch = \"wasm32\"))]
+                fn __with_mock<R>(f: impl FnOnce(&mut dyn {camel}Mock) -> R) -> R {{
+                    MOCK.with(|m| {{
+                        let mut mock = m.borrow_mut();
+                        let mock = mock
+                            .as_deref_mut()
+                            .expect(\"no mock set for imports of `{name}`, call `set_mock` first\");
+                        f(mock)
+                    }})
+                }}
+            "
+        );
     }
 
     fn generate_guest_export(&mut self, module_name: &str, func: &Function, default_export: bool) {
@@ -342,6 +695,14 @@ impl InterfaceGenerator<'_> {
         let export_name = self.iface.core_export_name(default_export, func);
         let mut macro_src = Source::default();
 
+        // With a borrowed result, whether it's owned and needs to be freed in
+        // the post-return function is recorded here.
+        let result_owned = self.borrowed_export_result(func).map(|_| {
+            let flag = format!("{}_RESULT_OWNED", func.name.to_shouty_snake_case());
+            uwriteln!(self.src, "static mut {flag}: bool = false;");
+            flag
+        });
+
         // Generate, simultaneously, the actual lifting/lowering function within
         // the original module (`call_{name_snake}`) as well as the function
         // which will ge exported from the wasm module itself through the export
@@ -357,6 +718,9 @@ impl InterfaceGenerator<'_> {
                 pub unsafe fn call_{name_snake}<T: {trait_bound}>(\
             ",
         );
+        if self.gen.opts.stateful_exports {
+            self.src.push_str("this: &mut T,");
+        }
         uwrite!(
             macro_src,
             "
@@ -395,12 +759,16 @@ impl InterfaceGenerator<'_> {
             "{prefix}{module_name}::call_{name_snake}::<$t>(",
             prefix = self.gen.opts.macro_call_prefix.as_deref().unwrap_or("")
         );
+        if self.gen.opts.stateful_exports {
+            macro_src.push_str("__instance(),");
+        }
         for param in params.iter() {
             uwrite!(macro_src, "{param},");
         }
         uwriteln!(macro_src, ")\n}}");
 
         let mut f = FunctionBindgen::new(self, params);
+        f.result_owned = result_owned.clone();
         f.gen.iface.call(
             AbiVariant::GuestExport,
             LiftLower::LiftArgsLowerResults,
@@ -463,7 +831,10 @@ impl InterfaceGenerator<'_> {
                 ..
             } = f;
             assert!(!needs_cleanup_list);
-            self.src.push_str(&String::from(src));
+            match &result_owned {
+                Some(flag) => uwriteln!(self.src, "if {flag} {{\n{}}}", &src[..]),
+                None => self.src.push_str(&String::from(src)),
+            }
             self.src.push_str("}\n");
         }
 
@@ -488,6 +859,18 @@ impl<'a> RustGenerator<'a> for InterfaceGenerator<'a> {
         self.default_param_mode
     }
 
+    fn additional_derives(&self) -> &[String] {
+        &self.gen.opts.additional_derives
+    }
+
+    fn type_attributes(&self) -> &[(String, String)] {
+        &self.gen.opts.type_attributes
+    }
+
+    fn with_types(&self) -> &[(String, String)] {
+        &self.gen.opts.with
+    }
+
     fn push_str(&mut self, s: &str) {
         self.src.push_str(s);
     }
@@ -523,6 +906,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.iface
     }
 
+    fn type_defined_elsewhere(&self, id: TypeId) -> bool {
+        self.mapped_type(id).is_some()
+    }
+
     fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
         self.print_typedef_record(id, record, docs, false);
     }
@@ -615,10 +1002,36 @@ struct FunctionBindgen<'a, 'b> {
     tmp: usize,
     needs_cleanup_list: bool,
     cleanup: Vec<(String, String)>,
+    lift_errors: LiftErrors,
+    /// The flag recording whether a borrowed export result is owned, see
+    /// `borrowed_export_results`.
+    result_owned: Option<String>,
+}
+
+/// How generated code reacts to invalid values being lifted.
+#[derive(Clone, Copy, PartialEq)]
+enum LiftErrors {
+    /// Panics, the default.
+    Panic,
+    /// Returns a `LiftError` from the generated function.
+    Return,
+    /// Calls the lift error handler set at runtime.
+    Handler,
+}
+
+impl LiftErrors {
+    fn default_for(opts: &Opts) -> LiftErrors {
+        if opts.validate_lifts {
+            LiftErrors::Handler
+        } else {
+            LiftErrors::Panic
+        }
+    }
 }
 
 impl<'a, 'b> FunctionBindgen<'a, 'b> {
     fn new(gen: &'b mut InterfaceGenerator<'a>, params: Vec<String>) -> FunctionBindgen<'a, 'b> {
+        let lift_errors = LiftErrors::default_for(&gen.gen.opts);
         FunctionBindgen {
             gen,
             params,
@@ -628,6 +1041,69 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             tmp: 0,
             needs_cleanup_list: false,
             cleanup: Vec::new(),
+            lift_errors,
+            result_owned: None,
+        }
+    }
+
+    /// Lowers the `Cow` export result `cow`, recording in `flag` whether it's
+    /// owned, in which case it's converted with `into_boxed` and leaked until
+    /// the post-return function frees it. Returns the pointer and length.
+    fn lower_borrowed_result(&mut self, cow: &str, flag: &str, into_boxed: &str) -> [String; 2] {
+        let tmp = self.tmp();
+        let ptr = format!("ptr{tmp}");
+        let len = format!("len{tmp}");
+        uwriteln!(
+            self.src,
+            "let ({ptr}, {len}) = match {cow} {{
+                wit_bindgen_guest_rust::rt::borrow::Cow::Borrowed(val) => {{
+                    {flag} = false;
+                    (val.as_ptr() as i32, val.len() as i32)
+                }}
+                wit_bindgen_guest_rust::rt::borrow::Cow::Owned(val) => {{
+                    let val = val.{into_boxed}();
+                    {flag} = true;
+                    let ret = (val.as_ptr() as i32, val.len() as i32);
+                    core::mem::forget(val);
+                    ret
+                }}
+            }};"
+        );
+        [ptr, len]
+    }
+
+    /// Returns the expression evaluated when an invalid value is lifted, where
+    /// `error` is the `LiftError` variant describing it and `msg` the message
+    /// to panic with by default.
+    fn invalid_lift(&self, msg: &str, error: &str) -> String {
+        match self.lift_errors {
+            LiftErrors::Panic => format!("panic!(\"{msg}\")"),
+            LiftErrors::Return => {
+                format!("return Err(wit_bindgen_guest_rust::LiftError::{error})")
+            }
+            LiftErrors::Handler => format!(
+                "wit_bindgen_guest_rust::rt::invalid_lift(wit_bindgen_guest_rust::LiftError::{error})"
+            ),
+        }
+    }
+
+    /// Returns the match arm taken by an invalid discriminant of the type
+    /// named `ty`.
+    fn invalid_discriminant(&self, msg: &str, ty: &str) -> String {
+        if self.lift_errors == LiftErrors::Panic {
+            return format!("_ => panic!(\"{msg}\"),\n");
+        }
+        let err = self.invalid_lift(
+            msg,
+            &format!("InvalidDiscriminant {{ ty: \"{ty}\", discriminant: n }}"),
+        );
+        format!("n => {err},\n")
+    }
+
+    fn type_name(&self, ty: TypeId, default: &str) -> String {
+        match &self.gen.iface.types[ty].name {
+            Some(name) => name.clone(),
+            None => default.to_string(),
         }
     }
 
@@ -784,7 +1260,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
         operands: &mut Vec<String>,
         results: &mut Vec<String>,
     ) {
-        let unchecked = self.gen.gen.opts.unchecked;
+        let unchecked = self.gen.gen.opts.unchecked && !self.gen.gen.opts.validate_lifts;
         let mut top_as = |cvt: &str| {
             let mut s = operands.pop().unwrap();
             s.push_str(" as ");
@@ -847,11 +1323,23 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                         "core::char::from_u32_unchecked({} as u32)",
                         operands[0]
                     ));
-                } else {
+                } else if self.lift_errors == LiftErrors::Panic {
                     results.push(format!(
                         "core::char::from_u32({} as u32).unwrap()",
                         operands[0]
                     ));
+                } else {
+                    let err = self.invalid_lift("", "InvalidChar(n)");
+                    results.push(format!(
+                        "{{
+                            let n = {} as u32;
+                            match core::char::from_u32(n) {{
+                                Some(c) => c,
+                                None => {err},
+                            }}
+                        }}",
+                        operands[0]
+                    ));
                 }
             }
 
@@ -869,11 +1357,16 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                         operands[0],
                     ));
                 } else {
+                    let invalid = if self.lift_errors == LiftErrors::Panic {
+                        "_ => panic!(\"invalid bool discriminant\")".to_string()
+                    } else {
+                        format!("n => {}", self.invalid_lift("", "InvalidBool(n)"))
+                    };
                     results.push(format!(
                         "match {} {{
                             0 => false,
                             1 => true,
-                            _ => panic!(\"invalid bool discriminant\"),
+                            {invalid},
                         }}",
                         operands[0],
                     ));
@@ -887,9 +1380,42 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     results.push(format!("(flags{}.bits() >> {}) as i32", tmp, i * 32));
                 }
             }
-            Instruction::FlagsLift { name, flags, .. } => {
+            Instruction::FlagsLift { flags, ty, .. } if self.lift_errors != LiftErrors::Panic => {
+                let repr = RustFlagsRepr::new(flags);
+                let name = self.typename_lift(*ty);
+                // Fewer than 32 flags are represented by an `i32` whose
+                // unused high bits must be zero too, while others are
+                // split into `i32`s which mustn't be sign-extended.
+                let flags = match repr {
+                    RustFlagsRepr::U8 | RustFlagsRepr::U16 => format!(
+                        "<{repr} as core::convert::TryFrom<i32>>::try_from({}).ok().and_then({name}::from_bits)",
+                        operands[0]
+                    ),
+                    _ => {
+                        let mut bits = String::from("0");
+                        for (i, op) in operands.iter().enumerate() {
+                            bits.push_str(&format!(" | (({op} as u32 as {repr}) << {})", i * 32));
+                        }
+                        format!("{name}::from_bits({bits})")
+                    }
+                };
+                let err = self.invalid_lift(
+                    "",
+                    &format!(
+                        "InvalidFlags {{ ty: \"{}\" }}",
+                        self.type_name(*ty, "flags")
+                    ),
+                );
+                results.push(format!(
+                    "match {flags} {{
+                        Some(flags) => flags,
+                        None => {err},
+                    }}"
+                ));
+            }
+            Instruction::FlagsLift { flags, ty, .. } => {
                 let repr = RustFlagsRepr::new(flags);
-                let name = name.to_upper_camel_case();
+                let name = self.typename_lift(*ty);
                 let mut result = format!("{}::empty()", name);
                 for (i, op) in operands.iter().enumerate() {
                     result.push_str(&format!(
@@ -946,8 +1472,10 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             // In unchecked mode when this type is a named enum then we know we
             // defined the type so we can transmute directly into it.
-            Instruction::VariantLift { name, variant, .. }
-                if variant.cases.iter().all(|c| c.ty.is_none()) && unchecked =>
+            Instruction::VariantLift { name, variant, ty }
+                if variant.cases.iter().all(|c| c.ty.is_none())
+                    && unchecked
+                    && self.gen.mapped_type(*ty).is_none() =>
             {
                 self.blocks.drain(self.blocks.len() - variant.cases.len()..);
                 let mut result = format!("core::mem::transmute::<_, ");
@@ -983,7 +1511,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     result.push_str(&format!("{pat} => {name}::{case}{block},\n"));
                 }
                 if !unchecked {
-                    result.push_str("_ => panic!(\"invalid enum discriminant\"),\n");
+                    let ty = self.type_name(*ty, "variant");
+                    result.push_str(&self.invalid_discriminant("invalid enum discriminant", &ty));
                 }
                 result.push_str("}");
                 results.push(result);
@@ -1032,7 +1561,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     result.push_str(&format!("{pat} => {name}::{case_name}({block}),\n"));
                 }
                 if !unchecked {
-                    result.push_str("_ => panic!(\"invalid union discriminant\"),\n");
+                    let ty = self.type_name(*ty, "union");
+                    result.push_str(&self.invalid_discriminant("invalid union discriminant", &ty));
                 }
                 result.push_str("}");
                 results.push(result);
@@ -1054,21 +1584,22 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 ));
             }
 
-            Instruction::OptionLift { .. } => {
+            Instruction::OptionLift { ty, .. } => {
                 let some = self.blocks.pop().unwrap();
                 let none = self.blocks.pop().unwrap();
                 assert_eq!(none, "()");
                 let operand = &operands[0];
                 let invalid = if unchecked {
-                    "core::hint::unreachable_unchecked()"
+                    "_ => core::hint::unreachable_unchecked(),\n".to_string()
                 } else {
-                    "panic!(\"invalid enum discriminant\")"
+                    let ty = self.type_name(*ty, "option");
+                    self.invalid_discriminant("invalid enum discriminant", &ty)
                 };
                 results.push(format!(
                     "match {operand} {{
                         0 => None,
                         1 => Some({some}),
-                        _ => {invalid},
+                        {invalid}
                     }}"
                 ));
             }
@@ -1092,27 +1623,28 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 ));
             }
 
-            Instruction::ResultLift { .. } => {
+            Instruction::ResultLift { ty, .. } => {
                 let err = self.blocks.pop().unwrap();
                 let ok = self.blocks.pop().unwrap();
                 let operand = &operands[0];
                 let invalid = if unchecked {
-                    "core::hint::unreachable_unchecked()"
+                    "_ => core::hint::unreachable_unchecked(),\n".to_string()
                 } else {
-                    "panic!(\"invalid enum discriminant\")"
+                    let ty = self.type_name(*ty, "result");
+                    self.invalid_discriminant("invalid enum discriminant", &ty)
                 };
                 results.push(format!(
                     "match {operand} {{
                         0 => Ok({ok}),
                         1 => Err({err}),
-                        _ => {invalid},
+                        {invalid}
                     }}"
                 ));
             }
 
-            Instruction::EnumLower { enum_, name, .. } => {
+            Instruction::EnumLower { enum_, ty, .. } => {
                 let mut result = format!("match {} {{\n", operands[0]);
-                let name = name.to_upper_camel_case();
+                let name = self.typename_lower(*ty);
                 for (i, case) in enum_.cases.iter().enumerate() {
                     let case = case.name.to_upper_camel_case();
                     result.push_str(&format!("{name}::{case} => {i},\n"));
@@ -1123,7 +1655,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             // In unchecked mode when this type is a named enum then we know we
             // defined the type so we can transmute directly into it.
-            Instruction::EnumLift { enum_, name, .. } if unchecked => {
+            Instruction::EnumLift { enum_, name, ty }
+                if unchecked && self.gen.mapped_type(*ty).is_none() =>
+            {
                 let mut result = format!("core::mem::transmute::<_, ");
                 result.push_str(&name.to_upper_camel_case());
                 result.push_str(">(");
@@ -1134,21 +1668,27 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results.push(result);
             }
 
-            Instruction::EnumLift { enum_, name, .. } => {
+            Instruction::EnumLift { enum_, ty, .. } => {
                 let mut result = format!("match ");
                 result.push_str(&operands[0]);
                 result.push_str(" {\n");
-                let name = name.to_upper_camel_case();
+                let name = self.typename_lift(*ty);
                 for (i, case) in enum_.cases.iter().enumerate() {
                     let case = case.name.to_upper_camel_case();
                     result.push_str(&format!("{i} => {name}::{case},\n"));
                 }
-                result.push_str("_ => panic!(\"invalid enum discriminant\"),\n");
+                let ty = self.type_name(*ty, "enum");
+                result.push_str(&self.invalid_discriminant("invalid enum discriminant", &ty));
                 result.push_str("}");
                 results.push(result);
             }
 
             Instruction::ListCanonLower { realloc, .. } => {
+                if let (Some(_), Some(flag)) = (realloc, self.result_owned.clone()) {
+                    let cow = self.lower_borrowed_result(&operands[0], &flag, "into_boxed_slice");
+                    results.extend(cow);
+                    return;
+                }
                 let tmp = self.tmp();
                 let val = format!("vec{}", tmp);
                 let ptr = format!("ptr{}", tmp);
@@ -1180,6 +1720,16 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
 
             Instruction::StringLower { realloc } => {
+                if let (Some(_), Some(flag)) = (realloc, self.result_owned.clone()) {
+                    let into_boxed = if self.gen.gen.opts.raw_strings {
+                        "into_boxed_slice"
+                    } else {
+                        "into_boxed_str"
+                    };
+                    let cow = self.lower_borrowed_result(&operands[0], &flag, into_boxed);
+                    results.extend(cow);
+                    return;
+                }
                 let tmp = self.tmp();
                 let val = format!("vec{}", tmp);
                 let ptr = format!("ptr{}", tmp);
@@ -1212,7 +1762,17 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 } else if unchecked {
                     results.push(format!("String::from_utf8_unchecked({})", result));
                 } else {
-                    results.push(format!("String::from_utf8({}).unwrap()", result));
+                    if self.lift_errors == LiftErrors::Panic {
+                        results.push(format!("String::from_utf8({}).unwrap()", result));
+                    } else {
+                        let err = self.invalid_lift("", "InvalidUtf8");
+                        results.push(format!(
+                            "match String::from_utf8({result}) {{
+                                Ok(s) => s,
+                                Err(_) => {err},
+                            }}"
+                        ));
+                    }
                 }
             }
 
@@ -1276,6 +1836,14 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 self.push_str(&format!(
                     "let mut {result} = Vec::with_capacity({len} as usize);\n",
                 ));
+                // Invalid elements return early, so the buffer is freed by a
+                // guard instead of after the loop.
+                let guarded = self.lift_errors == LiftErrors::Return;
+                if guarded {
+                    self.push_str(&format!(
+                        "let _dealloc{tmp} = wit_bindgen_guest_rust::rt::Dealloc::new({base}, ({len} as usize) * {size}, {align});\n",
+                    ));
+                }
 
                 self.push_str("for i in 0..");
                 self.push_str(&len);
@@ -1291,9 +1859,11 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 self.push_str(");\n");
                 self.push_str("}\n");
                 results.push(result);
-                self.push_str(&format!(
-                    "wit_bindgen_guest_rust::rt::dealloc({base}, ({len} as usize) * {size}, {align});\n",
-                ));
+                if !guarded {
+                    self.push_str(&format!(
+                        "wit_bindgen_guest_rust::rt::dealloc({base}, ({len} as usize) * {size}, {align});\n",
+                    ));
+                }
             }
 
             Instruction::IterElem { .. } => results.push("e".to_string()),
@@ -1317,6 +1887,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::CallInterface { func, .. } => {
                 self.let_results(func.results.len(), results);
                 match &func.kind {
+                    FunctionKind::Freestanding if self.gen.gen.opts.stateful_exports => {
+                        self.push_str(&format!("this.{}", to_rust_ident(&func.name)));
+                    }
                     FunctionKind::Freestanding => {
                         self.push_str(&format!("T::{}", func.name.to_snake_case()));
                     }
@@ -1329,17 +1902,20 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::Return { amt, .. } => {
                 self.emit_cleanup();
-                match amt {
-                    0 => {}
-                    1 => {
-                        self.push_str(&operands[0]);
-                        self.push_str("\n");
+                let ret = match amt {
+                    0 => None,
+                    1 => Some(operands[0].clone()),
+                    _ => Some(format!("({})", operands.join(", "))),
+                };
+                match ret {
+                    Some(ret) if self.lift_errors == LiftErrors::Return => {
+                        self.push_str(&format!("Ok({ret})\n"));
                     }
-                    _ => {
-                        self.push_str("(");
-                        self.push_str(&operands.join(", "));
-                        self.push_str(")\n");
+                    Some(ret) => {
+                        self.push_str(&ret);
+                        self.push_str("\n");
                     }
+                    None => {}
                 }
             }
 
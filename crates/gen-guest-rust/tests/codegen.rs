@@ -174,3 +174,311 @@ mod macro_name {
 
     jam!(Component);
 }
+
+// This is a static compilation test to check that only the selected subset of
+// an interface's functions and types are generated.
+mod filtered {
+    wit_bindgen_guest_rust::generate!({
+        export_str["exports3"]: "
+            record point { x: u32, y: u32 }
+            enum color { red, green }
+            record unused { c: color }
+
+            foo: func(p: point)
+            bar: func() -> color
+            baz: func(u: unused)
+        ",
+        name: "baz",
+        allow_functions: ["exports3#foo", "exports3#bar"],
+        deny_functions: ["exports3#bar"],
+        allow_types: ["exports3#color"],
+    });
+
+    struct Component;
+
+    // Only `foo` needs to be implemented, and `unused` isn't generated since
+    // no selected function uses it.
+    impl exports3::Exports3 for Component {
+        fn foo(p: exports3::Point) {
+            let _ = exports3::Color::Red;
+            println!("foo: {} {}", p.x, p.y);
+        }
+    }
+
+    export_baz!(Component);
+}
+
+// This is a static compilation test to check that additional derives and
+// attributes are added to generated types.
+mod derives {
+    wit_bindgen_guest_rust::generate!({
+        import_str["cat"]: "
+            record point { x: u32, y: u32 }
+            variant shape { dot(point), empty }
+            enum color { red, green }
+
+            foo: func(p: point, s: shape, c: color)
+        ",
+        name: "not-used-name",
+        additional_derives: [Hash, PartialEq, Eq],
+        type_attributes: {
+            "point": #[non_exhaustive],
+            "cat#color": #[allow(dead_code)] #[doc = "A color."],
+        },
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let mut shapes = std::collections::HashSet::new();
+        shapes.insert(cat::Shape::Empty);
+        let mut colors = std::collections::HashSet::new();
+        colors.insert(cat::Color::Red);
+        assert!(cat::Point { x: 0, y: 0 } == cat::Point { x: 0, y: 0 });
+    }
+}
+
+// This is a static compilation test to check that existing Rust types can be
+// used in place of generated types.
+mod with {
+    pub struct MyPoint {
+        pub x: u32,
+        pub y: u32,
+    }
+
+    wit_bindgen_guest_rust::generate!({
+        import_str["cat"]: "
+            record point { x: u32, y: u32 }
+            enum color { red, green }
+
+            foo: func(p: point, c: color) -> point
+        ",
+        name: "not-used-name",
+        with: { "cat.point": super::MyPoint },
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let _p: MyPoint = cat::foo(MyPoint { x: 1, y: 2 }, cat::Color::Red);
+    }
+}
+
+// This is a static compilation test to check that exports can be implemented
+// with `&mut self` methods of a single stateful instance.
+mod stateful {
+    wit_bindgen_guest_rust::generate!({
+        export_str["counter"]: "
+            increment: func(by: u32) -> u32
+            name: func() -> string
+        ",
+        name: "stateful",
+        stateful_exports,
+    });
+
+    struct Counter {
+        count: u32,
+    }
+
+    impl counter::Counter for Counter {
+        fn increment(&mut self, by: u32) -> u32 {
+            self.count += by;
+            self.count
+        }
+
+        fn name(&mut self) -> String {
+            format!("counter at {}", self.count)
+        }
+    }
+
+    export_stateful!(Counter, Counter { count: 0 });
+}
+
+mod native {
+    wit_bindgen_guest_rust::generate!({
+        import_str["host"]: "
+            log: func(msg: string)
+            add: func(a: u32, b: u32) -> u32
+        ",
+        export_str["guest"]: "
+            run: func() -> u32
+        ",
+        name: "native",
+        native_testing,
+    });
+
+    struct Component;
+
+    impl guest::Guest for Component {
+        fn run() -> u32 {
+            host::log("running");
+            host::add(1, 2)
+        }
+    }
+
+    export_native!(Component);
+
+    #[derive(Default)]
+    struct Host {
+        logs: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl host::HostMock for Host {
+        fn log(&mut self, msg: &str) {
+            self.logs.borrow_mut().push(msg.to_string());
+        }
+
+        fn add(&mut self, a: u32, b: u32) -> u32 {
+            a + b
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_with_mock() {
+        let host = Host::default();
+        let logs = host.logs.clone();
+        host::set_mock(host);
+        assert_eq!(<Component as guest::Guest>::run(), 3);
+        assert_eq!(*logs.borrow(), ["running"]);
+    }
+}
+
+mod validate_lifts {
+    wit_bindgen_guest_rust::generate!({
+        import_str["host"]: "
+            enum color { red, green }
+            flags perms { read, write }
+            variant shape { circle(f32), square(f32) }
+            union number { u32, f64 }
+
+            get-char: func() -> char
+            get-bool: func() -> bool
+            get-color: func() -> color
+            get-perms: func() -> perms
+            get-shape: func() -> option<shape>
+            get-number: func() -> result<number, string>
+            get-list: func() -> list<string>
+            get-colors: func() -> list<color>
+
+            flags many {
+                f0, f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16, f17, f18, f19,
+                f20, f21, f22, f23, f24, f25, f26, f27, f28, f29, f30, f31, f32, f33, f34, f35, f36, f37, f38, f39
+            }
+            get-many: func() -> many
+        ",
+        export_str["guest"]: "
+            set-color: func(c: char, color: color) -> string
+        ",
+        name: "validate-lifts",
+        validate_lifts,
+    });
+
+    struct Component;
+
+    impl guest::Guest for Component {
+        fn set_color(c: char, color: guest::Color) -> String {
+            format!("{c} {color:?}")
+        }
+    }
+
+    export_validate_lifts!(Component);
+
+    fn handler(err: wit_bindgen_guest_rust::LiftError) -> ! {
+        panic!("invalid value from the host: {err}")
+    }
+
+    #[allow(dead_code)]
+    fn use_imports() -> Result<(), wit_bindgen_guest_rust::LiftError> {
+        wit_bindgen_guest_rust::set_lift_error_handler(handler);
+        let _: char = host::try_get_char()?;
+        let _: bool = host::try_get_bool()?;
+        let _: host::Color = host::try_get_color()?;
+        let _: host::Perms = host::try_get_perms()?;
+        let _: Option<host::Shape> = host::try_get_shape()?;
+        let _: Result<host::Number, String> = host::try_get_number()?;
+        let _: Vec<String> = host::try_get_list()?;
+        let _: Vec<host::Color> = host::try_get_colors()?;
+        let _: host::Many = host::try_get_many()?;
+        let _: host::Color = host::get_color();
+        Ok(())
+    }
+}
+
+mod borrowed_export_results {
+    use std::borrow::Cow;
+
+    wit_bindgen_guest_rust::generate!({
+        export_str["guest"]: "
+            greeting: func() -> string
+            name: func(owned: bool) -> string
+            data: func() -> list<u8>
+            samples: func() -> list<f32>
+            names: func() -> list<string>
+        ",
+        name: "borrowed-export-results",
+        borrowed_export_results,
+    });
+
+    struct Component;
+
+    impl guest::Guest for Component {
+        fn greeting() -> Cow<'static, str> {
+            Cow::Borrowed("hello")
+        }
+
+        fn name(owned: bool) -> Cow<'static, str> {
+            if owned {
+                Cow::Owned("name".to_string())
+            } else {
+                Cow::Borrowed("name")
+            }
+        }
+
+        fn data() -> Cow<'static, [u8]> {
+            Cow::Borrowed(&[1, 2, 3])
+        }
+
+        fn samples() -> Cow<'static, [f32]> {
+            Cow::Owned(vec![1.0, 2.0])
+        }
+
+        fn names() -> Vec<String> {
+            vec!["a".to_string()]
+        }
+    }
+
+    export_borrowed_export_results!(Component);
+}
+
+mod world_dir {
+    wit_bindgen_guest_rust::generate!("tests/wit");
+
+    struct Component;
+
+    impl greeter::Greeter for Component {
+        fn greet(name: String) -> String {
+            let text = format!("hello {name}");
+            console::log(console::Message { text: &text });
+            text
+        }
+    }
+
+    export_greeter!(Component);
+}
+
+mod world_file {
+    wit_bindgen_guest_rust::generate!({
+        path: "tests/wit/world.wit",
+        world: "greeter",
+        name: "world-file",
+    });
+
+    struct Component;
+
+    impl greeter::Greeter for Component {
+        fn greet(name: String) -> String {
+            name
+        }
+    }
+
+    export_world_file!(Component);
+}
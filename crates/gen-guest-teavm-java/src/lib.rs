@@ -26,8 +26,9 @@ pub struct TeaVmJava {
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "clap", derive(clap::Args))]
 pub struct Opts {
-    /// Whether or not to generate a stub class for exported functions
-    #[cfg_attr(feature = "clap", arg(long))]
+    /// Whether or not to generate a stub class for exported functions, which
+    /// is never overwritten once it exists.
+    #[cfg_attr(feature = "clap", arg(long, alias = "stubs"))]
     pub generate_stub: bool,
 }
 
@@ -141,23 +142,7 @@ impl TeaVmJava {
     }
 
     fn print_docs(&mut self, docs: &Docs) {
-        if let Some(docs) = &docs.contents {
-            let lines = docs
-                .trim()
-                .lines()
-                .map(|line| format!("* {line}"))
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            uwrite!(
-                self.src,
-                "
-                /**
-                 {lines}
-                 */
-                "
-            )
-        }
+        self.src.push_str(&docs_string(docs));
     }
 
     fn non_empty_type<'a>(&self, iface: &'a Interface, ty: Option<&'a Type>) -> Option<&'a Type> {
@@ -731,10 +716,12 @@ impl Generator for TeaVmJava {
         if self.opts.generate_stub {
             let class = iface.name.to_upper_camel_case();
             let sig = self.sig_string(iface, func, Some(&format!("{class}.")));
+            let docs = docs_string(&func.docs);
 
             uwrite!(
                 self.stub,
                 r#"
+                {docs}
                 {sig} {{
                     throw new RuntimeException("todo");
                 }}
@@ -872,7 +859,7 @@ impl Generator for TeaVmJava {
         if self.opts.generate_stub {
             self.stub.push_str("}\n");
 
-            files.push(
+            files.push_stub(
                 &format!("{}Impl.java", iface.name.to_upper_camel_case()),
                 indent(&self.stub).as_bytes(),
             );
@@ -880,6 +867,27 @@ impl Generator for TeaVmJava {
     }
 }
 
+fn docs_string(docs: &Docs) -> String {
+    match &docs.contents {
+        Some(docs) => {
+            let lines = docs
+                .trim()
+                .lines()
+                .map(|line| format!("* {line}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "
+                /**
+                 {lines}
+                 */
+                "
+            )
+        }
+        None => String::new(),
+    }
+}
+
 struct Block {
     body: String,
     results: Vec<String>,
@@ -1,3 +1,4 @@
+use anyhow::{bail, Result};
 use heck::*;
 use std::collections::BTreeMap;
 use std::fmt::Write as _;
@@ -17,6 +18,10 @@ struct Wasmtime {
     opts: Opts,
     imports: Vec<String>,
     exports: Exports,
+    stubs: Source,
+    /// The implementation of the trait of the default export by the world's
+    /// struct, with `traits_module`.
+    default_export_impl: Option<String>,
 }
 
 #[derive(Default)]
@@ -36,9 +41,181 @@ pub struct Opts {
     #[cfg_attr(feature = "clap", arg(long))]
     pub tracing: bool,
 
-    /// Whether or not to use async rust functions and traits.
-    #[cfg_attr(feature = "clap", arg(long = "async"))]
-    pub async_: bool,
+    /// Whether or not to use async rust functions and traits, and for which
+    /// imports: `all`, the default with no value, `only:FUNCTION,..` or
+    /// `except:FUNCTION,..`, where functions may be qualified as
+    /// `INTERFACE#FUNCTION`.
+    ///
+    /// Exports and instantiation are async regardless of the imports
+    /// selected, as wasmtime requires of async stores.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "async",
+            value_name = "MODE",
+            value_parser = parse_async,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "all"
+        )
+    )]
+    pub async_: Option<Async>,
+
+    /// Additional traits to derive for all generated records, variants,
+    /// unions and enums, for example `Hash` or `serde::Serialize`. Can be
+    /// specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(long = "additional-derive", value_name = "DERIVE")
+    )]
+    pub additional_derives: Vec<String>,
+
+    /// Additional attributes to add to the generated type of a WIT type,
+    /// given as `TYPE=ATTRIBUTE` where `TYPE` may be qualified as
+    /// `INTERFACE#TYPE`, for example `point=#[non_exhaustive]`. Can be
+    /// specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "type-attribute",
+            value_name = "TYPE=ATTRIBUTE",
+            value_parser = wit_bindgen_gen_rust_lib::parse_type_attribute
+        )
+    )]
+    pub type_attributes: Vec<(String, String)>,
+
+    /// Existing Rust types to use in place of generating WIT types, given as
+    /// `MODULE.TYPE=PATH`, for example `types.timestamp=crate::Timestamp`.
+    ///
+    /// `MODULE` is the interface which defines the type, so every interface
+    /// which `use`s the type refers to the same Rust type. The Rust type must
+    /// have the same shape as the type which would otherwise be generated.
+    /// Can be specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "with",
+            value_name = "MODULE.TYPE=PATH",
+            value_parser = wit_bindgen_gen_rust_lib::parse_with
+        )
+    )]
+    pub with: Vec<(String, String)>,
+
+    /// If true, also generates `{name}_stubs.rs`, a skeleton implementing
+    /// the trait of every import with `todo!()` for filling in. Unlike the
+    /// bindings it's never overwritten once it exists.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub stubs: bool,
+
+    /// If true, generates only the types and traits of the world without any
+    /// references to `wasmtime`, for use in crates which shouldn't depend on
+    /// it.
+    ///
+    /// Imports get their usual trait, and exports get an `{Interface}Exports`
+    /// trait implemented by bindings generated with `traits_module`. The
+    /// `wasmtime` traits which those bindings require of types are only
+    /// derived with `traits_cfg`. Flags aren't supported yet unless mapped to
+    /// existing types with `with`, and async traits use the `async-trait`
+    /// crate directly.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub traits_only: bool,
+
+    /// The `cfg` predicate, for example `feature = "wasmtime"`, under which
+    /// the types generated with `traits_only` derive the `wasmtime` traits
+    /// required by bindings generated with `traits_module`. Without it the
+    /// traits aren't derived.
+    #[cfg_attr(
+        feature = "clap",
+        arg(long, value_name = "PREDICATE", requires = "traits_only")
+    )]
+    pub traits_cfg: Option<String>,
+
+    /// The path of the bindings generated with `traits_only`, for example
+    /// `my_crate::bindings`, which are used instead of generating the types
+    /// and traits of the world again.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub traits_module: Option<String>,
+
+    /// If true, exports return a `wit_bindgen_host_wasmtime_rust::CallError`
+    /// instead of an `anyhow::Error`, which records the export called and
+    /// classifies why it failed, for example telling a guest panic apart
+    /// from an error returned by a host import.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub call_errors: bool,
+
+    /// Host error types for WIT error types returned by imports, given as
+    /// `MODULE.TYPE=RUST_TYPE,CONVERT` like the types of `with`, for example
+    /// `fs.errno=std::io::Error,crate::to_errno`.
+    ///
+    /// Imports returning `result<T, TYPE>` return `Result<T, RUST_TYPE>`
+    /// instead, so implementations can use `?` on host errors. `CONVERT` is a
+    /// function taking a `RUST_TYPE` and returning an `anyhow::Result<TYPE>`,
+    /// which converts errors to the WIT type, or traps by returning an error.
+    /// Can be specified multiple times.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long = "error-type",
+            value_name = "MODULE.TYPE=RUST_TYPE,CONVERT",
+            value_parser = parse_error_type
+        )
+    )]
+    pub error_types: Vec<(String, String, String)>,
+}
+
+/// Which imports are async, for bindings which use async.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Async {
+    /// All imports are async.
+    All,
+    /// Only the listed imports are async, and all others are sync.
+    Only(Vec<String>),
+    /// All imports but the listed ones are async.
+    Except(Vec<String>),
+}
+
+/// Parses the `all`, `only:FUNCTION,..` or `except:FUNCTION,..` mode of the
+/// `--async` flag.
+pub fn parse_async(s: &str) -> Result<Async, String> {
+    let names = |names: &str| {
+        names
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    if s == "all" {
+        Ok(Async::All)
+    } else if let Some(only) = s.strip_prefix("only:") {
+        Ok(Async::Only(names(only)))
+    } else if let Some(except) = s.strip_prefix("except:") {
+        Ok(Async::Except(names(except)))
+    } else {
+        Err(format!(
+            "expected `all`, `only:FUNCTION,..` or `except:FUNCTION,..`, but found `{s}`"
+        ))
+    }
+}
+
+/// Parses a `MODULE.TYPE=RUST_TYPE,CONVERT` mapping of a WIT error type, as
+/// accepted by the `--error-type` flag.
+///
+/// `RUST_TYPE` is split from `CONVERT` at the last comma since it may contain
+/// commas itself, for example in generic arguments.
+pub fn parse_error_type(s: &str) -> Result<(String, String, String), String> {
+    match s.split_once('=').and_then(|(ty, rest)| {
+        let (rust_ty, convert) = rest.rsplit_once(',')?;
+        Some((ty, rust_ty, convert))
+    }) {
+        Some((ty, rust_ty, convert))
+            if ty.contains('.') && !rust_ty.is_empty() && !convert.is_empty() =>
+        {
+            Ok((ty.to_string(), rust_ty.to_string(), convert.to_string()))
+        }
+        _ => Err(format!(
+            "expected `MODULE.TYPE=RUST_TYPE,CONVERT`, for example `fs.errno=std::io::Error,crate::to_errno`, but found `{s}`"
+        )),
+    }
 }
 
 impl Opts {
@@ -47,40 +224,236 @@ impl Opts {
         r.opts = self;
         Box::new(r)
     }
+
+    /// Whether the bindings use async at all, which makes exports and
+    /// instantiation async.
+    fn is_async(&self) -> bool {
+        self.async_.is_some()
+    }
+
+    /// Whether the import `func` of the interface `iface` is async.
+    fn is_async_import(&self, iface: &Interface, func: &Function) -> bool {
+        let matches = |names: &[String]| {
+            let qualified = format!("{}#{}", iface.name, func.name);
+            names.iter().any(|n| *n == func.name || *n == qualified)
+        };
+        match &self.async_ {
+            None => false,
+            Some(Async::All) => true,
+            Some(Async::Only(names)) => matches(names),
+            Some(Async::Except(names)) => !matches(names),
+        }
+    }
+
+    /// Whether the types and traits are separate from the `wasmtime` glue,
+    /// which means they can't refer to `wit_bindgen_host_wasmtime_rust`.
+    fn split_traits(&self) -> bool {
+        self.traits_only || self.traits_module.is_some()
+    }
 }
 
-impl WorldGenerator for Wasmtime {
-    fn import(&mut self, name: &str, iface: &Interface, _files: &mut Files) {
-        let mut gen = InterfaceGenerator::new(self, iface, TypeMode::Owned);
-        gen.types();
-        gen.generate_from_error_impls();
-        gen.generate_add_to_linker(name);
+impl Wasmtime {
+    /// Generates the struct of the world, through which it's instantiated and
+    /// its exports are called.
+    fn generate_world_struct(&mut self, name: &str) {
+        let camel = name.to_upper_camel_case();
+        uwriteln!(self.src, "pub struct {camel} {{");
+        for (name, (ty, _)) in self.exports.fields.iter() {
+            uwriteln!(self.src, "{name}: {ty},");
+        }
+        self.src.push_str("}\n");
 
-        let snake = name.to_snake_case();
-        let module = &gen.src[..];
+        let (async_, async__, send, await_) = if self.opts.is_async() {
+            ("async", "_async", ":Send", ".await")
+        } else {
+            ("", "", "", "")
+        };
 
+        uwriteln!(
+            self.src,
+            "
+                impl {camel} {{
+                    /// Instantiates the provided `module` using the specified
+                    /// parameters, wrapping up the result in a structure that
+                    /// translates between wasm and the host.
+                    pub {async_} fn instantiate{async__}<T {send}>(
+                        mut store: impl wasmtime::AsContextMut<Data = T>,
+                        component: &wasmtime::component::Component,
+                        linker: &wasmtime::component::Linker<T>,
+                    ) -> anyhow::Result<(Self, wasmtime::component::Instance)> {{
+                        let instance = linker.instantiate{async__}(&mut store, component){await_}?;
+                        Ok((Self::new(store, &instance)?, instance))
+                    }}
+
+                    /// Low-level creation wrapper for wrapping up the exports
+                    /// of the `instance` provided in this structure of wasm
+                    /// exports.
+                    ///
+                    /// This function will extract exports from the `instance`
+                    /// defined within `store` and wrap them all up in the
+                    /// returned structure which can be used to interact with
+                    /// the wasm module.
+                    pub fn new(
+                        mut store: impl wasmtime::AsContextMut,
+                        instance: &wasmtime::component::Instance,
+                    ) -> anyhow::Result<Self> {{
+                        let mut store = store.as_context_mut();
+                        let mut exports = instance.exports(&mut store);
+                        let mut exports = exports.root();
+            ",
+        );
+        for (name, (_, get)) in self.exports.fields.iter() {
+            uwriteln!(self.src, "let {name} = {get};");
+        }
+        uwriteln!(self.src, "Ok({camel} {{");
+        for (name, _) in self.exports.fields.iter() {
+            uwriteln!(self.src, "{name},");
+        }
+        uwriteln!(self.src, "}})");
+        uwriteln!(self.src, "}}");
+
+        for func in self.exports.funcs.iter() {
+            self.src.push_str(func);
+        }
+
+        uwriteln!(self.src, "}}");
+        if let Some(export_impl) = self.default_export_impl.take() {
+            self.src.push_str(&export_impl);
+        }
+    }
+
+    /// Appends the module `snake` containing `module` to the bindings.
+    fn push_module(&mut self, snake: &str, module: &str) {
+        let imports = if self.opts.traits_only {
+            ""
+        } else {
+            "#[allow(unused_imports)]
+            use wit_bindgen_host_wasmtime_rust::{wasmtime, anyhow};"
+        };
         uwriteln!(
             self.src,
             "
                 #[allow(clippy::all)]
                 pub mod {snake} {{
-                    #[allow(unused_imports)]
-                    use wit_bindgen_host_wasmtime_rust::{{wasmtime, anyhow}};
+                    {imports}
 
                     {module}
                 }}
             "
         );
+    }
+}
+
+impl WorldGenerator for Wasmtime {
+    fn validate(&self, interfaces: &ComponentInterfaces) -> Result<()> {
+        let all = || {
+            interfaces
+                .imports
+                .values()
+                .chain(interfaces.exports.values())
+                .chain(interfaces.default.as_ref())
+        };
+        if self.opts.traits_cfg.is_some() && !self.opts.traits_only {
+            bail!("`traits_cfg` only applies with `traits_only`");
+        }
+        if self.opts.traits_only {
+            for iface in all() {
+                for (_, ty) in iface.types.iter() {
+                    let name = match (&ty.kind, &ty.name) {
+                        (TypeDefKind::Flags(_), Some(name)) => name,
+                        _ => continue,
+                    };
+                    let module = ty.foreign_module.as_deref().unwrap_or(&iface.name);
+                    let key = format!("{module}.{name}");
+                    if self.opts.with.iter().any(|(ty, _)| *ty == key) {
+                        continue;
+                    }
+                    bail!(
+                        "flags aren't supported with `traits_only`, but `{}` of interface `{}` \
+                         is flags; map it to an existing type with `with` instead",
+                        name,
+                        iface.name
+                    );
+                }
+            }
+        }
+
+        let async_names = match &self.opts.async_ {
+            Some(Async::Only(names) | Async::Except(names)) => &names[..],
+            None | Some(Async::All) => &[],
+        };
+        for name in async_names {
+            let known = interfaces.imports.values().any(|iface| {
+                iface.functions.iter().any(|func| {
+                    *name == func.name || *name == format!("{}#{}", iface.name, func.name)
+                })
+            });
+            if !known {
+                bail!("no imported function named `{name}` to select as async or not");
+            }
+        }
+
+        for (name, _, _) in self.opts.error_types.iter() {
+            let known = interfaces.imports.values().any(|iface| {
+                iface.types.iter().any(|(_, ty)| match &ty.name {
+                    Some(ty_name) => {
+                        let module = ty.foreign_module.as_deref().unwrap_or(&iface.name);
+                        *name == format!("{module}.{ty_name}")
+                    }
+                    None => false,
+                })
+            });
+            if !known {
+                bail!("no type of an imported interface named `{name}` to map with `error_types`");
+            }
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, name: &str, iface: &Interface, _files: &mut Files) {
+        let snake = name.to_snake_case();
+        let mut gen = InterfaceGenerator::new(self, iface, TypeMode::Owned);
+        if let Some(path) = &gen.gen.opts.traits_module {
+            uwriteln!(gen.src, "pub use {path}::{snake}::*;");
+        } else {
+            gen.types();
+            if !gen.gen.opts.traits_only {
+                gen.generate_from_error_impls();
+            }
+            gen.generate_import_trait(name);
+        }
+        if !gen.gen.opts.traits_only {
+            gen.generate_add_to_linker(name);
+        }
+        if gen.gen.opts.stubs {
+            gen.generate_stubs(name);
+        }
+
+        let module = mem::take(&mut gen.src);
+        self.push_module(&snake, &module);
 
         self.imports.push(snake); // TODO
     }
 
     fn export(&mut self, name: &str, iface: &Interface, _files: &mut Files) {
+        let snake = name.to_snake_case();
+        let camel = name.to_upper_camel_case();
         let mut gen = InterfaceGenerator::new(self, iface, TypeMode::AllBorrowed("'a"));
-        gen.types();
-        gen.generate_from_error_impls();
+        if let Some(path) = &gen.gen.opts.traits_module {
+            uwriteln!(gen.src, "pub use {path}::{snake}::*;");
+        } else {
+            gen.types();
+            if !gen.gen.opts.traits_only {
+                gen.generate_from_error_impls();
+            }
+        }
+        if gen.gen.opts.traits_only {
+            gen.generate_export_trait(name);
+            let module = mem::take(&mut gen.src);
+            self.push_module(&snake, &module);
+            return;
+        }
 
-        let camel = name.to_upper_camel_case();
         uwriteln!(gen.src, "pub struct {camel} {{");
         for func in iface.functions.iter() {
             uwriteln!(
@@ -114,22 +487,13 @@ impl WorldGenerator for Wasmtime {
             gen.define_rust_guest_export(Some(name), func);
         }
         uwriteln!(gen.src, "}}");
+        if gen.gen.opts.traits_module.is_some() {
+            let export_impl = gen.generate_export_trait_impl(name, &camel);
+            gen.src.push_str(&export_impl);
+        }
 
-        let snake = name.to_snake_case();
-        let module = &gen.src[..];
-
-        uwriteln!(
-            self.src,
-            "
-                #[allow(clippy::all)]
-                pub mod {snake} {{
-                    #[allow(unused_imports)]
-                    use wit_bindgen_host_wasmtime_rust::{{wasmtime, anyhow}};
-
-                    {module}
-                }}
-            "
-        );
+        let module = mem::take(&mut gen.src);
+        self.push_module(&snake, &module);
 
         let getter = format!(
             "\
@@ -153,9 +517,19 @@ impl WorldGenerator for Wasmtime {
         ));
     }
 
-    fn export_default(&mut self, _name: &str, iface: &Interface, _files: &mut Files) {
+    fn export_default(&mut self, name: &str, iface: &Interface, _files: &mut Files) {
         let mut gen = InterfaceGenerator::new(self, iface, TypeMode::AllBorrowed("'a"));
-        gen.types();
+        if let Some(path) = &gen.gen.opts.traits_module {
+            uwriteln!(gen.src, "pub use {path}::*;");
+        } else {
+            gen.types();
+        }
+        if gen.gen.opts.traits_only {
+            gen.generate_export_trait(name);
+            let src = gen.src;
+            self.src.push_str(&src);
+            return;
+        }
         let fields = gen.extract_typed_functions();
         for (name, getter) in fields {
             let prev = gen
@@ -172,74 +546,43 @@ impl WorldGenerator for Wasmtime {
             let func = mem::replace(&mut gen.src, prev);
             gen.gen.exports.funcs.push(func.to_string());
         }
+        if gen.gen.opts.traits_module.is_some() {
+            // The default export is named after the world, as is its struct.
+            let camel = name.to_upper_camel_case();
+            gen.gen.default_export_impl = Some(gen.generate_export_trait_impl(name, &camel));
+        }
 
         let src = gen.src;
         self.src.push_str(&src);
     }
 
     fn finish(&mut self, name: &str, _interfaces: &ComponentInterfaces, files: &mut Files) {
-        let camel = name.to_upper_camel_case();
-        uwriteln!(self.src, "pub struct {camel} {{");
-        for (name, (ty, _)) in self.exports.fields.iter() {
-            uwriteln!(self.src, "{name}: {ty},");
-        }
-        self.src.push_str("}\n");
-
-        let (async_, async__, send, await_) = if self.opts.async_ {
-            ("async", "_async", ":Send", ".await")
-        } else {
-            ("", "", "", "")
-        };
-
-        uwriteln!(
-            self.src,
-            "
-                impl {camel} {{
-                    /// Instantiates the provided `module` using the specified
-                    /// parameters, wrapping up the result in a structure that
-                    /// translates between wasm and the host.
-                    pub {async_} fn instantiate{async__}<T {send}>(
-                        mut store: impl wasmtime::AsContextMut<Data = T>,
-                        component: &wasmtime::component::Component,
-                        linker: &wasmtime::component::Linker<T>,
-                    ) -> anyhow::Result<(Self, wasmtime::component::Instance)> {{
-                        let instance = linker.instantiate{async__}(&mut store, component){await_}?;
-                        Ok((Self::new(store, &instance)?, instance))
-                    }}
-
-                    /// Low-level creation wrapper for wrapping up the exports
-                    /// of the `instance` provided in this structure of wasm
-                    /// exports.
-                    ///
-                    /// This function will extract exports from the `instance`
-                    /// defined within `store` and wrap them all up in the
-                    /// returned structure which can be used to interact with
-                    /// the wasm module.
-                    pub fn new(
-                        mut store: impl wasmtime::AsContextMut,
-                        instance: &wasmtime::component::Instance,
-                    ) -> anyhow::Result<Self> {{
-                        let mut store = store.as_context_mut();
-                        let mut exports = instance.exports(&mut store);
-                        let mut exports = exports.root();
-            ",
-        );
-        for (name, (_, get)) in self.exports.fields.iter() {
-            uwriteln!(self.src, "let {name} = {get};");
-        }
-        uwriteln!(self.src, "Ok({camel} {{");
-        for (name, _) in self.exports.fields.iter() {
-            uwriteln!(self.src, "{name},");
+        if !self.stubs.is_empty() {
+            let snake = name.to_snake_case();
+            let stubs = mem::take(&mut self.stubs);
+            let src = format!(
+                "\
+// Skeleton implementations of the imports of `{name}`, generated by
+// `wit-bindgen host wasmtime-rust --stubs`. This file isn't overwritten when
+// the bindings are regenerated.
+//
+// The bindings are expected to be the `crate::{snake}` module.
+
+use crate::{snake} as bindings;
+
+/// The implementation of the imports of `{name}`.
+#[derive(Default)]
+pub struct Host;
+{stubs}",
+                stubs = &stubs[..],
+            );
+            files.push_stub(&format!("{snake}_stubs.rs"), src.as_bytes());
         }
-        uwriteln!(self.src, "}})");
-        uwriteln!(self.src, "}}");
 
-        for func in self.exports.funcs.iter() {
-            self.src.push_str(func);
+        if !self.opts.traits_only {
+            self.generate_world_struct(name);
         }
 
-        uwriteln!(self.src, "}}");
-
         let mut src = mem::take(&mut self.src);
         if self.opts.rustfmt {
             let mut child = Command::new("rustfmt")
@@ -314,12 +657,19 @@ impl<'a> InterfaceGenerator<'a> {
         }
     }
 
+    /// Whether types derive the `wasmtime` traits, which they do unless
+    /// generated with `traits_only` and no `traits_cfg`.
+    fn derive_component(&self) -> bool {
+        !self.gen.opts.traits_only || self.gen.opts.traits_cfg.is_some()
+    }
+
     fn special_case_host_error(&self, results: &Results) -> Option<&Result_> {
         // We only support the wit_bindgen_host_wasmtime_rust::Error case when
         // a function has just one result, which is itself a `result<a, e>`, and the
-        // `e` is *not* a primitive (i.e. defined in std) type.
+        // `e` is *not* a primitive (i.e. defined in std) type. Traits which are
+        // independent of wasmtime return the `result` as is instead.
         let mut i = results.iter_types();
-        if i.len() == 1 {
+        if i.len() == 1 && !self.gen.opts.split_traits() {
             match i.next().unwrap() {
                 Type::Id(id) => match &self.iface.types[*id].kind {
                     TypeDefKind::Result(r) => match r.err {
@@ -335,54 +685,236 @@ impl<'a> InterfaceGenerator<'a> {
         }
     }
 
-    fn generate_add_to_linker(&mut self, name: &str) {
+    /// Returns the `result` of `func` along with the host Rust error type and
+    /// the function converting it, if its error type is mapped with
+    /// `error_types`.
+    fn mapped_error(&self, func: &Function) -> Option<(&Result_, &str, &str)> {
+        let mut results = func.results.iter_types();
+        if results.len() != 1 {
+            return None;
+        }
+        let (result, err) = match results.next().unwrap() {
+            Type::Id(id) => match &self.iface.types[*id].kind {
+                TypeDefKind::Result(
+                    r @ Result_ {
+                        err: Some(Type::Id(err)),
+                        ..
+                    },
+                ) => (r, &self.iface.types[*err]),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let name = err.name.as_deref()?;
+        let module = err.foreign_module.as_deref().unwrap_or(&self.iface.name);
+        let key = format!("{module}.{name}");
+        self.gen
+            .opts
+            .error_types
+            .iter()
+            .find(|(ty, _, _)| *ty == key)
+            .map(|(_, rust_ty, convert)| (result, rust_ty.as_str(), convert.as_str()))
+    }
+
+    /// Prints the signature of the method of an import's trait for `func`.
+    fn print_import_signature(&mut self, func: &Function) {
+        let mut fnsig = FnSig::default();
+        fnsig.async_ = self.gen.opts.is_async_import(self.iface, func);
+        fnsig.private = true;
+        fnsig.self_arg = Some("&mut self".to_string());
+
+        self.print_docs_and_params(func, TypeMode::Owned, &fnsig);
+        self.push_str(" -> ");
+
+        if let Some((r, rust_ty, _)) = self.mapped_error(func) {
+            // Mapped errors are converted from the host type by the glue.
+            let (ok, rust_ty) = (r.ok, rust_ty.to_string());
+            self.push_str("std::result::Result<");
+            match ok {
+                Some(ok) => self.print_ty(&ok, TypeMode::Owned),
+                None => self.push_str("()"),
+            }
+            uwrite!(self.src, ", {rust_ty}>");
+        } else if let Some(r) = self.special_case_host_error(&func.results).cloned() {
+            // Functions which have a single result `result<ok,err>` get special
+            // cased to use the host_wasmtime_rust::Error<err>, making it possible
+            // for them to trap or use `?` to propogate their errors
+            self.push_str("wit_bindgen_host_wasmtime_rust::Result<");
+            if let Some(ok) = r.ok {
+                self.print_ty(&ok, TypeMode::Owned);
+            } else {
+                self.push_str("()");
+            }
+            self.push_str(",");
+            if let Some(err) = r.err {
+                self.print_ty(&err, TypeMode::Owned);
+            } else {
+                self.push_str("()");
+            }
+            self.push_str(">");
+        } else {
+            // All other functions get their return values wrapped in an anyhow::Result.
+            // Returning the anyhow::Error case can be used to trap.
+            self.push_str("anyhow::Result<");
+            self.print_result_ty(&func.results, TypeMode::Owned);
+            self.push_str(">");
+        }
+    }
+
+    /// Prints the signature of the method of an export's trait for `func`.
+    fn print_export_signature(&mut self, func: &Function) -> Vec<String> {
+        let mut fnsig = FnSig::default();
+        fnsig.async_ = self.gen.opts.is_async();
+        fnsig.private = true;
+        fnsig.self_arg = Some("&mut self".to_string());
+
+        let params = self.print_docs_and_params(func, TypeMode::AllBorrowed("'_"), &fnsig);
+        self.push_str(" -> anyhow::Result<");
+        self.print_result_ty(&func.results, TypeMode::Owned);
+        self.push_str(">");
+        params
+    }
+
+    fn async_trait_attr(&self) -> &'static str {
+        if !self.gen.opts.is_async() {
+            ""
+        } else if self.gen.opts.traits_only {
+            "#[async_trait::async_trait]"
+        } else {
+            "#[wit_bindgen_host_wasmtime_rust::async_trait]"
+        }
+    }
+
+    /// Generates the `{Interface}Exports` trait of the exported interface
+    /// `name`, with `traits_only`.
+    fn generate_export_trait(&mut self, name: &str) {
         let camel = name.to_upper_camel_case();
+        let async_trait = self.async_trait_attr();
+        uwriteln!(self.src, "{async_trait}");
+        uwriteln!(self.src, "pub trait {camel}Exports {{");
+        for func in self.iface.functions.iter() {
+            self.print_export_signature(func);
+            self.push_str(";\n");
+        }
+        uwriteln!(self.src, "}}");
+    }
 
-        if self.gen.opts.async_ {
-            uwriteln!(self.src, "#[wit_bindgen_host_wasmtime_rust::async_trait]")
+    /// Returns the implementation of the `{Interface}Exports` trait from
+    /// `traits_module` for the exported interface `name`, whose functions are
+    /// methods of the struct `target`.
+    fn generate_export_trait_impl(&mut self, name: &str, target: &str) -> String {
+        let prev = mem::take(&mut self.src);
+        let camel = name.to_upper_camel_case();
+        let (send, where_clause, await_) = if self.gen.opts.is_async() {
+            (
+                " + Send",
+                "where <S as wasmtime::AsContext>::Data: Send",
+                ".await",
+            )
+        } else {
+            ("", "", "")
+        };
+        uwriteln!(
+            self.src,
+            "
+                /// The exports of `{target}` along with the store they're
+                /// called in, implementing `{camel}Exports`.
+                pub struct {target}WithStore<'a, S> {{
+                    exports: &'a {target},
+                    store: S,
+                }}
+
+                impl {target} {{
+                    /// Returns the implementation of `{camel}Exports` calling
+                    /// these exports in `store`.
+                    pub fn with_store<S: wasmtime::AsContextMut>(
+                        &self,
+                        store: S,
+                    ) -> {target}WithStore<'_, S> {{
+                        {target}WithStore {{ exports: self, store }}
+                    }}
+                }}
+            "
+        );
+        let async_trait = self.async_trait_attr();
+        uwriteln!(
+            self.src,
+            "
+                {async_trait}
+                impl<'a, S: wasmtime::AsContextMut{send}> {camel}Exports
+                    for {target}WithStore<'a, S>
+                    {where_clause}
+                {{
+            "
+        );
+        for func in self.iface.functions.iter() {
+            let params = self.print_export_signature(func);
+            uwriteln!(
+                self.src,
+                " {{
+                    Ok(self.exports.{}(&mut self.store, {}){await_}?)
+                }}",
+                func.name.to_snake_case(),
+                params.join(", "),
+            );
         }
-        // Generate the `pub trait` which represents the host functionality for
-        // this import.
-        uwriteln!(self.src, "pub trait {camel}: Sized {{");
+        uwriteln!(self.src, "}}");
+        mem::replace(&mut self.src, prev).into()
+    }
+
+    /// Generates the skeleton implementation of the imported interface `name`
+    /// for the stubs file.
+    fn generate_stubs(&mut self, name: &str) {
+        let prev = mem::take(&mut self.src);
         for func in self.iface.functions.iter() {
-            let mut fnsig = FnSig::default();
-            fnsig.async_ = self.gen.opts.async_;
-            fnsig.private = true;
-            fnsig.self_arg = Some("&mut self".to_string());
-
-            self.print_docs_and_params(func, TypeMode::Owned, &fnsig);
-            self.push_str(" -> ");
-
-            if let Some(r) = self.special_case_host_error(&func.results).cloned() {
-                // Functions which have a single result `result<ok,err>` get special
-                // cased to use the host_wasmtime_rust::Error<err>, making it possible
-                // for them to trap or use `?` to propogate their errors
-                self.push_str("wit_bindgen_host_wasmtime_rust::Result<");
-                if let Some(ok) = r.ok {
-                    self.print_ty(&ok, TypeMode::Owned);
-                } else {
-                    self.push_str("()");
-                }
-                self.push_str(",");
-                if let Some(err) = r.err {
-                    self.print_ty(&err, TypeMode::Owned);
-                } else {
-                    self.push_str("()");
-                }
-                self.push_str(">");
-            } else {
-                // All other functions get their return values wrapped in an anyhow::Result.
-                // Returning the anyhow::Error case can be used to trap.
-                self.push_str("anyhow::Result<");
-                self.print_result_ty(&func.results, TypeMode::Owned);
-                self.push_str(">");
-            }
+            self.print_import_signature(func);
+            self.push_str(" {\ntodo!()\n}\n");
+        }
+        let funcs = mem::replace(&mut self.src, prev);
+        let snake = name.to_snake_case();
+        let camel = name.to_upper_camel_case();
+        let async_trait = self.async_trait_attr();
+        let anyhow = if self.gen.opts.traits_only {
+            ""
+        } else {
+            "use wit_bindgen_host_wasmtime_rust::anyhow;"
+        };
+        uwriteln!(
+            self.gen.stubs,
+            "
+                #[allow(unused_imports, unused_variables)]
+                mod {snake} {{
+                    use super::bindings::{snake}::*;
+                    {anyhow}
+
+                    {async_trait}
+                    impl {camel} for super::Host {{
+                        {funcs}
+                    }}
+                }}
+            ",
+            funcs = &funcs[..],
+        );
+    }
 
+    /// Generates the `pub trait` which represents the host functionality for
+    /// the imported interface `name`.
+    fn generate_import_trait(&mut self, name: &str) {
+        let camel = name.to_upper_camel_case();
+        let async_trait = self.async_trait_attr();
+        uwriteln!(self.src, "{async_trait}");
+        uwriteln!(self.src, "pub trait {camel}: Sized {{");
+        for func in self.iface.functions.iter() {
+            self.print_import_signature(func);
             self.push_str(";\n");
         }
         uwriteln!(self.src, "}}");
+    }
 
-        let where_clause = if self.gen.opts.async_ {
+    fn generate_add_to_linker(&mut self, name: &str) {
+        let camel = name.to_upper_camel_case();
+
+        let where_clause = if self.gen.opts.is_async() {
             format!("T: Send, U: {camel} + Send")
         } else {
             format!("U: {camel}")
@@ -403,7 +935,7 @@ impl<'a> InterfaceGenerator<'a> {
             uwrite!(
                 self.src,
                 "inst.{}(\"{}\", ",
-                if self.gen.opts.async_ {
+                if self.gen.opts.is_async_import(self.iface, func) {
                     "func_wrap_async"
                 } else {
                     "func_wrap"
@@ -420,6 +952,7 @@ impl<'a> InterfaceGenerator<'a> {
     fn generate_guest_import_closure(&mut self, func: &Function) {
         // Generate the closure that's passed to a `Linker`, the final piece of
         // codegen here.
+        let async_ = self.gen.opts.is_async_import(self.iface, func);
         self.src
             .push_str("move |mut caller: wasmtime::StoreContextMut<'_, T>, (");
         for (i, _param) in func.params.iter().enumerate() {
@@ -433,7 +966,7 @@ impl<'a> InterfaceGenerator<'a> {
             self.src.push_str(", ");
         }
         self.src.push_str(") |");
-        if self.gen.opts.async_ {
+        if async_ {
             self.src.push_str(" Box::new(async move { \n");
         } else {
             self.src.push_str(" { \n");
@@ -460,30 +993,55 @@ impl<'a> InterfaceGenerator<'a> {
         for (i, _) in func.params.iter().enumerate() {
             uwrite!(self.src, "arg{},", i);
         }
-        if self.gen.opts.async_ {
+        if async_ {
             uwrite!(self.src, ").await;\n");
         } else {
             uwrite!(self.src, ");\n");
         }
 
-        if self.special_case_host_error(&func.results).is_some() {
+        // With `call_errors` errors are marked as coming from the host, as
+        // opposed to traps of the guest, by a `HostError` cause which stays in
+        // the chain of the trap the error causes.
+        let host_error = if self.gen.opts.call_errors {
+            format!(
+                ".map_err(|e| anyhow::Error::new(wit_bindgen_host_wasmtime_rust::HostError {{
+                    interface: \"{}\",
+                    function: \"{}\",
+                }}).context(e))",
+                self.iface.name, func.name,
+            )
+        } else {
+            String::new()
+        };
+        if let Some((_, _, convert)) = self.mapped_error(func) {
+            uwrite!(
+                self.src,
+                "match r {{
+                    Ok(a) => Ok((Ok(a),)),
+                    Err(e) => match {convert}(e) {{
+                        Ok(e) => Ok((Err(e),)),
+                        Err(trap) => Err(trap){host_error},
+                    }}
+                }}"
+            );
+        } else if self.special_case_host_error(&func.results).is_some() {
             uwrite!(
                 self.src,
                 "match r {{
                     Ok(a) => Ok((Ok(a),)),
                     Err(e) => match e.downcast() {{
                         Ok(api_error) => Ok((Err(api_error),)),
-                        Err(anyhow_error) => Err(anyhow_error),
+                        Err(anyhow_error) => Err(anyhow_error){host_error},
                     }}
                 }}"
             );
         } else if func.results.iter_types().len() == 1 {
-            uwrite!(self.src, "Ok((r?,))\n");
+            uwrite!(self.src, "Ok((r{host_error}?,))\n");
         } else {
-            uwrite!(self.src, "r\n");
+            uwrite!(self.src, "r{host_error}\n");
         }
 
-        if self.gen.opts.async_ {
+        if async_ {
             // Need to close Box::new and async block
             self.src.push_str("})");
         } else {
@@ -517,7 +1075,7 @@ impl<'a> InterfaceGenerator<'a> {
     }
 
     fn define_rust_guest_export(&mut self, ns: Option<&str>, func: &Function) {
-        let (async_, async__, await_) = if self.gen.opts.async_ {
+        let (async_, async__, await_) = if self.gen.opts.is_async() {
             ("async", "_async", ".await")
         } else {
             ("", "", "")
@@ -534,10 +1092,30 @@ impl<'a> InterfaceGenerator<'a> {
             self.print_ty(&param.1, TypeMode::AllBorrowed("'_"));
             self.push_str(",");
         }
-        self.src.push_str(") -> anyhow::Result<");
+        let (error, call_error) = if self.gen.opts.call_errors {
+            let interface = match ns {
+                Some(ns) => format!("Some(\"{ns}\")"),
+                None => "None".to_string(),
+            };
+            (
+                ", wit_bindgen_host_wasmtime_rust::CallError",
+                format!(
+                    ".map_err(|e| wit_bindgen_host_wasmtime_rust::CallError::new({interface}, \"{}\", e))",
+                    func.name
+                ),
+            )
+        } else {
+            ("", String::new())
+        };
+        if self.gen.opts.call_errors {
+            self.src.push_str(") -> std::result::Result<");
+        } else {
+            self.src.push_str(") -> anyhow::Result<");
+        }
         self.print_result_ty(&func.results, TypeMode::Owned);
+        self.src.push_str(error);
 
-        if self.gen.opts.async_ {
+        if self.gen.opts.is_async() {
             self.src
                 .push_str("> where <S as wasmtime::AsContext>::Data: Send {\n");
         } else {
@@ -588,11 +1166,11 @@ impl<'a> InterfaceGenerator<'a> {
         for (i, _) in func.params.iter().enumerate() {
             uwrite!(self.src, "arg{}, ", i);
         }
-        uwriteln!(self.src, ")){await_}?;");
+        uwriteln!(self.src, ")){await_}{call_error}?;");
 
         uwriteln!(
             self.src,
-            "callee.post_return{async__}(store.as_context_mut()){await_}?;"
+            "callee.post_return{async__}(store.as_context_mut()){await_}{call_error}?;"
         );
 
         self.src.push_str("Ok(");
@@ -613,7 +1191,7 @@ impl<'a> InterfaceGenerator<'a> {
 
     fn generate_from_error_impls(&mut self) {
         for (id, ty) in self.iface.types.iter() {
-            if ty.name.is_none() {
+            if ty.name.is_none() || self.mapped_type(id).is_some() {
                 continue;
             }
             let info = self.info(id);
@@ -651,6 +1229,26 @@ impl<'a> RustGenerator<'a> for InterfaceGenerator<'a> {
         self.default_param_mode
     }
 
+    fn additional_derives(&self) -> &[String] {
+        &self.gen.opts.additional_derives
+    }
+
+    fn type_attributes(&self) -> &[(String, String)] {
+        &self.gen.opts.type_attributes
+    }
+
+    fn with_types(&self) -> &[(String, String)] {
+        &self.gen.opts.with
+    }
+
+    fn component_cfg(&self) -> Option<&str> {
+        if self.gen.opts.traits_only {
+            self.gen.opts.traits_cfg.as_deref()
+        } else {
+            None
+        }
+    }
+
     fn push_str(&mut self, s: &str) {
         self.src.push_str(s);
     }
@@ -682,8 +1280,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.iface
     }
 
+    fn type_defined_elsewhere(&self, id: TypeId) -> bool {
+        self.mapped_type(id).is_some()
+    }
+
     fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
-        self.print_typedef_record(id, record, docs, true);
+        let derive_component = self.derive_component();
+        self.print_typedef_record(id, record, docs, derive_component);
     }
 
     fn type_tuple(&mut self, id: TypeId, _name: &str, tuple: &Tuple, docs: &Docs) {
@@ -691,6 +1294,9 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
     }
 
     fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+        // Flags are only defined through `wasmtime`'s macro for now, which
+        // `Wasmtime::validate` rejects with `traits_only`.
+        assert!(!self.gen.opts.traits_only);
         self.rustdoc(docs);
         self.src.push_str("wasmtime::component::flags!(\n");
         self.src
@@ -709,11 +1315,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
     }
 
     fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
-        self.print_typedef_variant(id, variant, docs, true);
+        let derive_component = self.derive_component();
+        self.print_typedef_variant(id, variant, docs, derive_component);
     }
 
     fn type_union(&mut self, id: TypeId, _name: &str, union: &Union, docs: &Docs) {
-        self.print_typedef_union(id, union, docs, true);
+        let derive_component = self.derive_component();
+        self.print_typedef_union(id, union, docs, derive_component);
     }
 
     fn type_option(&mut self, id: TypeId, _name: &str, payload: &Type, docs: &Docs) {
@@ -725,11 +1333,23 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
     }
 
     fn type_enum(&mut self, id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
-        self.print_typedef_enum(id, name, enum_, docs,
-           &["#[derive(wasmtime::component::ComponentType, wasmtime::component::Lift, wasmtime::component::Lower)]".to_owned(),
-           "#[component(enum)]".to_owned()],
-           Box::new(|case| format!("#[component(name = \"{}\")]", case.name))
-       );
+        if !self.derive_component() {
+            self.print_typedef_enum(id, name, enum_, docs, &[], Box::new(|_| String::new()));
+            return;
+        }
+        let attrs = [
+            self.component_attr("derive(wasmtime::component::ComponentType, wasmtime::component::Lift, wasmtime::component::Lower)"),
+            self.component_attr("component(enum)"),
+        ];
+        let cfg = self.component_cfg().map(|cfg| cfg.to_string());
+        let case_attr = move |case: &EnumCase| {
+            let attr = format!("component(name = \"{}\")", case.name);
+            match &cfg {
+                Some(cfg) => format!("#[cfg_attr({cfg}, {attr})]"),
+                None => format!("#[{attr}]"),
+            }
+        };
+        self.print_typedef_enum(id, name, enum_, docs, &attrs, Box::new(case_attr));
     }
 
     fn type_alias(&mut self, id: TypeId, _name: &str, ty: &Type, docs: &Docs) {
@@ -62,3 +62,448 @@ macro_rules! codegen_test {
 }
 
 test_helpers::codegen_tests!("*.wit");
+
+// This is a static compilation test to check that additional derives and
+// attributes are added to generated types.
+mod derives {
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["cat"]: "
+            record point { x: u32, y: u32 }
+            variant shape { dot(point), empty }
+            enum color { red, green }
+
+            foo: func(p: point, s: shape, c: color)
+        ",
+        name: "not-used-name",
+        additional_derives: [Hash, PartialEq, Eq],
+        type_attributes: {
+            "point": #[non_exhaustive],
+        },
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let mut set = std::collections::HashSet::new();
+        set.insert(cat::Shape::Empty);
+        assert!(cat::Point { x: 0, y: 0 } == cat::Point { x: 0, y: 0 });
+    }
+}
+
+// This is a static compilation test to check that existing Rust types can be
+// used in place of generated types.
+mod with {
+    use wit_bindgen_host_wasmtime_rust::wasmtime;
+
+    #[derive(
+        wasmtime::component::ComponentType,
+        wasmtime::component::Lift,
+        wasmtime::component::Lower,
+        Clone,
+        Copy,
+    )]
+    #[component(record)]
+    pub struct MyPoint {
+        #[component(name = "x")]
+        pub x: u32,
+        #[component(name = "y")]
+        pub y: u32,
+    }
+
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["cat"]: "
+            record point { x: u32, y: u32 }
+
+            foo: func(p: point) -> point
+        ",
+        name: "not-used-name",
+        with: { "cat.point": super::MyPoint },
+    });
+
+    struct Host;
+
+    impl cat::Cat for Host {
+        fn foo(&mut self, p: MyPoint) -> wit_bindgen_host_wasmtime_rust::anyhow::Result<MyPoint> {
+            Ok(p)
+        }
+    }
+}
+
+// This is a static compilation test to check that the types and traits can be
+// generated without `wasmtime`, and used by separately generated bindings.
+mod traits_only {
+    mod types {
+        wit_bindgen_host_wasmtime_rust_macro::generate!({
+            import_str["logger"]: "
+                record entry { level: level, message: string }
+                enum level { info, warn }
+                variant target { console, file(string) }
+
+                log: func(entry: entry, target: target)
+            ",
+            name: "types",
+            traits_only,
+        });
+    }
+
+    mod traits {
+        wit_bindgen_host_wasmtime_rust_macro::generate!({
+            import_str["logger"]: "
+                log: func(message: string) -> result<u32, string>
+            ",
+            export_str["handler"]: "
+                handle: func(request: string) -> string
+            ",
+            default_str: "
+                run: func(args: list<string>) -> u32
+            ",
+            name: "app",
+            traits_only,
+        });
+    }
+
+    mod glue {
+        wit_bindgen_host_wasmtime_rust::generate!({
+            import_str["logger"]: "
+                log: func(message: string) -> result<u32, string>
+            ",
+            export_str["handler"]: "
+                handle: func(request: string) -> string
+            ",
+            default_str: "
+                run: func(args: list<string>) -> u32
+            ",
+            name: "app",
+            traits_module: "crate::traits_only::traits",
+        });
+    }
+
+    struct Host;
+
+    impl traits::logger::Logger for Host {
+        fn log(&mut self, message: String) -> anyhow::Result<Result<u32, String>> {
+            Ok(Ok(message.len() as u32))
+        }
+    }
+
+    fn handle(handler: &mut impl traits::handler::HandlerExports) -> anyhow::Result<String> {
+        handler.handle("request")
+    }
+
+    fn run(app: &mut impl traits::AppExports) -> anyhow::Result<u32> {
+        app.run(&["arg"])
+    }
+
+    #[allow(dead_code)]
+    fn test(
+        linker: &mut wasmtime::component::Linker<Host>,
+        mut store: wasmtime::Store<Host>,
+        app: &glue::App,
+    ) -> anyhow::Result<()> {
+        glue::logger::add_to_linker(linker, |host| host)?;
+        handle(&mut app.handler().with_store(&mut store))?;
+        run(&mut app.with_store(&mut store))?;
+        Ok(())
+    }
+}
+
+// This is a static compilation test to check that exports return a
+// `CallError` with `call_errors`.
+mod call_errors {
+    use wit_bindgen_host_wasmtime_rust::{CallError, CallErrorKind};
+
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["host"]: "
+            fetch: func(url: string) -> string
+        ",
+        export_str["guest"]: "
+            run: func(input: string) -> u32
+        ",
+        default_str: "
+            start: func()
+        ",
+        name: "call-errors",
+        call_errors: true,
+    });
+
+    fn describe(err: &CallError) -> String {
+        let frames = err.backtrace().map(|frames| frames.len()).unwrap_or(0);
+        match err.kind() {
+            CallErrorKind::GuestPanic => format!("guest panicked with {frames} frames"),
+            CallErrorKind::Host => format!("{:?}", err.host_error()),
+            kind => format!("{:?} in {:?}#{}", kind, err.interface(), err.function()),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn test(mut store: wasmtime::Store<()>, exports: &CallErrors) -> anyhow::Result<()> {
+        if let Err(err) = exports.guest().run(&mut store, "input") {
+            describe(&err);
+        }
+        exports.start(&mut store)?;
+        Ok(())
+    }
+}
+
+// This is a static compilation test to check that imports can return host
+// error types which are converted to WIT error types.
+mod error_types {
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["fs"]: "
+            enum errno { not-found, access, other }
+
+            read: func(path: string) -> result<list<u8>, errno>
+            remove: func(path: string) -> result<_, errno>
+        ",
+        name: "error-types",
+        error_types: {
+            "fs.errno": std::io::Error => super::to_errno,
+        },
+    });
+
+    fn to_errno(err: std::io::Error) -> anyhow::Result<fs::Errno> {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Ok(fs::Errno::NotFound),
+            std::io::ErrorKind::PermissionDenied => Ok(fs::Errno::Access),
+            std::io::ErrorKind::OutOfMemory => Err(err.into()),
+            _ => Ok(fs::Errno::Other),
+        }
+    }
+
+    struct Host;
+
+    impl fs::Fs for Host {
+        fn read(&mut self, path: String) -> Result<Vec<u8>, std::io::Error> {
+            std::fs::read(path)
+        }
+
+        fn remove(&mut self, path: String) -> Result<(), std::io::Error> {
+            std::fs::remove_file(path)
+        }
+    }
+
+    #[allow(dead_code)]
+    fn test(linker: &mut wasmtime::component::Linker<Host>) -> anyhow::Result<()> {
+        fs::add_to_linker(linker, |host| host)
+    }
+}
+
+// This is a static compilation test to check that host error types keep
+// their generic arguments.
+mod generic_error_types {
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["fs"]: "
+            enum errno { not-found, other }
+
+            read: func(path: string) -> result<list<u8>, errno>
+        ",
+        name: "generic-error-types",
+        error_types: {
+            "fs.errno": Box<dyn std::error::Error + Send + Sync> => super::to_errno,
+        },
+    });
+
+    fn to_errno(_err: Box<dyn std::error::Error + Send + Sync>) -> anyhow::Result<fs::Errno> {
+        Ok(fs::Errno::Other)
+    }
+
+    struct Host;
+
+    impl fs::Fs for Host {
+        fn read(
+            &mut self,
+            path: String,
+        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(std::fs::read(path)?)
+        }
+    }
+
+    #[allow(dead_code)]
+    fn test(linker: &mut wasmtime::component::Linker<Host>) -> anyhow::Result<()> {
+        fs::add_to_linker(linker, |host| host)
+    }
+}
+
+// This is a static compilation test to check that only some imports can be
+// async.
+mod async_only {
+    wit_bindgen_host_wasmtime_rust::generate!({
+        import_str["host"]: "
+            fetch: func(url: string) -> string
+            log: func(message: string)
+        ",
+        export_str["guest"]: "
+            run: func() -> u32
+        ",
+        name: "async-only",
+        async: { only: ["fetch"] },
+    });
+
+    struct Host;
+
+    #[wit_bindgen_host_wasmtime_rust::async_trait]
+    impl host::Host for Host {
+        async fn fetch(&mut self, url: String) -> anyhow::Result<String> {
+            Ok(url)
+        }
+
+        fn log(&mut self, message: String) -> anyhow::Result<()> {
+            drop(message);
+            Ok(())
+        }
+    }
+
+    #[allow(dead_code)]
+    async fn test(
+        mut store: wasmtime::Store<Host>,
+        component: &wasmtime::component::Component,
+        linker: &mut wasmtime::component::Linker<Host>,
+    ) -> anyhow::Result<u32> {
+        host::add_to_linker(linker, |host| host)?;
+        let (exports, _) = AsyncOnly::instantiate_async(&mut store, component, linker).await?;
+        exports.guest().run(&mut store).await
+    }
+}
+
+mod validate {
+    use wit_bindgen_core::wit_parser::Interface;
+    use wit_bindgen_core::WorldGenerator;
+    use wit_bindgen_gen_host_wasmtime_rust::{Async, Opts};
+    use wit_component::ComponentInterfaces;
+
+    fn validate(opts: Opts, wit: &str) -> anyhow::Result<()> {
+        let mut interfaces = ComponentInterfaces::default();
+        let iface = Interface::parse("host", wit).unwrap();
+        interfaces.imports.insert("host".to_string(), iface);
+        opts.build().validate(&interfaces)
+    }
+
+    fn validate_err(opts: Opts, wit: &str) -> String {
+        validate(opts, wit).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn traits_only_flags() {
+        let wit = "
+            flags perms { read, write }
+            check: func(perms: perms)
+        ";
+        let opts = Opts {
+            traits_only: true,
+            ..Opts::default()
+        };
+        assert!(validate_err(opts.clone(), wit).contains("flags aren't supported"));
+
+        let opts = Opts {
+            with: vec![("host.perms".to_string(), "crate::Perms".to_string())],
+            ..opts
+        };
+        validate(opts, wit).unwrap();
+    }
+
+    #[test]
+    fn traits_cfg() {
+        let wit = "check: func()";
+        let opts = Opts {
+            traits_cfg: Some("feature = \"wasmtime\"".to_string()),
+            ..Opts::default()
+        };
+        assert!(validate_err(opts.clone(), wit).contains("only applies with `traits_only`"));
+
+        let opts = Opts {
+            traits_only: true,
+            ..opts
+        };
+        validate(opts, wit).unwrap();
+    }
+
+    #[test]
+    fn async_imports() {
+        let wit = "
+            fetch: func(url: string) -> string
+            log: func(message: string)
+        ";
+        for names in [vec![], vec!["fetch"], vec!["host#fetch", "log"]] {
+            let names = names.into_iter().map(String::from).collect::<Vec<_>>();
+            let only = Opts {
+                async_: Some(Async::Only(names.clone())),
+                ..Opts::default()
+            };
+            validate(only, wit).unwrap();
+            let except = Opts {
+                async_: Some(Async::Except(names)),
+                ..Opts::default()
+            };
+            validate(except, wit).unwrap();
+        }
+
+        let unknown = Opts {
+            async_: Some(Async::Only(vec!["other#fetch".to_string()])),
+            ..Opts::default()
+        };
+        assert!(validate_err(unknown, wit).contains("no imported function named `other#fetch`"));
+    }
+
+    #[test]
+    fn parse_async() {
+        use wit_bindgen_gen_host_wasmtime_rust::parse_async;
+
+        assert_eq!(parse_async("all").unwrap(), Async::All);
+        assert_eq!(parse_async("only:").unwrap(), Async::Only(vec![]));
+        assert_eq!(
+            parse_async("only:fetch,host#log").unwrap(),
+            Async::Only(vec!["fetch".to_string(), "host#log".to_string()])
+        );
+        assert_eq!(
+            parse_async("except:log").unwrap(),
+            Async::Except(vec!["log".to_string()])
+        );
+        assert!(parse_async("fetch").is_err());
+    }
+
+    #[test]
+    fn error_types() {
+        let wit = "
+            enum errno { not-found, other }
+            read: func(path: string) -> result<string, errno>
+        ";
+        let mapping = |ty: &str| Opts {
+            error_types: vec![(
+                ty.to_string(),
+                "std::io::Error".to_string(),
+                "crate::to_errno".to_string(),
+            )],
+            ..Opts::default()
+        };
+        validate(mapping("host.errno"), wit).unwrap();
+        assert!(
+            validate_err(mapping("host.error"), wit).contains("no type of an imported interface")
+        );
+        assert!(
+            validate_err(mapping("other.errno"), wit).contains("no type of an imported interface")
+        );
+    }
+
+    #[test]
+    fn parse_error_type() {
+        use wit_bindgen_gen_host_wasmtime_rust::parse_error_type;
+
+        assert_eq!(
+            parse_error_type("fs.errno=std::io::Error,crate::to_errno").unwrap(),
+            (
+                "fs.errno".to_string(),
+                "std::io::Error".to_string(),
+                "crate::to_errno".to_string()
+            )
+        );
+        assert_eq!(
+            parse_error_type("fs.errno=Result<u32, String>,crate::to_errno").unwrap(),
+            (
+                "fs.errno".to_string(),
+                "Result<u32, String>".to_string(),
+                "crate::to_errno".to_string()
+            )
+        );
+        assert!(parse_error_type("errno=std::io::Error,crate::to_errno").is_err());
+        assert!(parse_error_type("fs.errno=std::io::Error").is_err());
+    }
+}
@@ -25,6 +25,57 @@ pub trait RustGenerator<'a> {
         false
     }
 
+    /// Additional traits to derive for all generated records, variants, unions
+    /// and enums, for example `Hash` or `serde::Serialize`.
+    fn additional_derives(&self) -> &[String] {
+        &[]
+    }
+
+    /// Additional attributes for the generated types of specific WIT types,
+    /// as pairs of a type name, optionally qualified as `INTERFACE#TYPE`, and
+    /// an attribute such as `#[non_exhaustive]`.
+    fn type_attributes(&self) -> &[(String, String)] {
+        &[]
+    }
+
+    /// Existing Rust types to use in place of generating WIT types, as pairs
+    /// of a type named `MODULE.TYPE` and the path of its Rust type.
+    ///
+    /// `MODULE` is the interface which originally defines the type, so a type
+    /// which is `use`d by several interfaces maps to the same Rust type in
+    /// all of them.
+    fn with_types(&self) -> &[(String, String)] {
+        &[]
+    }
+
+    /// The `cfg` predicate which derives of `wasmtime`'s component traits are
+    /// gated on, if they're only derived conditionally.
+    fn component_cfg(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the attribute `#[attr]` for deriving `wasmtime`'s component
+    /// traits, gated on [`RustGenerator::component_cfg`].
+    fn component_attr(&self, attr: &str) -> String {
+        match self.component_cfg() {
+            Some(cfg) => format!("#[cfg_attr({cfg}, {attr})]"),
+            None => format!("#[{attr}]"),
+        }
+    }
+
+    /// Returns the path of the existing Rust type used for the type `id`, if
+    /// it's mapped by [`RustGenerator::with_types`].
+    fn mapped_type(&self, id: TypeId) -> Option<&str> {
+        let ty = &self.iface().types[id];
+        let name = ty.name.as_deref()?;
+        let module = ty.foreign_module.as_deref().unwrap_or(&self.iface().name);
+        let key = format!("{module}.{name}");
+        self.with_types()
+            .iter()
+            .find(|(ty, _)| *ty == key)
+            .map(|(_, path)| path.as_str())
+    }
+
     fn push_str(&mut self, s: &str);
     fn info(&self, ty: TypeId) -> TypeInfo;
     fn types_mut(&mut self) -> &mut Types;
@@ -210,8 +261,12 @@ pub trait RustGenerator<'a> {
 
             // If the type recursively owns data and it's a
             // variant/record/list, then we need to place the
-            // lifetime parameter on the type as well.
-            if info.owns_data() && needs_generics(self.iface(), &ty.kind) {
+            // lifetime parameter on the type as well. Existing types used in
+            // place of generated types are always used as-is.
+            if info.owns_data()
+                && needs_generics(self.iface(), &ty.kind)
+                && self.mapped_type(id).is_none()
+            {
                 self.print_generics(lt);
             }
 
@@ -487,19 +542,23 @@ pub trait RustGenerator<'a> {
             self.rustdoc(docs);
 
             if derive_component {
-                self.push_str("#[derive(wasmtime::component::ComponentType)]\n");
+                let attr = self.component_attr("derive(wasmtime::component::ComponentType)");
+                self.push_str(&format!("{attr}\n"));
                 if lt.is_none() {
-                    self.push_str("#[derive(wasmtime::component::Lift)]\n");
+                    let attr = self.component_attr("derive(wasmtime::component::Lift)");
+                    self.push_str(&format!("{attr}\n"));
                 }
-                self.push_str("#[derive(wasmtime::component::Lower)]\n");
-                self.push_str("#[component(record)]\n");
+                let attr = self.component_attr("derive(wasmtime::component::Lower)");
+                self.push_str(&format!("{attr}\n"));
+                let attr = self.component_attr("component(record)");
+                self.push_str(&format!("{attr}\n"));
             }
 
             if !info.owns_data() {
                 self.push_str("#[repr(C)]\n");
-                self.push_str("#[derive(Copy, Clone)]\n");
+                self.print_derives(id, &["Copy", "Clone"]);
             } else {
-                self.push_str("#[derive(Clone)]\n");
+                self.print_derives(id, &["Clone"]);
             }
             self.push_str(&format!("pub struct {}", name));
             self.print_generics(lt);
@@ -507,7 +566,9 @@ pub trait RustGenerator<'a> {
             for field in record.fields.iter() {
                 self.rustdoc(&field.docs);
                 if derive_component {
-                    self.push_str(&format!("#[component(name = \"{}\")]\n", field.name));
+                    let attr =
+                        self.component_attr(&format!("component(name = \"{}\")", field.name));
+                    self.push_str(&format!("{attr}\n"));
                 }
                 self.push_str("pub ");
                 self.push_str(&to_rust_ident(&field.name));
@@ -560,6 +621,33 @@ pub trait RustGenerator<'a> {
         }
     }
 
+    /// Prints the `#[derive]` of the type `id`, deriving `derives` along with
+    /// any additional derives, followed by any additional attributes of the
+    /// type.
+    ///
+    /// Additional derives of traits which are already derived, or of `Debug`
+    /// which is always implemented manually, are skipped.
+    fn print_derives(&mut self, id: TypeId, derives: &[&str]) {
+        let mut all = derives.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        for derive in self.additional_derives() {
+            let name = derive.rsplit("::").next().unwrap().trim();
+            if name != "Debug" && !derives.contains(&name) && !all.contains(derive) {
+                all.push(derive.clone());
+            }
+        }
+        self.push_str(&format!("#[derive({})]\n", all.join(", ")));
+
+        let name = self.iface().types[id].name.as_deref().unwrap();
+        let qualified = format!("{}#{}", self.iface().name, name);
+        let attrs = self
+            .type_attributes()
+            .iter()
+            .filter(|(ty, _)| *ty == name || *ty == qualified)
+            .map(|(_, attr)| format!("{attr}\n"))
+            .collect::<String>();
+        self.push_str(&attrs);
+    }
+
     fn print_typedef_tuple(&mut self, id: TypeId, tuple: &Tuple, docs: &Docs) {
         let info = self.info(id);
         for (name, mode) in self.modes_of(id) {
@@ -643,17 +731,21 @@ pub trait RustGenerator<'a> {
             self.rustdoc(docs);
             let lt = self.lifetime_for(&info, mode);
             if let Some(derive_component) = derive_component {
-                self.push_str("#[derive(wasmtime::component::ComponentType)]\n");
+                let attr = self.component_attr("derive(wasmtime::component::ComponentType)");
+                self.push_str(&format!("{attr}\n"));
                 if lt.is_none() {
-                    self.push_str("#[derive(wasmtime::component::Lift)]\n");
+                    let attr = self.component_attr("derive(wasmtime::component::Lift)");
+                    self.push_str(&format!("{attr}\n"));
                 }
-                self.push_str("#[derive(wasmtime::component::Lower)]\n");
-                self.push_str(&format!("#[component({})]\n", derive_component));
+                let attr = self.component_attr("derive(wasmtime::component::Lower)");
+                self.push_str(&format!("{attr}\n"));
+                let attr = self.component_attr(&format!("component({})", derive_component));
+                self.push_str(&format!("{attr}\n"));
             }
             if !info.owns_data() {
-                self.push_str("#[derive(Clone, Copy)]\n");
+                self.print_derives(id, &["Clone", "Copy"]);
             } else {
-                self.push_str("#[derive(Clone)]\n");
+                self.print_derives(id, &["Clone"]);
             }
             self.push_str(&format!("pub enum {name}"));
             self.print_generics(lt);
@@ -662,7 +754,8 @@ pub trait RustGenerator<'a> {
                 self.rustdoc(docs);
                 if derive_component.is_some() {
                     if let Some(n) = component_name {
-                        self.push_str(&format!("#[component(name = \"{}\")] ", n));
+                        let attr = self.component_attr(&format!("component(name = \"{}\")", n));
+                        self.push_str(&format!("{attr} "));
                     }
                 }
                 self.push_str(&case_name);
@@ -801,7 +894,8 @@ pub trait RustGenerator<'a> {
         }
         self.push_str("#[repr(");
         self.int_repr(enum_.tag());
-        self.push_str(")]\n#[derive(Clone, Copy, PartialEq, Eq)]\n");
+        self.push_str(")]\n");
+        self.print_derives(id, &["Clone", "Copy", "PartialEq", "Eq"]);
         self.push_str(&format!("pub enum {} {{\n", name.to_upper_camel_case()));
         for case in enum_.cases.iter() {
             self.rustdoc(&case.docs);
@@ -917,6 +1011,9 @@ pub trait RustGenerator<'a> {
     }
 
     fn param_name(&self, ty: TypeId) -> String {
+        if let Some(path) = self.mapped_type(ty) {
+            return path.to_string();
+        }
         let info = self.info(ty);
         let name = self.iface().types[ty]
             .name
@@ -931,6 +1028,9 @@ pub trait RustGenerator<'a> {
     }
 
     fn result_name(&self, ty: TypeId) -> String {
+        if let Some(path) = self.mapped_type(ty) {
+            return path.to_string();
+        }
         let info = self.info(ty);
         let name = self.iface().types[ty]
             .name
@@ -1220,3 +1320,30 @@ impl fmt::Display for RustFlagsRepr {
         }
     }
 }
+
+/// Parses a `TYPE=ATTRIBUTE` pair naming an additional attribute of a
+/// generated type, as accepted by the `--type-attribute` flag of Rust
+/// generators.
+pub fn parse_type_attribute(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((ty, attr)) if !ty.is_empty() && attr.starts_with("#[") => {
+            Ok((ty.to_string(), attr.to_string()))
+        }
+        _ => Err(format!(
+            "expected `TYPE=ATTRIBUTE`, for example `point=#[non_exhaustive]`, but found `{s}`"
+        )),
+    }
+}
+
+/// Parses a `MODULE.TYPE=PATH` pair mapping a WIT type to an existing Rust
+/// type, as accepted by the `--with` flag of Rust generators.
+pub fn parse_with(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((ty, path)) if ty.contains('.') && !path.is_empty() => {
+            Ok((ty.to_string(), path.to_string()))
+        }
+        _ => Err(format!(
+            "expected `MODULE.TYPE=PATH`, for example `types.timestamp=crate::Timestamp`, but found `{s}`"
+        )),
+    }
+}
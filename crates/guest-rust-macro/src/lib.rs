@@ -16,6 +16,13 @@ mod kw {
     syn::custom_keyword!(raw_strings);
     syn::custom_keyword!(macro_call_prefix);
     syn::custom_keyword!(export_macro_name);
+    syn::custom_keyword!(additional_derives);
+    syn::custom_keyword!(type_attributes);
+    syn::custom_keyword!(with);
+    syn::custom_keyword!(stateful_exports);
+    syn::custom_keyword!(native_testing);
+    syn::custom_keyword!(validate_lifts);
+    syn::custom_keyword!(borrowed_export_results);
 }
 
 enum Opt {
@@ -24,6 +31,13 @@ enum Opt {
     RawStrings,
     MacroCallPrefix(LitStr),
     ExportMacroName(LitStr),
+    AdditionalDerives(Vec<String>),
+    TypeAttributes(Vec<(String, String)>),
+    With(Vec<(String, String)>),
+    StatefulExports,
+    NativeTesting,
+    ValidateLifts,
+    BorrowedExportResults,
 }
 
 impl Parse for Opt {
@@ -46,6 +60,34 @@ impl Parse for Opt {
             input.parse::<kw::export_macro_name>()?;
             input.parse::<Token![:]>()?;
             Ok(Opt::ExportMacroName(input.parse()?))
+        } else if l.peek(kw::additional_derives) {
+            input.parse::<kw::additional_derives>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::AdditionalDerives(
+                wit_bindgen_rust_macro_shared::parse_derives(input)?,
+            ))
+        } else if l.peek(kw::type_attributes) {
+            input.parse::<kw::type_attributes>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::TypeAttributes(
+                wit_bindgen_rust_macro_shared::parse_type_attributes(input)?,
+            ))
+        } else if l.peek(kw::stateful_exports) {
+            input.parse::<kw::stateful_exports>()?;
+            Ok(Opt::StatefulExports)
+        } else if l.peek(kw::native_testing) {
+            input.parse::<kw::native_testing>()?;
+            Ok(Opt::NativeTesting)
+        } else if l.peek(kw::validate_lifts) {
+            input.parse::<kw::validate_lifts>()?;
+            Ok(Opt::ValidateLifts)
+        } else if l.peek(kw::borrowed_export_results) {
+            input.parse::<kw::borrowed_export_results>()?;
+            Ok(Opt::BorrowedExportResults)
+        } else if l.peek(kw::with) {
+            input.parse::<kw::with>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::With(wit_bindgen_rust_macro_shared::parse_with(input)?))
         } else {
             Err(l.error())
         }
@@ -60,6 +102,13 @@ impl wit_bindgen_rust_macro_shared::Configure<Opts> for Opt {
             Opt::RawStrings => opts.raw_strings = true,
             Opt::MacroCallPrefix(prefix) => opts.macro_call_prefix = Some(prefix.value()),
             Opt::ExportMacroName(name) => opts.export_macro_name = Some(name.value()),
+            Opt::AdditionalDerives(derives) => opts.additional_derives.extend(derives),
+            Opt::TypeAttributes(attrs) => opts.type_attributes.extend(attrs),
+            Opt::With(with) => opts.with.extend(with),
+            Opt::StatefulExports => opts.stateful_exports = true,
+            Opt::NativeTesting => opts.native_testing = true,
+            Opt::ValidateLifts => opts.validate_lifts = true,
+            Opt::BorrowedExportResults => opts.borrowed_export_results = true,
         }
     }
 }
@@ -9,19 +9,303 @@ pub use wit_bindgen_guest_rust_macro::*;
 #[doc(hidden)]
 pub use bitflags;
 
+use core::fmt;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A malformed value lifted from the canonical ABI, reported by bindings
+/// generated with `validate_lifts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiftError {
+    /// A `char` which isn't a Unicode scalar value.
+    InvalidChar(u32),
+    /// A `bool` which is neither 0 nor 1.
+    InvalidBool(i32),
+    /// A discriminant which isn't a case of the variant, enum, union, option
+    /// or result `ty`.
+    InvalidDiscriminant { ty: &'static str, discriminant: i32 },
+    /// Flags `ty` with bits set which aren't any of its flags.
+    InvalidFlags { ty: &'static str },
+    /// A string which isn't valid UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for LiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiftError::InvalidChar(c) => write!(f, "invalid char `{c:#x}`"),
+            LiftError::InvalidBool(b) => write!(f, "invalid bool discriminant `{b}`"),
+            LiftError::InvalidDiscriminant { ty, discriminant } => {
+                write!(f, "invalid discriminant `{discriminant}` for `{ty}`")
+            }
+            LiftError::InvalidFlags { ty } => write!(f, "invalid bits set for flags `{ty}`"),
+            LiftError::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+        }
+    }
+}
+
+static LIFT_ERROR_HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Sets the function called with malformed values lifted by bindings
+/// generated with `validate_lifts` which can't be returned as an error, that
+/// is in arguments to exports or in results of imports called without their
+/// `try_*` variant.
+///
+/// Without a handler the instance traps.
+pub fn set_lift_error_handler(handler: fn(LiftError) -> !) {
+    LIFT_ERROR_HANDLER.store(handler as *mut (), Ordering::Relaxed);
+}
+
+/// Declares `$name` as the `#[global_allocator]` and defines the
+/// `cabi_realloc` function, through which the host allocates memory for values
+/// passed to the guest, in terms of it.
+///
+/// This is for use when the default `realloc` feature of this crate, which
+/// defines `cabi_realloc` with the global allocator, is disabled, for example
+/// to use an allocator with a smaller code size than the standard library's.
+/// If the feature ends up enabled anyway, by another crate depending on this
+/// one, only the global allocator is declared, which that `cabi_realloc` uses.
+///
+/// The allocator has to be the global allocator because the bindings free
+/// memory allocated through `cabi_realloc` with it: lifted lists and strings
+/// are owned `Vec`s and `String`s, and other buffers are freed once lifted.
+/// As it serves every allocation of the program, including those which live
+/// across calls such as the instance of stateful exports, it must never
+/// release memory which wasn't freed, for example by resetting an arena.
+///
+/// With the `realloc-stats` feature `$name` is wrapped in a
+/// `stats::Counting` allocator, which dereferences to `$ty`.
+///
+/// ```ignore
+/// wit_bindgen_guest_rust::export_cabi_realloc!(static ALLOC: MyAlloc = MyAlloc::new());
+/// ```
+#[macro_export]
+macro_rules! export_cabi_realloc {
+    (static $name:ident: $ty:ty = $init:expr) => {
+        $crate::__global_allocator!(static $name: $ty = $init);
+        $crate::__cabi_realloc!($name);
+    };
+}
+
+// With the `realloc` feature `cabi_realloc` is already defined with the
+// global allocator, which `export_cabi_realloc!` declared.
+#[cfg(feature = "realloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cabi_realloc {
+    ($name:ident) => {};
+}
+
+#[cfg(not(feature = "realloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cabi_realloc {
+    ($name:ident) => {
+        const _: () = {
+            #[no_mangle]
+            unsafe extern "C" fn cabi_realloc(
+                old_ptr: *mut u8,
+                old_len: usize,
+                align: usize,
+                new_len: usize,
+            ) -> *mut u8 {
+                $crate::rt::cabi_realloc_with(&$name, old_ptr, old_len, align, new_len)
+            }
+        };
+    };
+}
+
+#[cfg(not(feature = "realloc-stats"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __global_allocator {
+    (static $name:ident: $ty:ty = $init:expr) => {
+        #[global_allocator]
+        static $name: $ty = $init;
+    };
+}
+
+#[cfg(feature = "realloc-stats")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __global_allocator {
+    (static $name:ident: $ty:ty = $init:expr) => {
+        #[global_allocator]
+        static $name: $crate::stats::Counting<$ty> = $crate::stats::Counting::new($init);
+    };
+}
+
+/// Counters of the memory allocated and freed through the allocator declared
+/// with [`export_cabi_realloc!`], enabled with the `realloc-stats` feature.
+///
+/// This includes the memory the host allocates through `cabi_realloc` when
+/// it passes lists or strings to the guest, as well as its release once they
+/// are lifted and dropped, so comparing [`ReallocStats::live_bytes`] before
+/// and after a call detects leaks.
+#[cfg(feature = "realloc-stats")]
+pub mod stats {
+    use core::alloc::{GlobalAlloc, Layout};
+    use core::ops::Deref;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    static FREES: AtomicUsize = AtomicUsize::new(0);
+    static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    static BYTES_FREED: AtomicUsize = AtomicUsize::new(0);
+
+    /// A snapshot of the counters.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ReallocStats {
+        /// The number of allocations made.
+        pub allocations: usize,
+        /// The number of allocations freed.
+        pub frees: usize,
+        /// The number of bytes allocated, including those added by growing
+        /// existing allocations.
+        pub bytes_allocated: usize,
+        /// The number of bytes freed, including those removed by shrinking
+        /// existing allocations.
+        pub bytes_freed: usize,
+    }
+
+    impl ReallocStats {
+        /// The number of bytes allocated and not freed yet.
+        ///
+        /// This is zero if more bytes were freed than allocated, which
+        /// happens when memory allocated before a `reset` is freed after it.
+        pub fn live_bytes(&self) -> usize {
+            self.bytes_allocated.saturating_sub(self.bytes_freed)
+        }
+    }
+
+    /// Returns the counters accumulated since the start of the program or the
+    /// last call to `reset`.
+    pub fn get() -> ReallocStats {
+        ReallocStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            frees: FREES.load(Ordering::Relaxed),
+            bytes_allocated: BYTES_ALLOCATED.load(Ordering::Relaxed),
+            bytes_freed: BYTES_FREED.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the counters to zero, returning their previous values.
+    ///
+    /// Memory allocated before the reset and freed after it is counted in
+    /// `bytes_freed` only, so the counters are only balanced if nothing was
+    /// allocated at the time of the reset.
+    pub fn reset() -> ReallocStats {
+        ReallocStats {
+            allocations: ALLOCATIONS.swap(0, Ordering::Relaxed),
+            frees: FREES.swap(0, Ordering::Relaxed),
+            bytes_allocated: BYTES_ALLOCATED.swap(0, Ordering::Relaxed),
+            bytes_freed: BYTES_FREED.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// An allocator counting the allocations and frees of `A`, used by
+    /// [`export_cabi_realloc!`](crate::export_cabi_realloc).
+    pub struct Counting<A>(A);
+
+    impl<A> Counting<A> {
+        pub const fn new(allocator: A) -> Counting<A> {
+            Counting(allocator)
+        }
+    }
+
+    impl<A> Deref for Counting<A> {
+        type Target = A;
+
+        fn deref(&self) -> &A {
+            &self.0
+        }
+    }
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.0.alloc(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.0.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            FREES.fetch_add(1, Ordering::Relaxed);
+            BYTES_FREED.fetch_add(layout.size(), Ordering::Relaxed);
+            self.0.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = self.0.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                if new_size > layout.size() {
+                    BYTES_ALLOCATED.fetch_add(new_size - layout.size(), Ordering::Relaxed);
+                } else {
+                    BYTES_FREED.fetch_add(layout.size() - new_size, Ordering::Relaxed);
+                }
+            }
+            new_ptr
+        }
+    }
+}
+
 #[doc(hidden)]
 pub mod rt {
     use super::alloc::alloc::Layout;
 
     // Re-export things from liballoc for convenient use.
-    pub use super::alloc::{alloc, string, vec};
+    pub use super::alloc::{alloc, borrow, string, vec};
+
+    use core::alloc::GlobalAlloc;
 
+    #[cfg(feature = "realloc")]
     #[no_mangle]
     unsafe extern "C" fn cabi_realloc(
         old_ptr: *mut u8,
         old_len: usize,
         align: usize,
         new_len: usize,
+    ) -> *mut u8 {
+        cabi_realloc_with(&DefaultAllocator, old_ptr, old_len, align, new_len)
+    }
+
+    /// Delegates to the global allocator.
+    #[cfg(feature = "realloc")]
+    struct DefaultAllocator;
+
+    #[cfg(feature = "realloc")]
+    unsafe impl GlobalAlloc for DefaultAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            alloc::alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            alloc::dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            alloc::realloc(ptr, layout, new_size)
+        }
+    }
+
+    /// The implementation of `cabi_realloc` in terms of `allocator`, used by
+    /// `export_cabi_realloc!`.
+    pub unsafe fn cabi_realloc_with(
+        allocator: &impl GlobalAlloc,
+        old_ptr: *mut u8,
+        old_len: usize,
+        align: usize,
+        new_len: usize,
     ) -> *mut u8 {
         let layout;
         let ptr = if old_len == 0 {
@@ -29,10 +313,10 @@ pub mod rt {
                 return align as *mut u8;
             }
             layout = Layout::from_size_align_unchecked(new_len, align);
-            alloc::alloc(layout)
+            allocator.alloc(layout)
         } else {
             layout = Layout::from_size_align_unchecked(old_len, align);
-            alloc::realloc(old_ptr, layout, new_len)
+            allocator.realloc(old_ptr, layout, new_len)
         };
         if ptr.is_null() {
             alloc::handle_alloc_error(layout);
@@ -40,6 +324,21 @@ pub mod rt {
         return ptr;
     }
 
+    #[cold]
+    pub fn invalid_lift(error: super::LiftError) -> ! {
+        let handler = super::LIFT_ERROR_HANDLER.load(super::Ordering::Relaxed);
+        if !handler.is_null() {
+            // Only `set_lift_error_handler` stores to the handler.
+            let handler =
+                unsafe { core::mem::transmute::<*mut (), fn(super::LiftError) -> !>(handler) };
+            handler(error);
+        }
+        #[cfg(target_arch = "wasm32")]
+        core::arch::wasm32::unreachable();
+        #[cfg(not(target_arch = "wasm32"))]
+        panic!("{error}");
+    }
+
     pub unsafe fn dealloc(ptr: i32, size: usize, align: usize) {
         if size == 0 {
             return;
@@ -48,6 +347,26 @@ pub mod rt {
         alloc::dealloc(ptr as *mut u8, layout);
     }
 
+    /// Deallocates a buffer once dropped, so that it's also freed when
+    /// lifting the values within it returns early with a `LiftError`.
+    pub struct Dealloc {
+        ptr: i32,
+        size: usize,
+        align: usize,
+    }
+
+    impl Dealloc {
+        pub unsafe fn new(ptr: i32, size: usize, align: usize) -> Dealloc {
+            Dealloc { ptr, size, align }
+        }
+    }
+
+    impl Drop for Dealloc {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.ptr, self.size, self.align) }
+        }
+    }
+
     macro_rules! as_traits {
         ($(($trait_:ident $func:ident $ty:ident <=> $($tys:ident)*))*) => ($(
             pub fn $func<T: $trait_>(t: T) -> $ty {
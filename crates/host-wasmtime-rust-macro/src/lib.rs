@@ -1,7 +1,8 @@
 use proc_macro::TokenStream;
 use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
 use syn::Token;
-use wit_bindgen_gen_host_wasmtime_rust::Opts;
+use wit_bindgen_gen_host_wasmtime_rust::{Async, Opts};
 
 #[proc_macro]
 pub fn generate(input: TokenStream) -> TokenStream {
@@ -10,11 +11,29 @@ pub fn generate(input: TokenStream) -> TokenStream {
 
 mod kw {
     syn::custom_keyword!(tracing);
+    syn::custom_keyword!(additional_derives);
+    syn::custom_keyword!(type_attributes);
+    syn::custom_keyword!(with);
+    syn::custom_keyword!(traits_only);
+    syn::custom_keyword!(traits_cfg);
+    syn::custom_keyword!(traits_module);
+    syn::custom_keyword!(call_errors);
+    syn::custom_keyword!(error_types);
+    syn::custom_keyword!(only);
+    syn::custom_keyword!(except);
 }
 
 enum Opt {
     Tracing(bool),
-    Async(bool),
+    Async(Option<Async>),
+    AdditionalDerives(Vec<String>),
+    TypeAttributes(Vec<(String, String)>),
+    With(Vec<(String, String)>),
+    TraitsOnly,
+    TraitsCfg(syn::LitStr),
+    TraitsModule(syn::LitStr),
+    CallErrors(bool),
+    ErrorTypes(Vec<(String, String, String)>),
 }
 
 impl Parse for Opt {
@@ -28,7 +47,71 @@ impl Parse for Opt {
         } else if l.peek(Token![async]) {
             input.parse::<Token![async]>()?;
             input.parse::<Token![:]>()?;
-            Ok(Opt::Async(input.parse::<syn::LitBool>()?.value))
+            if !input.peek(syn::token::Brace) {
+                let async_ = input.parse::<syn::LitBool>()?.value;
+                return Ok(Opt::Async(if async_ { Some(Async::All) } else { None }));
+            }
+            let contents;
+            syn::braced!(contents in input);
+            let l = contents.lookahead1();
+            let only = if l.peek(kw::only) {
+                contents.parse::<kw::only>()?;
+                true
+            } else if l.peek(kw::except) {
+                contents.parse::<kw::except>()?;
+                false
+            } else {
+                return Err(l.error());
+            };
+            contents.parse::<Token![:]>()?;
+            let list;
+            syn::bracketed!(list in contents);
+            let names = Punctuated::<syn::LitStr, Token![,]>::parse_terminated(&list)?
+                .into_iter()
+                .map(|name| name.value())
+                .collect();
+            if only {
+                Ok(Opt::Async(Some(Async::Only(names))))
+            } else {
+                Ok(Opt::Async(Some(Async::Except(names))))
+            }
+        } else if l.peek(kw::additional_derives) {
+            input.parse::<kw::additional_derives>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::AdditionalDerives(
+                wit_bindgen_rust_macro_shared::parse_derives(input)?,
+            ))
+        } else if l.peek(kw::type_attributes) {
+            input.parse::<kw::type_attributes>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::TypeAttributes(
+                wit_bindgen_rust_macro_shared::parse_type_attributes(input)?,
+            ))
+        } else if l.peek(kw::with) {
+            input.parse::<kw::with>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::With(wit_bindgen_rust_macro_shared::parse_with(input)?))
+        } else if l.peek(kw::traits_only) {
+            input.parse::<kw::traits_only>()?;
+            Ok(Opt::TraitsOnly)
+        } else if l.peek(kw::traits_cfg) {
+            input.parse::<kw::traits_cfg>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::TraitsCfg(input.parse()?))
+        } else if l.peek(kw::traits_module) {
+            input.parse::<kw::traits_module>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::TraitsModule(input.parse()?))
+        } else if l.peek(kw::call_errors) {
+            input.parse::<kw::call_errors>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::CallErrors(input.parse::<syn::LitBool>()?.value))
+        } else if l.peek(kw::error_types) {
+            input.parse::<kw::error_types>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::ErrorTypes(
+                wit_bindgen_rust_macro_shared::parse_error_types(input)?,
+            ))
         } else {
             Err(l.error())
         }
@@ -40,6 +123,14 @@ impl wit_bindgen_rust_macro_shared::Configure<Opts> for Opt {
         match self {
             Opt::Tracing(val) => opts.tracing = val,
             Opt::Async(val) => opts.async_ = val,
+            Opt::AdditionalDerives(derives) => opts.additional_derives.extend(derives),
+            Opt::TypeAttributes(attrs) => opts.type_attributes.extend(attrs),
+            Opt::With(with) => opts.with.extend(with),
+            Opt::TraitsOnly => opts.traits_only = true,
+            Opt::TraitsCfg(cfg) => opts.traits_cfg = Some(cfg.value()),
+            Opt::TraitsModule(path) => opts.traits_module = Some(path.value()),
+            Opt::CallErrors(val) => opts.call_errors = val,
+            Opt::ErrorTypes(types) => opts.error_types.extend(types),
         }
     }
 }
@@ -0,0 +1,238 @@
+use std::fmt;
+
+/// An error calling an export of a guest through bindings generated with
+/// `call_errors`, recording which export was called and classifying why the
+/// call failed.
+pub struct CallError {
+    interface: Option<&'static str>,
+    function: &'static str,
+    kind: CallErrorKind,
+    err: anyhow::Error,
+}
+
+/// The classification of a [`CallError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CallErrorKind {
+    /// The guest panicked, that is it executed an `unreachable` instruction.
+    GuestPanic,
+    /// The guest accessed its memory or a table out of bounds.
+    OutOfBounds,
+    /// The guest trapped for another reason.
+    Trap(wasmtime::TrapCode),
+    /// A host import called by the guest returned an error, see
+    /// [`HostError`].
+    Host,
+    /// The guest violated the canonical ABI, for example by returning a
+    /// variant with an invalid discriminant or a string out of bounds of its
+    /// memory. Wasmtime reports these as traps without a trap code.
+    Abi,
+    /// The call failed without trapping, for example because the store ran
+    /// out of fuel or the export was called with the wrong instance.
+    Other,
+}
+
+impl CallError {
+    /// Classifies `err`, the error calling `function` of the exported
+    /// `interface`, or of the default export if `None`.
+    pub fn new(
+        interface: Option<&'static str>,
+        function: &'static str,
+        err: anyhow::Error,
+    ) -> CallError {
+        // Errors of host imports reach the caller wrapped in the trap they
+        // caused, so look for the `HostError` in the whole chain.
+        let kind = if err.chain().any(|e| e.is::<HostError>()) {
+            CallErrorKind::Host
+        } else {
+            match err
+                .downcast_ref::<wasmtime::Trap>()
+                .map(|trap| trap.trap_code())
+            {
+                Some(Some(wasmtime::TrapCode::UnreachableCodeReached)) => CallErrorKind::GuestPanic,
+                Some(Some(
+                    wasmtime::TrapCode::MemoryOutOfBounds
+                    | wasmtime::TrapCode::HeapMisaligned
+                    | wasmtime::TrapCode::TableOutOfBounds,
+                )) => CallErrorKind::OutOfBounds,
+                Some(Some(code)) => CallErrorKind::Trap(code),
+                Some(None) => CallErrorKind::Abi,
+                None => CallErrorKind::Other,
+            }
+        };
+        CallError {
+            interface,
+            function,
+            kind,
+            err,
+        }
+    }
+
+    /// The exported interface of the function called, or `None` for the
+    /// default export.
+    pub fn interface(&self) -> Option<&'static str> {
+        self.interface
+    }
+
+    /// The name of the function called.
+    pub fn function(&self) -> &'static str {
+        self.function
+    }
+
+    pub fn kind(&self) -> CallErrorKind {
+        self.kind
+    }
+
+    /// The backtrace of the guest when it failed, if wasmtime captured one.
+    pub fn backtrace(&self) -> Option<&[wasmtime::FrameInfo]> {
+        self.err.downcast_ref::<wasmtime::Trap>()?.trace()
+    }
+
+    /// The context of the error returned by a host import, for the
+    /// [`CallErrorKind::Host`] kind.
+    pub fn host_error(&self) -> Option<&HostError> {
+        self.err.chain().find_map(|e| e.downcast_ref())
+    }
+
+    pub fn into_inner(self) -> anyhow::Error {
+        self.err
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.interface {
+            Some(interface) => write!(f, "failed to call `{interface}#{}`", self.function)?,
+            None => write!(f, "failed to call `{}`", self.function)?,
+        }
+        match self.kind {
+            CallErrorKind::GuestPanic => write!(f, ": the guest panicked"),
+            CallErrorKind::OutOfBounds => write!(f, ": out of bounds access by the guest"),
+            CallErrorKind::Trap(code) => write!(f, ": {code}"),
+            CallErrorKind::Host => write!(f, ": a host import failed"),
+            CallErrorKind::Abi => write!(f, ": invalid use of the canonical ABI by the guest"),
+            CallErrorKind::Other => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallError")
+            .field("interface", &self.interface)
+            .field("function", &self.function)
+            .field("kind", &self.kind)
+            .field("err", &self.err)
+            .finish()
+    }
+}
+
+impl std::error::Error for CallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.err.as_ref())
+    }
+}
+
+/// The cause attached to the errors of host imports generated with
+/// `call_errors`, so that [`CallError`] can tell them apart from traps.
+///
+/// The error returned by the import is kept as the context of this one, so
+/// that its message is still the message of the trap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostError {
+    pub interface: &'static str,
+    pub function: &'static str,
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "host import `{}#{}` returned an error",
+            self.interface, self.function
+        )
+    }
+}
+
+impl std::error::Error for HostError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime::{Engine, Instance, Module, Store, Trap};
+
+    fn trap(body: &str) -> anyhow::Error {
+        let engine = Engine::default();
+        let module = Module::new(
+            &engine,
+            format!("(module (memory 1) (func (export \"f\") {body}))"),
+        )
+        .unwrap();
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).unwrap();
+        let f = instance
+            .get_typed_func::<(), (), _>(&mut store, "f")
+            .unwrap();
+        anyhow::Error::from(f.call(&mut store, ()).unwrap_err())
+    }
+
+    fn kind(err: anyhow::Error) -> CallErrorKind {
+        CallError::new(Some("exports"), "f", err).kind()
+    }
+
+    #[test]
+    fn classify_traps() {
+        assert_eq!(kind(trap("unreachable")), CallErrorKind::GuestPanic);
+        assert_eq!(
+            kind(trap("i32.const -1 i32.load drop")),
+            CallErrorKind::OutOfBounds
+        );
+        assert_eq!(
+            kind(trap("i32.const 1 i32.const 0 i32.div_u drop")),
+            CallErrorKind::Trap(wasmtime::TrapCode::IntegerDivisionByZero)
+        );
+    }
+
+    #[test]
+    fn classify_abi_errors() {
+        // Lifting invalid values out of the guest fails with a trap that
+        // carries a message but no trap code.
+        let err = anyhow::Error::from(Trap::new("invalid variant discriminant"));
+        assert_eq!(kind(err), CallErrorKind::Abi);
+    }
+
+    #[test]
+    fn classify_other_errors() {
+        let err = CallError::new(None, "f", anyhow::anyhow!("wrong store"));
+        assert_eq!(err.kind(), CallErrorKind::Other);
+        assert_eq!(err.to_string(), "failed to call `f`");
+    }
+
+    #[test]
+    fn classify_host_errors() {
+        let host_error = HostError {
+            interface: "imports",
+            function: "g",
+        };
+        let err = anyhow::Error::new(host_error).context(anyhow::anyhow!("failed"));
+        let err = CallError::new(None, "f", err);
+        assert_eq!(err.kind(), CallErrorKind::Host);
+        assert_eq!(err.host_error(), Some(&host_error));
+        assert!(err.backtrace().is_none());
+
+        // With more context, like that of the trap the error caused.
+        let err = anyhow::Error::new(host_error)
+            .context(anyhow::anyhow!("failed"))
+            .context("wasm trap");
+        let err = CallError::new(None, "f", err);
+        assert_eq!(err.kind(), CallErrorKind::Host);
+        assert_eq!(err.host_error(), Some(&host_error));
+    }
+
+    #[test]
+    fn backtrace() {
+        let err = CallError::new(None, "f", trap("unreachable"));
+        assert!(err.backtrace().is_some());
+        assert_eq!(err.to_string(), "failed to call `f`: the guest panicked");
+    }
+}
@@ -1,5 +1,8 @@
 pub use wit_bindgen_host_wasmtime_rust_macro::*;
 
+mod call_error;
+pub use call_error::*;
+
 #[cfg(feature = "tracing-lib")]
 pub use tracing_lib as tracing;
 #[doc(hidden)]
@@ -2,12 +2,13 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
+use quote::ToTokens;
 use std::marker;
 use std::path::{Path, PathBuf};
 use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::{token, Token};
-use wit_bindgen_core::{wit_parser::Interface, Files, WorldGenerator};
+use wit_bindgen_core::{wit_parser::Interface, Files, Filter, WorldGenerator};
 use wit_component::ComponentInterfaces;
 
 pub fn generate<F, O>(
@@ -18,7 +19,7 @@ where
     F: Parse + Configure<O>,
     O: Default,
 {
-    let input = syn::parse_macro_input!(input as Opts<F, O>);
+    let mut input = syn::parse_macro_input!(input as Opts<F, O>);
     let mut gen = mkgen(input.opts);
     let mut files = Files::default();
     let name = match &input.name {
@@ -29,6 +30,13 @@ where
                 .into()
         }
     };
+    if let Err(e) = input
+        .filter
+        .apply(&mut input.interfaces)
+        .and_then(|()| gen.validate(&input.interfaces))
+    {
+        return Error::new(Span::call_site(), e).to_compile_error().into();
+    }
     gen.generate(name, &input.interfaces, &mut files);
 
     let (_, contents) = files.iter().next().unwrap();
@@ -36,13 +44,14 @@ where
     let contents = std::str::from_utf8(contents).unwrap();
     let mut contents = contents.parse::<TokenStream>().unwrap();
 
-    // Include a dummy `include_str!` for any files we read so rustc knows that
-    // we depend on the contents of those files.
+    // Include a dummy `include_bytes!` for any files we read so rustc knows
+    // that we depend on the contents of those files. Bytes are used since WIT
+    // packages are binary files.
     let cwd = std::env::var("CARGO_MANIFEST_DIR").unwrap();
     for file in input.files.iter() {
         contents.extend(
             format!(
-                "const _: &str = include_str!(r#\"{}\"#);\n",
+                "const _: &[u8] = include_bytes!(r#\"{}\"#);\n",
                 Path::new(&cwd).join(file).display()
             )
             .parse::<TokenStream>()
@@ -62,6 +71,7 @@ struct Opts<F, O> {
     interfaces: ComponentInterfaces,
     name: Option<String>,
     files: Vec<String>,
+    filter: Filter,
     _marker: marker::PhantomData<F>,
 }
 
@@ -73,6 +83,12 @@ mod kw {
     syn::custom_keyword!(export);
     syn::custom_keyword!(default);
     syn::custom_keyword!(name);
+    syn::custom_keyword!(path);
+    syn::custom_keyword!(world);
+    syn::custom_keyword!(allow_functions);
+    syn::custom_keyword!(deny_functions);
+    syn::custom_keyword!(allow_types);
+    syn::custom_keyword!(deny_types);
 }
 
 impl<F, O> Parse for Opts<F, O>
@@ -81,21 +97,35 @@ where
     O: Default,
 {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let call_site = proc_macro2::Span::call_site();
         let mut ret = Opts {
             opts: O::default(),
             interfaces: ComponentInterfaces::default(),
             files: Vec::new(),
             name: None,
+            filter: Filter::default(),
             _marker: marker::PhantomData,
         };
 
+        let mut world_path = None;
+        let mut world_name = None;
         if input.peek(token::Brace) {
             let content;
             syn::braced!(content in input);
             let fields = Punctuated::<ConfigField<F>, Token![,]>::parse_terminated(&content)?;
             for field in fields.into_pairs() {
                 match field.into_value() {
+                    ConfigField::Path(path) => {
+                        if world_path.is_some() {
+                            return Err(Error::new(path.span(), "cannot specify `path` twice"));
+                        }
+                        world_path = Some(path);
+                    }
+                    ConfigField::World(name) => {
+                        if world_name.is_some() {
+                            return Err(Error::new(name.span(), "cannot specify `world` twice"));
+                        }
+                        world_name = Some(name);
+                    }
                     ConfigField::Import(span, i) => ret.import(span, i)?,
                     ConfigField::ImportPath(name, path) => {
                         let span = path.span();
@@ -120,18 +150,41 @@ where
                         }
                         ret.name = Some(name.value());
                     }
+                    ConfigField::AllowFunctions(items) => {
+                        for (interface, name) in items {
+                            ret.filter.allow_function(&interface, &name);
+                        }
+                    }
+                    ConfigField::DenyFunctions(items) => {
+                        for (interface, name) in items {
+                            ret.filter.deny_function(&interface, &name);
+                        }
+                    }
+                    ConfigField::AllowTypes(items) => {
+                        for (interface, name) in items {
+                            ret.filter.allow_type(&interface, &name);
+                        }
+                    }
+                    ConfigField::DenyTypes(items) => {
+                        for (interface, name) in items {
+                            ret.filter.deny_type(&interface, &name);
+                        }
+                    }
                     ConfigField::Other(other) => other.configure(&mut ret.opts),
                 }
             }
         } else {
-            while !input.is_empty() {
-                let s = input.parse::<syn::LitStr>()?;
-                ret.files.push(s.value());
+            world_path = Some(input.parse::<syn::LitStr>()?);
+        }
+        match (world_path, world_name) {
+            (Some(path), name) => ret.world(path, name)?,
+            (None, Some(name)) => {
+                return Err(Error::new(
+                    name.span(),
+                    "`world` requires a `path` to read it from",
+                ))
             }
-            return Err(Error::new(
-                call_site,
-                "string inputs won't be supported until `world` syntax is implemented",
-            ));
+            (None, None) => {}
         }
         Ok(ret)
     }
@@ -144,11 +197,42 @@ impl<F, O> Opts<F, O> {
         let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
         let path = manifest_dir.join(path);
         self.files.push(path.to_str().unwrap().to_string());
-        let mut file = Interface::parse_file(path).map_err(|e| Error::new(span, e))?;
-        if let Some(name) = name {
-            file.name = name.value();
+        let name = name.map(|name| name.value());
+        wit_component::read_interface(path, name.as_deref()).map_err(|e| Error::new(span, e))
+    }
+
+    /// Reads the world at `path`, a world file or directory relative to the
+    /// crate, adding its interfaces to those specified explicitly.
+    fn world(&mut self, path: syn::LitStr, name: Option<syn::LitStr>) -> Result<()> {
+        let span = path.span();
+        let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        let name = name.map(|name| name.value());
+        let world = wit_component::read_world(manifest_dir.join(path.value()), name.as_deref())
+            .map_err(|e| Error::new(span, format!("{e:#}")))?;
+        self.files.extend(
+            world
+                .files
+                .iter()
+                .map(|file| file.to_str().unwrap().to_string()),
+        );
+        let ComponentInterfaces {
+            imports,
+            exports,
+            default,
+        } = world.interfaces;
+        for (_, i) in imports {
+            self.import(span, i)?;
+        }
+        for (_, i) in exports {
+            self.export(span, i)?;
         }
-        Ok(file)
+        if let Some(i) = default {
+            self.interface(span, i)?;
+        }
+        if self.name.is_none() {
+            self.name = Some(world.name);
+        }
+        Ok(())
     }
 
     fn import(&mut self, span: Span, i: Interface) -> Result<()> {
@@ -182,6 +266,12 @@ enum ConfigField<F> {
     Default(Span, Interface),
     DefaultPath(Option<syn::LitStr>, syn::LitStr),
     Name(syn::LitStr),
+    Path(syn::LitStr),
+    World(syn::LitStr),
+    AllowFunctions(Vec<(String, String)>),
+    DenyFunctions(Vec<(String, String)>),
+    AllowTypes(Vec<(String, String)>),
+    DenyTypes(Vec<(String, String)>),
     Other(F),
 }
 
@@ -217,6 +307,30 @@ impl<F: Parse> Parse for ConfigField<F> {
             input.parse::<kw::name>()?;
             input.parse::<Token![:]>()?;
             Ok(ConfigField::Name(input.parse()?))
+        } else if l.peek(kw::path) {
+            input.parse::<kw::path>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::Path(input.parse()?))
+        } else if l.peek(kw::world) {
+            input.parse::<kw::world>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::World(input.parse()?))
+        } else if l.peek(kw::allow_functions) {
+            input.parse::<kw::allow_functions>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::AllowFunctions(parse_items(input)?))
+        } else if l.peek(kw::deny_functions) {
+            input.parse::<kw::deny_functions>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::DenyFunctions(parse_items(input)?))
+        } else if l.peek(kw::allow_types) {
+            input.parse::<kw::allow_types>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::AllowTypes(parse_items(input)?))
+        } else if l.peek(kw::deny_types) {
+            input.parse::<kw::deny_types>()?;
+            input.parse::<Token![:]>()?;
+            Ok(ConfigField::DenyTypes(parse_items(input)?))
         } else {
             Ok(ConfigField::Other(input.parse()?))
         }
@@ -240,3 +354,116 @@ fn parse_opt_name(input: ParseStream<'_>) -> Result<Option<syn::LitStr>> {
     syn::bracketed!(name in input);
     Ok(Some(name.parse::<syn::LitStr>()?))
 }
+
+/// Parses a list of `"INTERFACE#NAME"` strings naming items to filter.
+fn parse_items(input: ParseStream<'_>) -> Result<Vec<(String, String)>> {
+    let items;
+    syn::bracketed!(items in input);
+    let items = Punctuated::<syn::LitStr, Token![,]>::parse_terminated(&items)?;
+    items
+        .iter()
+        .map(|item| {
+            let value = item.value();
+            let (interface, name) =
+                Filter::parse_item(&value).map_err(|e| Error::new(item.span(), e))?;
+            Ok((interface.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a list of paths of traits to derive, such as
+/// `[Hash, serde::Serialize]`, for the `additional_derives` option of Rust
+/// generators.
+pub fn parse_derives(input: ParseStream<'_>) -> Result<Vec<String>> {
+    let derives;
+    syn::bracketed!(derives in input);
+    let derives = Punctuated::<syn::Path, Token![,]>::parse_terminated(&derives)?;
+    Ok(derives.iter().map(path_to_string).collect())
+}
+
+/// Parses a map of type names to attributes, such as
+/// `{ "point": #[non_exhaustive] }`, for the `type_attributes` option of Rust
+/// generators.
+pub fn parse_type_attributes(input: ParseStream<'_>) -> Result<Vec<(String, String)>> {
+    let contents;
+    syn::braced!(contents in input);
+    let mut ret = Vec::new();
+    while !contents.is_empty() {
+        let ty = contents.parse::<syn::LitStr>()?.value();
+        contents.parse::<Token![:]>()?;
+        for attr in syn::Attribute::parse_outer(&contents)? {
+            ret.push((
+                ty.clone(),
+                format!("#[{}{}]", path_to_string(&attr.path), attr.tokens),
+            ));
+        }
+        if contents.is_empty() {
+            break;
+        }
+        contents.parse::<Token![,]>()?;
+    }
+    Ok(ret)
+}
+
+/// Stringifies `path` including any generic arguments of its segments, such
+/// as those of `Box<dyn std::error::Error>`.
+fn path_to_string(path: &syn::Path) -> String {
+    path.to_token_stream().to_string()
+}
+
+/// Parses a map of WIT type names to existing Rust types, such as
+/// `{ "types.timestamp": crate::Timestamp }`, for the `with` option of Rust
+/// generators.
+pub fn parse_with(input: ParseStream<'_>) -> Result<Vec<(String, String)>> {
+    let contents;
+    syn::braced!(contents in input);
+    let mut ret = Vec::new();
+    while !contents.is_empty() {
+        let ty = contents.parse::<syn::LitStr>()?;
+        if !ty.value().contains('.') {
+            return Err(Error::new(
+                ty.span(),
+                "expected a type named as `MODULE.TYPE`",
+            ));
+        }
+        contents.parse::<Token![:]>()?;
+        let path = contents.parse::<syn::Path>()?;
+        ret.push((ty.value(), path_to_string(&path)));
+        if contents.is_empty() {
+            break;
+        }
+        contents.parse::<Token![,]>()?;
+    }
+    Ok(ret)
+}
+
+/// Parses `{ "MODULE.TYPE": RUST_TYPE => CONVERT, ... }`, mapping WIT error
+/// types to host Rust error types and the functions converting them back.
+pub fn parse_error_types(input: ParseStream<'_>) -> Result<Vec<(String, String, String)>> {
+    let contents;
+    syn::braced!(contents in input);
+    let mut ret = Vec::new();
+    while !contents.is_empty() {
+        let ty = contents.parse::<syn::LitStr>()?;
+        if !ty.value().contains('.') {
+            return Err(Error::new(
+                ty.span(),
+                "expected a type named as `MODULE.TYPE`",
+            ));
+        }
+        contents.parse::<Token![:]>()?;
+        let rust_ty = contents.parse::<syn::Path>()?;
+        contents.parse::<Token![=>]>()?;
+        let convert = contents.parse::<syn::Path>()?;
+        ret.push((
+            ty.value(),
+            path_to_string(&rust_ty),
+            path_to_string(&convert),
+        ));
+        if contents.is_empty() {
+            break;
+        }
+        contents.parse::<Token![,]>()?;
+    }
+    Ok(ret)
+}
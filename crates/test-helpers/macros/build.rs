@@ -29,6 +29,29 @@ fn main() {
     let wasi_adapter = out_dir.join("wasm32-unknown-unknown/release/wasi_snapshot_preview1.wasm");
     println!("wasi adapter: {:?}", &wasi_adapter);
 
+    // The adapter disables the `cabi_realloc` of `wit-bindgen-guest-rust`,
+    // which would pull in an allocator and with it an element section making
+    // the adapter unusable, in favor of its own trapping one.
+    let adapter = fs::read(&wasi_adapter).unwrap();
+    let mut exports_realloc = false;
+    for payload in wasmparser::Parser::new(0).parse_all(&adapter) {
+        match payload.unwrap() {
+            wasmparser::Payload::ElementSection(_) => {
+                panic!("the wasi adapter shouldn't have an element section")
+            }
+            wasmparser::Payload::ExportSection(exports) => {
+                for export in exports {
+                    exports_realloc |= export.unwrap().name == "cabi_realloc";
+                }
+            }
+            _ => {}
+        }
+    }
+    assert!(
+        exports_realloc,
+        "the wasi adapter should export `cabi_realloc`"
+    );
+
     if cfg!(feature = "guest-rust") {
         let mut cmd = Command::new("cargo");
         cmd.arg("build")
@@ -0,0 +1,3 @@
+include!("../../../../tests/runtime/borrowed_results/wasm.rs");
+
+fn main() {}
@@ -0,0 +1,3 @@
+include!("../../../../tests/runtime/smoke/wasm.rs");
+
+fn main() {}
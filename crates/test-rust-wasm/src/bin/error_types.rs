@@ -0,0 +1,3 @@
+include!("../../../../tests/runtime/results/wasm.rs");
+
+fn main() {}
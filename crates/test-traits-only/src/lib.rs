@@ -0,0 +1,15 @@
+//! Types generated with `traits_only`, which derive the `wasmtime` traits
+//! only with the `wasmtime` feature of this crate, through `traits_cfg`.
+
+wit_bindgen_host_wasmtime_rust_macro::generate!({
+    import_str["logger"]: "
+        record entry { level: level, message: string }
+        enum level { info, warn }
+        variant target { console, file(string) }
+
+        log: func(entry: entry, target: target)
+    ",
+    name: "types",
+    traits_only,
+    traits_cfg: "feature = \"wasmtime\"",
+});
@@ -0,0 +1,27 @@
+// With the `wasmtime` feature the types derive the `wasmtime` traits, so
+// bindings generated with `traits_module` can use them.
+#![cfg(feature = "wasmtime")]
+
+use test_traits_only::logger::{Entry, Level, Target};
+use wasmtime::component::{ComponentType, Lift, Lower};
+
+wit_bindgen_host_wasmtime_rust::generate!({
+    import_str["logger"]: "
+        record entry { level: level, message: string }
+        enum level { info, warn }
+        variant target { console, file(string) }
+
+        log: func(entry: entry, target: target)
+    ",
+    name: "types",
+    traits_module: "test_traits_only",
+});
+
+fn assert_component_type<T: ComponentType + Lift + Lower>() {}
+
+#[test]
+fn derives() {
+    assert_component_type::<Entry>();
+    assert_component_type::<Level>();
+    assert_component_type::<Target>();
+}
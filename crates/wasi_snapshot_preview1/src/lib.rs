@@ -23,12 +23,12 @@ wit_bindgen_guest_rust::generate!({
     name: "testwasi",
 });
 
-// Nothing in this wasm module should end up needing cabi_realloc. However, if
-// we don't define this trapping implementation of the export, we'll pull in
-// the one from wit_bindgen_guest_rust, which will pull in the libc allocator
-// and a bunch of panic related machinery from std, which will use vtables
-// and therefore create a Wasm ElementSection, which will make the resulting
-// wasm unusable as an adapter module.
+// Nothing in this wasm module should end up needing cabi_realloc, so the
+// default one from wit_bindgen_guest_rust is disabled: it would pull in the
+// libc allocator and a bunch of panic related machinery from std, which will
+// use vtables and therefore create a Wasm ElementSection, which will make the
+// resulting wasm unusable as an adapter module. The export is still defined,
+// trapping, in case anything does end up needing it.
 #[no_mangle]
 unsafe extern "C" fn cabi_realloc(
     old_ptr: *mut u8,
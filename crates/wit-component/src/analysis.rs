@@ -0,0 +1,837 @@
+//! Support for analyzing the structure of a component.
+//!
+//! This is primarily intended to help understand the output of this crate's
+//! [`ComponentEncoder`](crate::ComponentEncoder), for example to figure out
+//! why a component is large or what is wired up to what internally.
+
+use crate::decoding::ComponentInfo;
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use std::fmt::{self, Write};
+use wasmparser::{
+    CanonicalFunction, CanonicalOption, ComponentAlias, ComponentExternalKind, ComponentTypeRef,
+    ExternalKind, Instance, Parser, Payload,
+};
+
+/// A report about the internal structure of a component.
+///
+/// This is created by [`analyze_component`] and can be rendered with its
+/// `Display` implementation, [`ComponentReport::to_dot`], or
+/// [`ComponentReport::to_json`].
+#[derive(Debug, Default)]
+pub struct ComponentReport {
+    /// The total size, in bytes, of the component.
+    pub size: usize,
+    /// The names of the component's imports.
+    pub imports: Vec<String>,
+    /// The names of the component's exports.
+    pub exports: Vec<String>,
+    /// All core wasm modules defined within the component.
+    pub modules: Vec<CoreModuleReport>,
+    /// All core wasm instances of the component in the order they're created.
+    pub instances: Vec<CoreInstanceReport>,
+    /// How the imports of each instantiated core wasm module are satisfied.
+    pub links: Vec<ImportLink>,
+    /// All `canon lift` and `canon lower` definitions within the component.
+    pub canonicals: Vec<CanonicalReport>,
+}
+
+/// Information about a core wasm module defined within a component.
+#[derive(Debug)]
+pub struct CoreModuleReport {
+    /// The index of this module in the component's core module index space.
+    pub index: u32,
+    /// The total size, in bytes, of this module.
+    pub size: usize,
+    /// The size, in bytes, of each section in this module in the order they
+    /// appear. Custom sections are named `custom:<name>`.
+    pub sections: Vec<(String, usize)>,
+}
+
+/// Information about a core wasm instance created within a component.
+#[derive(Debug)]
+pub struct CoreInstanceReport {
+    /// The index of this instance in the component's core instance index
+    /// space.
+    pub index: u32,
+    /// How this instance is created.
+    pub kind: CoreInstanceKind,
+}
+
+/// The ways a core wasm instance can be created.
+#[derive(Debug)]
+pub enum CoreInstanceKind {
+    /// The instance is an instantiation of a core module, with the core
+    /// instances given as named arguments.
+    Instantiate {
+        /// The index of the module being instantiated.
+        module: u32,
+        /// The name and instance index of each instantiation argument.
+        args: Vec<(String, u32)>,
+    },
+    /// The instance is a "bag of exports" synthesized from other items.
+    FromExports(Vec<String>),
+}
+
+/// Describes what satisfies one import of an instantiated core module.
+#[derive(Debug)]
+pub struct ImportLink {
+    /// The core instance being created.
+    pub instance: u32,
+    /// The module that `instance` is an instantiation of.
+    pub module: u32,
+    /// The import module name being satisfied.
+    pub name: String,
+    /// What the items provided for `name` originate from.
+    pub providers: Vec<Provider>,
+}
+
+/// The origin of an item provided to a core module instantiation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provider {
+    /// The item is exported from another instantiated core module, such as an
+    /// adapter module.
+    Instance {
+        /// The core instance providing the item.
+        instance: u32,
+        /// The module that `instance` is an instantiation of.
+        module: u32,
+    },
+    /// The item is a `canon lower` of a function from the component import of
+    /// this name.
+    Import(String),
+    /// The origin of the item couldn't be determined.
+    Unknown,
+}
+
+/// The kind of a canonical function definition.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CanonicalKind {
+    /// A `canon lift` of a core function into a component function.
+    Lift,
+    /// A `canon lower` of a component function into a core function.
+    Lower,
+}
+
+/// Information about a `canon lift` or `canon lower` definition.
+#[derive(Debug)]
+pub struct CanonicalReport {
+    /// Whether this is a lift or a lower.
+    pub kind: CanonicalKind,
+    /// A description of the function being lifted or lowered.
+    pub func: String,
+    /// A description of each canonical option specified.
+    pub options: Vec<String>,
+}
+
+/// The origin of an item in one of the component's core index spaces.
+#[derive(Clone, Copy)]
+enum CoreItem<'a> {
+    /// An alias of the export `name` of a core instance.
+    Export { instance: u32, name: &'a str },
+    /// A `canon lower` of the component function specified.
+    Lower(u32),
+}
+
+/// The origin of an item in one of the component's index spaces.
+#[derive(Clone, Copy)]
+enum ComponentItem<'a> {
+    /// The import of this name.
+    Import(&'a str),
+    /// An alias of the export `name` of a component instance.
+    Export { instance: u32, name: &'a str },
+    /// A `canon lift` of the core function specified.
+    Lift(u32),
+    /// Something else not otherwise tracked.
+    Other,
+}
+
+/// Index spaces of a component, tracked while walking its sections.
+#[derive(Default)]
+struct State<'a> {
+    core_funcs: Vec<CoreItem<'a>>,
+    core_tables: Vec<CoreItem<'a>>,
+    core_memories: Vec<CoreItem<'a>>,
+    core_globals: Vec<CoreItem<'a>>,
+    core_instances: Vec<Instance<'a>>,
+    funcs: Vec<ComponentItem<'a>>,
+    instances: Vec<ComponentItem<'a>>,
+
+    /// Map from a function exported by a "shim" instance to the function which
+    /// is placed into the shim's table by a later "fixup" instantiation.
+    shim_targets: IndexMap<(u32, &'a str), u32>,
+
+    /// Canonical functions as they're found, resolved at the end.
+    canonicals: Vec<(CanonicalKind, u32, Box<[CanonicalOption]>)>,
+}
+
+/// Analyzes the structure of the component `bytes`.
+///
+/// The component is validated and then walked to learn about the core modules
+/// it contains, how they're instantiated and linked together, and how
+/// functions are lifted and lowered. The returned report is suitable for
+/// understanding the size and shape of a component.
+///
+/// Indirect lowerings and adapter functions produced by this crate's encoder
+/// are resolved through the "shim" table they're called through, meaning that
+/// the providers reported in [`ComponentReport::links`] are the adapter
+/// instances and imports that actually implement a function.
+pub fn analyze_component(bytes: &[u8]) -> Result<ComponentReport> {
+    let info = ComponentInfo::new(bytes)?;
+    let mut report = ComponentReport {
+        size: bytes.len(),
+        imports: info.imports.keys().map(|s| s.to_string()).collect(),
+        exports: info.exports.keys().map(|s| s.to_string()).collect(),
+        ..Default::default()
+    };
+    let mut state = State::default();
+    let mut depth = 0;
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        let payload = payload?;
+        match payload {
+            Payload::Version { .. } => {
+                depth += 1;
+                continue;
+            }
+            Payload::End(_) => {
+                depth -= 1;
+                continue;
+            }
+            _ => {}
+        }
+        if depth != 1 {
+            continue;
+        }
+
+        match payload {
+            Payload::ModuleSection { range, .. } => {
+                report.modules.push(CoreModuleReport {
+                    index: report.modules.len() as u32,
+                    size: range.end - range.start,
+                    sections: section_sizes(&bytes[range])?,
+                });
+            }
+            Payload::ComponentSection { .. } => bail!("nested components are not supported"),
+            Payload::InstanceSection(s) => {
+                for instance in s {
+                    state.core_instances.push(instance?);
+                }
+            }
+            Payload::ComponentInstanceSection(s) => {
+                for instance in s {
+                    instance?;
+                    state.instances.push(ComponentItem::Other);
+                }
+            }
+            Payload::ComponentImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    match import.ty {
+                        ComponentTypeRef::Func(_) => {
+                            state.funcs.push(ComponentItem::Import(import.name))
+                        }
+                        ComponentTypeRef::Instance(_) => {
+                            state.instances.push(ComponentItem::Import(import.name))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Payload::ComponentAliasSection(s) => {
+                for alias in s {
+                    match alias? {
+                        ComponentAlias::CoreInstanceExport {
+                            kind,
+                            instance_index,
+                            name,
+                        } => {
+                            let item = CoreItem::Export {
+                                instance: instance_index,
+                                name,
+                            };
+                            match kind {
+                                ExternalKind::Func => state.core_funcs.push(item),
+                                ExternalKind::Table => state.core_tables.push(item),
+                                ExternalKind::Memory => state.core_memories.push(item),
+                                ExternalKind::Global => state.core_globals.push(item),
+                                ExternalKind::Tag => {}
+                            }
+                        }
+                        ComponentAlias::InstanceExport {
+                            kind,
+                            instance_index,
+                            name,
+                        } => {
+                            let item = ComponentItem::Export {
+                                instance: instance_index,
+                                name,
+                            };
+                            match kind {
+                                ComponentExternalKind::Func => state.funcs.push(item),
+                                ComponentExternalKind::Instance => state.instances.push(item),
+                                _ => {}
+                            }
+                        }
+                        ComponentAlias::Outer { .. } => {}
+                    }
+                }
+            }
+            Payload::ComponentCanonicalSection(s) => {
+                for func in s {
+                    match func? {
+                        CanonicalFunction::Lift {
+                            core_func_index,
+                            options,
+                            ..
+                        } => {
+                            let index = state.funcs.len() as u32;
+                            state.funcs.push(ComponentItem::Lift(core_func_index));
+                            state.canonicals.push((CanonicalKind::Lift, index, options));
+                        }
+                        CanonicalFunction::Lower {
+                            func_index,
+                            options,
+                        } => {
+                            let index = state.core_funcs.len() as u32;
+                            state.core_funcs.push(CoreItem::Lower(func_index));
+                            state
+                                .canonicals
+                                .push((CanonicalKind::Lower, index, options));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    state.find_shim_targets();
+
+    for (i, instance) in state.core_instances.iter().enumerate() {
+        let i = i as u32;
+        let kind = match instance {
+            Instance::Instantiate { module_index, args } => {
+                for arg in args.iter() {
+                    report.links.push(ImportLink {
+                        instance: i,
+                        module: *module_index,
+                        name: arg.name.to_string(),
+                        providers: state.providers(arg.index),
+                    });
+                }
+                CoreInstanceKind::Instantiate {
+                    module: *module_index,
+                    args: args
+                        .iter()
+                        .map(|arg| (arg.name.to_string(), arg.index))
+                        .collect(),
+                }
+            }
+            Instance::FromExports(exports) => {
+                CoreInstanceKind::FromExports(exports.iter().map(|e| e.name.to_string()).collect())
+            }
+        };
+        report.instances.push(CoreInstanceReport { index: i, kind });
+    }
+
+    for (kind, index, options) in state.canonicals.iter() {
+        let func = match kind {
+            CanonicalKind::Lift => state.describe_func(*index),
+            CanonicalKind::Lower => state.describe_core_func(*index),
+        };
+        let options = options.iter().map(|o| state.describe_option(o)).collect();
+        report.canonicals.push(CanonicalReport {
+            kind: *kind,
+            func,
+            options,
+        });
+    }
+
+    Ok(report)
+}
+
+impl<'a> State<'a> {
+    /// Finds "fixup" instantiations, which are arguments that export a table
+    /// aliased from a "shim" instance alongside the functions that are placed
+    /// into that table. The shim instance's export of the same name is then
+    /// actually implemented by the function provided to the fixup.
+    fn find_shim_targets(&mut self) {
+        for instance in self.core_instances.iter() {
+            let exports = match instance {
+                Instance::FromExports(exports) => exports,
+                Instance::Instantiate { .. } => continue,
+            };
+            let shim = exports.iter().find_map(|e| match e.kind {
+                ExternalKind::Table => match self.core_tables.get(e.index as usize)? {
+                    CoreItem::Export { instance, .. } => Some(*instance),
+                    CoreItem::Lower(_) => None,
+                },
+                _ => None,
+            });
+            let shim = match shim {
+                Some(shim) => shim,
+                None => continue,
+            };
+            for export in exports.iter() {
+                if export.kind == ExternalKind::Func {
+                    self.shim_targets.insert((shim, export.name), export.index);
+                }
+            }
+        }
+    }
+
+    /// Resolves a core function through any shims to where it's defined.
+    fn resolve_core_func(&self, mut index: u32) -> Option<CoreItem<'a>> {
+        // Bound the number of shims followed to guard against cycles.
+        for _ in 0..self.core_funcs.len() + 1 {
+            let item = *self.core_funcs.get(index as usize)?;
+            match item {
+                CoreItem::Export { instance, name } => {
+                    match self.shim_targets.get(&(instance, name)) {
+                        Some(target) => index = *target,
+                        None => return Some(item),
+                    }
+                }
+                CoreItem::Lower(_) => return Some(item),
+            }
+        }
+        None
+    }
+
+    /// Returns the providers of all items in the core instance `index`.
+    fn providers(&self, index: u32) -> Vec<Provider> {
+        let mut ret = IndexSet::new();
+        match self.core_instances.get(index as usize) {
+            Some(Instance::Instantiate { module_index, .. }) => {
+                ret.insert(Provider::Instance {
+                    instance: index,
+                    module: *module_index,
+                });
+            }
+            Some(Instance::FromExports(exports)) => {
+                for export in exports.iter() {
+                    let item = match export.kind {
+                        ExternalKind::Func => self.resolve_core_func(export.index),
+                        ExternalKind::Table => self.core_tables.get(export.index as usize).copied(),
+                        ExternalKind::Memory => {
+                            self.core_memories.get(export.index as usize).copied()
+                        }
+                        ExternalKind::Global => {
+                            self.core_globals.get(export.index as usize).copied()
+                        }
+                        ExternalKind::Tag => None,
+                    };
+                    ret.insert(self.provider(item));
+                }
+            }
+            None => {
+                ret.insert(Provider::Unknown);
+            }
+        }
+        ret.into_iter().collect()
+    }
+
+    fn provider(&self, item: Option<CoreItem<'_>>) -> Provider {
+        match item {
+            Some(CoreItem::Export { instance, .. }) => {
+                match self.core_instances.get(instance as usize) {
+                    Some(Instance::Instantiate { module_index, .. }) => Provider::Instance {
+                        instance,
+                        module: *module_index,
+                    },
+                    _ => Provider::Unknown,
+                }
+            }
+            Some(CoreItem::Lower(func)) => match self.funcs.get(func as usize) {
+                Some(ComponentItem::Import(name)) => Provider::Import(name.to_string()),
+                Some(ComponentItem::Export { instance, .. }) => {
+                    match self.instances.get(*instance as usize) {
+                        Some(ComponentItem::Import(name)) => Provider::Import(name.to_string()),
+                        _ => Provider::Unknown,
+                    }
+                }
+                _ => Provider::Unknown,
+            },
+            None => Provider::Unknown,
+        }
+    }
+
+    fn describe_core_item(&self, item: Option<&CoreItem<'_>>) -> String {
+        match item {
+            Some(CoreItem::Export { instance, name }) => {
+                match self.core_instances.get(*instance as usize) {
+                    Some(Instance::Instantiate { module_index, .. }) => {
+                        format!("`{name}` of core instance {instance} (module {module_index})")
+                    }
+                    _ => format!("`{name}` of core instance {instance}"),
+                }
+            }
+            Some(CoreItem::Lower(func)) => format!("lowered {}", self.describe_func(*func)),
+            None => "<unknown>".to_string(),
+        }
+    }
+
+    fn describe_core_func(&self, index: u32) -> String {
+        self.describe_core_item(self.core_funcs.get(index as usize))
+    }
+
+    fn describe_func(&self, index: u32) -> String {
+        match self.funcs.get(index as usize) {
+            Some(ComponentItem::Import(name)) => format!("import `{name}`"),
+            Some(ComponentItem::Export { instance, name }) => {
+                match self.instances.get(*instance as usize) {
+                    Some(ComponentItem::Import(import)) => format!("`{import}::{name}`"),
+                    _ => format!("`{name}` of instance {instance}"),
+                }
+            }
+            Some(ComponentItem::Lift(core)) => self.describe_core_func(*core),
+            Some(ComponentItem::Other) | None => "<unknown>".to_string(),
+        }
+    }
+
+    fn describe_option(&self, option: &CanonicalOption) -> String {
+        match option {
+            CanonicalOption::UTF8 => "string-encoding=utf8".to_string(),
+            CanonicalOption::UTF16 => "string-encoding=utf16".to_string(),
+            CanonicalOption::CompactUTF16 => "string-encoding=latin1+utf16".to_string(),
+            CanonicalOption::Memory(i) => format!(
+                "memory {}",
+                self.describe_core_item(self.core_memories.get(*i as usize))
+            ),
+            CanonicalOption::Realloc(i) => format!("realloc {}", self.describe_core_func(*i)),
+            CanonicalOption::PostReturn(i) => {
+                format!("post-return {}", self.describe_core_func(*i))
+            }
+        }
+    }
+}
+
+/// Returns the name and size of all sections in the core wasm `module`.
+///
+/// This is done by hand rather than with `wasmparser` since only the framing
+/// of each section is needed.
+fn section_sizes(module: &[u8]) -> Result<Vec<(String, usize)>> {
+    const NAMES: &[&str] = &[
+        "custom",
+        "type",
+        "import",
+        "function",
+        "table",
+        "memory",
+        "global",
+        "export",
+        "start",
+        "element",
+        "code",
+        "data",
+        "datacount",
+        "tag",
+    ];
+
+    fn read_u32(bytes: &[u8], pos: &mut usize) -> Result<usize> {
+        let mut ret = 0;
+        let mut shift = 0;
+        loop {
+            let byte = match bytes.get(*pos) {
+                Some(byte) => *byte,
+                None => bail!("unexpected end of module"),
+            };
+            *pos += 1;
+            ret |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(ret);
+            }
+            shift += 7;
+            if shift > 28 {
+                bail!("invalid LEB128 encoding");
+            }
+        }
+    }
+
+    let mut ret = Vec::new();
+    // Skip the magic number and version.
+    let mut pos = 8;
+    while pos < module.len() {
+        let id = module[pos];
+        pos += 1;
+        let size = read_u32(module, &mut pos)?;
+        let end = pos + size;
+        if end > module.len() {
+            bail!("section extends past the end of the module");
+        }
+        let name = match id {
+            0 => {
+                let mut name_pos = pos;
+                let len = read_u32(module, &mut name_pos)?;
+                let name = module
+                    .get(name_pos..name_pos + len)
+                    .and_then(|n| std::str::from_utf8(n).ok())
+                    .unwrap_or("<invalid>");
+                format!("custom:{name}")
+            }
+            id => match NAMES.get(id as usize) {
+                Some(name) => name.to_string(),
+                None => format!("unknown:{id}"),
+            },
+        };
+        ret.push((name, size));
+        pos = end;
+    }
+    Ok(ret)
+}
+
+impl ComponentReport {
+    /// Renders the instantiation graph of this component in the Graphviz DOT
+    /// format.
+    ///
+    /// Each core instance is a node and each edge represents an instantiation
+    /// argument, pointing from the provider of an import to the instance that
+    /// imports it.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str("digraph component {\n");
+        dot.push_str("  rankdir=LR;\n");
+        for (i, import) in self.imports.iter().enumerate() {
+            writeln!(
+                dot,
+                "  import{i} [shape=ellipse, label={}];",
+                dot_str(&format!("import {import}"))
+            )
+            .unwrap();
+        }
+        for instance in self.instances.iter() {
+            let module = match &instance.kind {
+                CoreInstanceKind::Instantiate { module, .. } => *module,
+                CoreInstanceKind::FromExports(_) => continue,
+            };
+            let size = self.modules.get(module as usize).map(|m| m.size);
+            writeln!(
+                dot,
+                "  instance{} [shape=box, label={}];",
+                instance.index,
+                dot_str(&format!(
+                    "instance {}\nmodule {module} ({} bytes)",
+                    instance.index,
+                    size.unwrap_or(0),
+                ))
+            )
+            .unwrap();
+        }
+        for link in self.links.iter() {
+            for provider in link.providers.iter() {
+                let from = match provider {
+                    Provider::Instance { instance, .. } => format!("instance{instance}"),
+                    Provider::Import(name) => match self.imports.iter().position(|i| i == name) {
+                        Some(i) => format!("import{i}"),
+                        None => continue,
+                    },
+                    Provider::Unknown => continue,
+                };
+                writeln!(
+                    dot,
+                    "  {from} -> instance{} [label={}];",
+                    link.instance,
+                    dot_str(&link.name)
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders this report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write!(json, "{{\"size\":{}", self.size).unwrap();
+
+        json.push_str(",\"imports\":");
+        json_list(&mut json, &self.imports, |json, s| json_str(json, s));
+        json.push_str(",\"exports\":");
+        json_list(&mut json, &self.exports, |json, s| json_str(json, s));
+
+        json.push_str(",\"modules\":");
+        json_list(&mut json, &self.modules, |json, m| {
+            write!(
+                json,
+                "{{\"index\":{},\"size\":{},\"sections\":",
+                m.index, m.size
+            )
+            .unwrap();
+            json_list(json, &m.sections, |json, (name, size)| {
+                json.push_str("{\"name\":");
+                json_str(json, name);
+                write!(json, ",\"size\":{size}}}").unwrap();
+            });
+            json.push('}');
+        });
+
+        json.push_str(",\"instances\":");
+        json_list(&mut json, &self.instances, |json, i| {
+            write!(json, "{{\"index\":{}", i.index).unwrap();
+            match &i.kind {
+                CoreInstanceKind::Instantiate { module, args } => {
+                    write!(json, ",\"module\":{module},\"args\":").unwrap();
+                    json_list(json, args, |json, (name, instance)| {
+                        json.push_str("{\"name\":");
+                        json_str(json, name);
+                        write!(json, ",\"instance\":{instance}}}").unwrap();
+                    });
+                }
+                CoreInstanceKind::FromExports(exports) => {
+                    json.push_str(",\"exports\":");
+                    json_list(json, exports, |json, s| json_str(json, s));
+                }
+            }
+            json.push('}');
+        });
+
+        json.push_str(",\"links\":");
+        json_list(&mut json, &self.links, |json, l| {
+            write!(
+                json,
+                "{{\"instance\":{},\"module\":{},\"name\":",
+                l.instance, l.module
+            )
+            .unwrap();
+            json_str(json, &l.name);
+            json.push_str(",\"providers\":");
+            json_list(json, &l.providers, |json, p| match p {
+                Provider::Instance { instance, module } => {
+                    write!(json, "{{\"instance\":{instance},\"module\":{module}}}").unwrap()
+                }
+                Provider::Import(name) => {
+                    json.push_str("{\"import\":");
+                    json_str(json, name);
+                    json.push('}');
+                }
+                Provider::Unknown => json.push_str("null"),
+            });
+            json.push('}');
+        });
+
+        json.push_str(",\"canonicals\":");
+        json_list(&mut json, &self.canonicals, |json, c| {
+            json.push_str("{\"kind\":");
+            json_str(
+                json,
+                match c.kind {
+                    CanonicalKind::Lift => "lift",
+                    CanonicalKind::Lower => "lower",
+                },
+            );
+            json.push_str(",\"func\":");
+            json_str(json, &c.func);
+            json.push_str(",\"options\":");
+            json_list(json, &c.options, |json, s| json_str(json, s));
+            json.push('}');
+        });
+
+        json.push_str("}\n");
+        json
+    }
+}
+
+impl fmt::Display for ComponentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "component: {} bytes", self.size)?;
+        for import in self.imports.iter() {
+            writeln!(f, "import `{import}`")?;
+        }
+        for export in self.exports.iter() {
+            writeln!(f, "export `{export}`")?;
+        }
+
+        writeln!(f, "\ncore modules:")?;
+        for module in self.modules.iter() {
+            writeln!(f, "  module {}: {} bytes", module.index, module.size)?;
+            for (name, size) in module.sections.iter() {
+                writeln!(f, "    {name}: {size} bytes")?;
+            }
+        }
+
+        writeln!(f, "\ncore instances:")?;
+        for instance in self.instances.iter() {
+            match &instance.kind {
+                CoreInstanceKind::Instantiate { module, .. } => {
+                    writeln!(f, "  instance {}: module {module}", instance.index)?;
+                    for link in self.links.iter().filter(|l| l.instance == instance.index) {
+                        write!(f, "    `{}` <-", link.name)?;
+                        for provider in link.providers.iter() {
+                            match provider {
+                                Provider::Instance { instance, module } => {
+                                    write!(f, " instance {instance} (module {module})")?
+                                }
+                                Provider::Import(name) => write!(f, " import `{name}`")?,
+                                Provider::Unknown => write!(f, " <unknown>")?,
+                            }
+                        }
+                        writeln!(f)?;
+                    }
+                }
+                CoreInstanceKind::FromExports(exports) => {
+                    writeln!(
+                        f,
+                        "  instance {}: exports of {} items",
+                        instance.index,
+                        exports.len()
+                    )?;
+                }
+            }
+        }
+
+        writeln!(f, "\ncanonical functions:")?;
+        for canonical in self.canonicals.iter() {
+            let kind = match canonical.kind {
+                CanonicalKind::Lift => "lift",
+                CanonicalKind::Lower => "lower",
+            };
+            write!(f, "  {kind} {}", canonical.func)?;
+            for option in canonical.options.iter() {
+                write!(f, "\n    {option}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn dot_str(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+fn json_str(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn json_list<T>(json: &mut String, items: &[T], mut f: impl FnMut(&mut String, &T)) {
+    json.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        f(json, item);
+    }
+    json.push(']');
+}
@@ -2,11 +2,13 @@
 
 use crate::extract::{extract_module_interfaces, ModuleInterfaces};
 use crate::{
-    decode_component_interfaces, ComponentEncoder, ComponentInterfaces, InterfacePrinter,
-    StringEncoding,
+    analyze_component, decode_component_interfaces, embed_module_interfaces, read_interface,
+    ComponentEncoder, ComponentInterfaces, ImportStub, InterfacePrinter, StringEncoding,
+    WitPackage,
 };
 use anyhow::{anyhow, bail, Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use indexmap::IndexMap;
 use std::path::{Path, PathBuf};
 use wit_parser::Interface;
 
@@ -40,7 +42,7 @@ fn parse_interface(name: Option<String>, path: &Path) -> Result<Interface> {
         bail!("interface file `{}` does not exist", path.display(),);
     }
 
-    let mut interface = Interface::parse_file(&path)
+    let mut interface = read_interface(path, name.as_deref())
         .with_context(|| format!("failed to parse interface file `{}`", path.display()))?;
 
     interface.name = name.unwrap_or_else(|| "".to_string());
@@ -48,6 +50,12 @@ fn parse_interface(name: Option<String>, path: &Path) -> Result<Interface> {
     Ok(interface)
 }
 
+fn parse_library(s: &str) -> Result<(String, Vec<u8>)> {
+    let (name, path) = parse_optionally_name_file(s);
+    let wasm = wat::parse_file(path)?;
+    Ok((name.to_string(), wasm))
+}
+
 fn parse_adapter(s: &str) -> Result<(String, Vec<u8>, Interface)> {
     let mut parts = s.splitn(2, ':');
     let maybe_named_module = parts.next().unwrap();
@@ -68,6 +76,7 @@ fn parse_adapter(s: &str) -> Result<(String, Vec<u8>, Interface)> {
                         exports,
                         default,
                     },
+                ..
             } = extract_module_interfaces(&wasm)?;
             if !exports.is_empty() || default.is_some() {
                 bail!("adapter modules cannot have an exported interface");
@@ -82,12 +91,45 @@ fn parse_adapter(s: &str) -> Result<(String, Vec<u8>, Interface)> {
     }
 }
 
+fn parse_stub(s: &str) -> Result<(String, ImportStub)> {
+    let mut parts = s.splitn(2, '=');
+    let name = parts.next().unwrap();
+    let stub = match parts.next() {
+        Some(stub) => stub.parse()?,
+        None => ImportStub::default(),
+    };
+    Ok((name.to_string(), stub))
+}
+
+fn parse_canonical_options(s: &str) -> Result<(String, CanonicalOptions)> {
+    let (target, options) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected `TARGET:OPTIONS`, found `{s}`"))?;
+    Ok((target.to_string(), options.parse()?))
+}
+
+fn parse_rename(s: &str) -> Result<(String, String)> {
+    let (from, to) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected `FROM=TO`, found `{s}`"))?;
+    Ok((from.to_string(), to.to_string()))
+}
+
 /// WebAssembly component encoder.
 ///
 /// Encodes a WebAssembly component from a core WebAssembly module.
 #[derive(Debug, Parser)]
-#[clap(name = "component-encoder", version = env!("CARGO_PKG_VERSION"))]
+#[clap(
+    name = "component-encoder",
+    version = env!("CARGO_PKG_VERSION"),
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct WitComponentApp {
+    /// An auxiliary command to run instead of encoding a component.
+    #[clap(subcommand)]
+    pub command: Option<WitComponentCommand>,
+
     /// The path to an interface definition file the component imports.
     #[clap(long = "import", value_name = "[NAME=]INTERFACE", value_parser = parse_named_interface)]
     pub imports: Vec<Interface>,
@@ -110,6 +152,25 @@ pub struct WitComponentApp {
     #[clap(long = "adapt", value_name = "[NAME=]MODULE[:[NAME=]INTERFACE]", value_parser = parse_adapter)]
     pub adapters: Vec<(String, Vec<u8>, Interface)>,
 
+    /// The path to a library module to link with the main module.
+    ///
+    /// Imports of the main module from the module `NAME` are satisfied by the
+    /// exports of the library, which may include memories, tables, and
+    /// globals. Libraries may only import from other libraries, not from
+    /// interfaces or adapters. The `[NAME=]` is inferred from the name of the
+    /// file specified by `MODULE` if not present (e.g. `libc.wasm`).
+    #[clap(long = "library", value_name = "[NAME=]MODULE", value_parser = parse_library)]
+    pub libraries: Vec<(String, Vec<u8>)>,
+
+    /// The name of an imported interface to replace with a generated stub.
+    ///
+    /// A stubbed interface is no longer imported by the component. The
+    /// behavior of the stub is either `trap` (the default), where all
+    /// functions trap when called, or `error`, where functions returning a
+    /// `result` return its `err` case and all other functions trap.
+    #[clap(long = "stub", value_name = "NAME[=BEHAVIOR]", value_parser = parse_stub)]
+    pub stubs: Vec<(String, ImportStub)>,
+
     /// The path of the output WebAssembly component.
     #[clap(long, short = 'o', value_name = "OUTPUT")]
     pub output: Option<PathBuf>,
@@ -127,29 +188,60 @@ pub struct WitComponentApp {
     #[clap(long, value_name = "ENCODING")]
     pub encoding: Option<StringEncoding>,
 
+    /// Canonical ABI options for an interface or one of its functions.
+    ///
+    /// The `TARGET` is the name of an interface or `INTERFACE#FUNCTION` and
+    /// `OPTIONS` is a comma-separated list of `encoding=ENCODING` and
+    /// `realloc=FUNCTION`, for example `foo:encoding=utf16,realloc=foo_alloc`.
+    #[clap(long = "options", value_name = "TARGET:OPTIONS", value_parser = parse_canonical_options)]
+    pub options: Vec<(String, CanonicalOptions)>,
+
+    /// Renames imports of the module matching `FROM` to `TO`.
+    ///
+    /// `FROM` matches the import module name, or both the module and field
+    /// name when written as `MODULE::FIELD`. A `*` matches any sequence of
+    /// characters which is substituted for the corresponding `*` in `TO`, for
+    /// example `env::foo_*=foo::*`.
+    #[clap(long = "rename-import", value_name = "FROM=TO", value_parser = parse_rename)]
+    pub rename_imports: Vec<(String, String)>,
+
+    /// Renames exports of the module matching `FROM` to `TO`, with wildcards
+    /// as for `--rename-import`, for example `foo_*=foo#*`.
+    #[clap(long = "rename-export", value_name = "FROM=TO", value_parser = parse_rename)]
+    pub rename_exports: Vec<(String, String)>,
+
+    /// The exported function of the module to call once the component is
+    /// instantiated, defaulting to `_initialize` if the module exports it.
+    #[clap(long, value_name = "NAME")]
+    pub initializer: Option<String>,
+
     /// Path to the WebAssembly module to encode.
-    #[clap(index = 1, value_name = "MODULE")]
-    pub module: PathBuf,
+    #[clap(index = 1, value_name = "MODULE", required = true)]
+    pub module: Option<PathBuf>,
 }
 
 impl WitComponentApp {
     /// Executes the application.
     pub fn execute(self) -> Result<()> {
-        if !self.module.is_file() {
+        if let Some(command) = self.command {
+            return command.execute();
+        }
+        let module_path = self.module.unwrap();
+        if !module_path.is_file() {
             bail!(
                 "module `{}` does not exist as a file",
-                self.module.display()
+                module_path.display()
             );
         }
 
         let output = self.output.unwrap_or_else(|| {
-            let mut stem: PathBuf = self.module.file_stem().unwrap().into();
+            let mut stem: PathBuf = module_path.file_stem().unwrap().into();
             stem.set_extension("wasm");
             stem
         });
 
-        let module = wat::parse_file(&self.module)
-            .with_context(|| format!("failed to parse module `{}`", self.module.display()))?;
+        let module = wat::parse_file(&module_path)
+            .with_context(|| format!("failed to parse module `{}`", module_path.display()))?;
 
         let mut encoder = ComponentEncoder::default()
             .module(&module)?
@@ -161,6 +253,14 @@ impl WitComponentApp {
             encoder = encoder.adapter(name, wasm, interface);
         }
 
+        for (name, wasm) in self.libraries.iter() {
+            encoder = encoder.library(name, wasm);
+        }
+
+        for (name, stub) in self.stubs.iter() {
+            encoder = encoder.stub_import(name, *stub);
+        }
+
         if let Some(interface) = self.interface {
             encoder = encoder.interface(interface)?;
         }
@@ -169,10 +269,26 @@ impl WitComponentApp {
             encoder = encoder.encoding(*encoding);
         }
 
+        for (target, options) in self.options {
+            encoder = encoder.canonical_options(&target, options);
+        }
+
+        for (from, to) in self.rename_imports.iter() {
+            encoder = encoder.rename_import(from, to);
+        }
+
+        for (from, to) in self.rename_exports.iter() {
+            encoder = encoder.rename_export(from, to);
+        }
+
+        if let Some(name) = &self.initializer {
+            encoder = encoder.initializer(name);
+        }
+
         let bytes = encoder.encode().with_context(|| {
             format!(
                 "failed to encode a component from module `{}`",
-                self.module.display()
+                module_path.display()
             )
         })?;
 
@@ -185,6 +301,301 @@ impl WitComponentApp {
     }
 }
 
+/// Auxiliary commands of the `wit-component` tool.
+#[derive(Debug, Subcommand)]
+pub enum WitComponentCommand {
+    /// Embeds interface type information into a core WebAssembly module.
+    Embed(EmbedApp),
+    /// Analyzes the structure and size of a component.
+    Analyze(AnalyzeApp),
+    /// Removes unused exports and items from a core WebAssembly module.
+    Gc(GcApp),
+    /// Encodes a set of interfaces as a binary WIT package.
+    Package(PackageApp),
+}
+
+impl WitComponentCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        match self {
+            WitComponentCommand::Embed(app) => app.execute(),
+            WitComponentCommand::Analyze(app) => app.execute(),
+            WitComponentCommand::Gc(app) => app.execute(),
+            WitComponentCommand::Package(app) => app.execute(),
+        }
+    }
+}
+
+/// WebAssembly interface embedder.
+///
+/// Embeds the type information of a set of interfaces into a core WebAssembly
+/// module as a custom section. The resulting module can then be encoded as a
+/// component without specifying the interfaces a second time.
+#[derive(Debug, Parser)]
+pub struct EmbedApp {
+    /// The path to an interface definition file the module imports.
+    #[clap(long = "import", value_name = "[NAME=]INTERFACE", value_parser = parse_named_interface)]
+    pub imports: Vec<Interface>,
+
+    /// The path to an interface definition file the module exports.
+    #[clap(long = "export", value_name = "[NAME=]INTERFACE", value_parser = parse_named_interface)]
+    pub exports: Vec<Interface>,
+
+    /// The default interface the module exports.
+    #[clap(long, short = 'i', value_name = "INTERFACE", value_parser = parse_unnamed_interface)]
+    pub interface: Option<Interface>,
+
+    /// Canonical ABI options to embed for an interface or one of its
+    /// functions, in the same format as `wit-component --options`.
+    #[clap(long = "options", value_name = "TARGET:OPTIONS", value_parser = parse_canonical_options)]
+    pub options: Vec<(String, CanonicalOptions)>,
+
+    /// The path of the output WebAssembly module.
+    #[clap(long, short = 'o', value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Path to the WebAssembly module to embed interfaces into.
+    #[clap(index = 1, value_name = "MODULE")]
+    pub module: PathBuf,
+}
+
+impl EmbedApp {
+    /// Executes the application.
+    pub fn execute(self) -> Result<()> {
+        if !self.module.is_file() {
+            bail!(
+                "module `{}` does not exist as a file",
+                self.module.display()
+            );
+        }
+
+        let module = wat::parse_file(&self.module)
+            .with_context(|| format!("failed to parse module `{}`", self.module.display()))?;
+
+        let mut interfaces = ComponentInterfaces {
+            default: self.interface,
+            ..Default::default()
+        };
+        for import in self.imports {
+            if interfaces.imports.contains_key(&import.name) {
+                bail!(
+                    "cannot specify import interface for `{}` twice",
+                    import.name
+                );
+            }
+            interfaces.imports.insert(import.name.clone(), import);
+        }
+        for export in self.exports {
+            if interfaces.exports.contains_key(&export.name) {
+                bail!(
+                    "cannot specify export interface for `{}` twice",
+                    export.name
+                );
+            }
+            interfaces.exports.insert(export.name.clone(), export);
+        }
+
+        let bytes = embed_module_interfaces(&module, &interfaces).with_context(|| {
+            format!(
+                "failed to embed interfaces into module `{}`",
+                self.module.display()
+            )
+        })?;
+
+        let bytes = if self.options.is_empty() {
+            bytes
+        } else {
+            let mut options = IndexMap::new();
+            for (target, o) in self.options {
+                if options.insert(target.clone(), o).is_some() {
+                    bail!("cannot specify canonical options for `{target}` twice");
+                }
+            }
+            embed_module_options(&bytes, &options).with_context(|| {
+                format!(
+                    "failed to embed canonical options into module `{}`",
+                    self.module.display()
+                )
+            })?
+        };
+
+        std::fs::write(&self.output, bytes)
+            .with_context(|| format!("failed to write output file `{}`", self.output.display()))?;
+
+        println!("embedded interfaces into `{}`", self.output.display());
+
+        Ok(())
+    }
+}
+
+/// The output formats supported by `wit-component analyze`.
+#[derive(Debug, Clone, Copy)]
+pub enum AnalyzeFormat {
+    /// A human-readable text report.
+    Text,
+    /// A graph of core instances in the Graphviz DOT format.
+    Dot,
+    /// A JSON object.
+    Json,
+}
+
+impl std::str::FromStr for AnalyzeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(AnalyzeFormat::Text),
+            "dot" => Ok(AnalyzeFormat::Dot),
+            "json" => Ok(AnalyzeFormat::Json),
+            _ => bail!("unknown analysis format `{}`", s),
+        }
+    }
+}
+
+/// WebAssembly component analyzer.
+///
+/// Reports the core modules within a component along with their sizes, how
+/// they're instantiated and linked together, and which functions are lifted
+/// and lowered.
+#[derive(Debug, Parser)]
+pub struct AnalyzeApp {
+    /// The format of the report.
+    /// Supported values are: `text` (default), `dot`, and `json`.
+    #[clap(long, value_name = "FORMAT", default_value = "text")]
+    pub format: AnalyzeFormat,
+
+    /// The path of the output report, defaulting to stdout.
+    #[clap(long, short = 'o', value_name = "OUTPUT")]
+    pub output: Option<PathBuf>,
+
+    /// The path to the WebAssembly component to analyze.
+    #[clap(index = 1, value_name = "COMPONENT")]
+    pub component: PathBuf,
+}
+
+impl AnalyzeApp {
+    /// Executes the application.
+    pub fn execute(self) -> Result<()> {
+        if !self.component.is_file() {
+            bail!(
+                "component `{}` does not exist as a file",
+                self.component.display()
+            );
+        }
+
+        let bytes = wat::parse_file(&self.component)
+            .with_context(|| format!("failed to parse component `{}`", self.component.display()))?;
+
+        let report = analyze_component(&bytes).with_context(|| {
+            format!("failed to analyze component `{}`", self.component.display())
+        })?;
+        let contents = match self.format {
+            AnalyzeFormat::Text => report.to_string(),
+            AnalyzeFormat::Dot => report.to_dot(),
+            AnalyzeFormat::Json => report.to_json(),
+        };
+
+        match &self.output {
+            Some(output) => std::fs::write(output, contents)
+                .with_context(|| format!("failed to write output file `{}`", output.display()))?,
+            None => print!("{}", contents),
+        }
+
+        Ok(())
+    }
+}
+
+/// WebAssembly module dead-code eliminator.
+///
+/// Removes all exports of a core WebAssembly module other than those
+/// specified and then removes all functions, globals, tables, memories,
+/// imports, and segments which are no longer reachable.
+#[derive(Debug, Parser)]
+pub struct GcApp {
+    /// The name of an export to keep, which may be specified multiple times.
+    ///
+    /// Note that a module being encoded as a component will typically need to
+    /// keep its `memory` and `cabi_realloc` exports in addition to the
+    /// functions of its exported interfaces.
+    #[clap(long = "keep-export", value_name = "NAME")]
+    pub keep_exports: Vec<String>,
+
+    /// The path of the output WebAssembly module.
+    #[clap(long, short = 'o', value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Path to the WebAssembly module to reduce.
+    #[clap(index = 1, value_name = "MODULE")]
+    pub module: PathBuf,
+}
+
+impl GcApp {
+    /// Executes the application.
+    pub fn execute(self) -> Result<()> {
+        if !self.module.is_file() {
+            bail!(
+                "module `{}` does not exist as a file",
+                self.module.display()
+            );
+        }
+
+        let module = wat::parse_file(&self.module)
+            .with_context(|| format!("failed to parse module `{}`", self.module.display()))?;
+
+        let keep = self
+            .keep_exports
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>();
+        let bytes = gc_module(&module, &keep)
+            .with_context(|| format!("failed to reduce module `{}`", self.module.display()))?;
+
+        std::fs::write(&self.output, &bytes)
+            .with_context(|| format!("failed to write output file `{}`", self.output.display()))?;
+
+        println!(
+            "reduced module from {} to {} bytes in `{}`",
+            module.len(),
+            bytes.len(),
+            self.output.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// WIT package encoder.
+///
+/// Encodes a set of interfaces, along with their documentation, as a binary
+/// WIT package which can be used in place of the `*.wit` files by tools such
+/// as `wit-bindgen`.
+#[derive(Debug, Parser)]
+pub struct PackageApp {
+    /// The path to an interface definition file to include in the package.
+    #[clap(index = 1, value_name = "[NAME=]INTERFACE", value_parser = parse_named_interface, required = true)]
+    pub interfaces: Vec<Interface>,
+
+    /// The path of the output WIT package.
+    #[clap(long, short = 'o', value_name = "OUTPUT")]
+    pub output: PathBuf,
+}
+
+impl PackageApp {
+    /// Executes the application.
+    pub fn execute(self) -> Result<()> {
+        let bytes = WitPackage::new(self.interfaces)?
+            .encode()
+            .context("failed to encode WIT package")?;
+
+        std::fs::write(&self.output, bytes)
+            .with_context(|| format!("failed to write output file `{}`", self.output.display()))?;
+
+        println!("encoded WIT package `{}`", self.output.display());
+
+        Ok(())
+    }
+}
+
 /// WebAssembly interface decoder.
 ///
 /// Decodes a WebAssembly interface from a WebAssembly component.
@@ -8,19 +8,19 @@ use wasmparser::{
 use wit_parser::*;
 
 /// Represents information about a decoded WebAssembly component.
-struct ComponentInfo<'a> {
+pub(crate) struct ComponentInfo<'a> {
     /// Wasmparser-defined type information learned after a component is fully
     /// validated.
     types: types::Types,
     /// Map of imports and what type they're importing.
-    imports: IndexMap<&'a str, ComponentTypeRef>,
+    pub(crate) imports: IndexMap<&'a str, ComponentTypeRef>,
     /// Map of exports and what they're exporting.
-    exports: IndexMap<&'a str, ComponentExport<'a>>,
+    pub(crate) exports: IndexMap<&'a str, ComponentExport<'a>>,
 }
 
 impl<'a> ComponentInfo<'a> {
     /// Creates a new component info by parsing the given WebAssembly component bytes.
-    fn new(bytes: &'a [u8]) -> Result<Self> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<Self> {
         let mut validator = Validator::new_with_features(WasmFeatures {
             component_model: true,
             ..Default::default()
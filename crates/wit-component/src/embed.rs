@@ -0,0 +1,87 @@
+use crate::{CanonicalOptions, ComponentEncoder, ComponentInterfaces};
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use std::fmt::Write;
+use wasm_encoder::{Encode, Section};
+
+/// This function will embed the `interfaces` provided into the core wasm
+/// module `wasm`, returning the new module.
+///
+/// The interfaces are encoded in the same "types only" format that the
+/// `wit-bindgen` generators produce and are appended to the module as a
+/// `component-type` custom section. The returned module can then be passed to
+/// [`ComponentEncoder::module`] without needing to specify interfaces
+/// separately.
+///
+/// This is the inverse of [`extract_module_interfaces`](crate::extract_module_interfaces)
+/// and is intended for toolchains which don't have a bindings generator of
+/// their own, such as hand-written modules or languages which otherwise only
+/// know how to produce core wasm following the canonical ABI.
+pub fn embed_module_interfaces(wasm: &[u8], interfaces: &ComponentInterfaces) -> Result<Vec<u8>> {
+    check_core_module(wasm)?;
+
+    let mut encoder = ComponentEncoder::default()
+        .types_only(true)
+        .imports(interfaces.imports.values().cloned())?
+        .exports(interfaces.exports.values().cloned())?;
+    if let Some(default) = &interfaces.default {
+        encoder = encoder.interface(default.clone())?;
+    }
+    let contents = encoder
+        .encode()
+        .context("failed to encode interfaces as a component type")?;
+
+    Ok(append_custom_section(wasm, "component-type", &contents))
+}
+
+/// This function will embed the canonical ABI `options` into the core wasm
+/// module `wasm`, returning the new module.
+///
+/// The keys of `options` name what the options apply to and are either the
+/// name of an interface or `INTERFACE#FUNCTION` for a single function. The
+/// options are appended to the module as a `component-options` custom section
+/// and are picked up by [`ComponentEncoder::module`] when the module is later
+/// encoded as a component.
+///
+/// This allows toolchains which link together code from multiple languages to
+/// record, for example, that one interface uses UTF-16 strings and its own
+/// allocator while all others use the defaults.
+pub fn embed_module_options(
+    wasm: &[u8],
+    options: &IndexMap<String, CanonicalOptions>,
+) -> Result<Vec<u8>> {
+    check_core_module(wasm)?;
+
+    let mut contents = String::new();
+    for (target, options) in options {
+        if target.contains(char::is_whitespace) {
+            bail!("invalid canonical options target `{target}`");
+        }
+        writeln!(contents, "{target} {options}").unwrap();
+    }
+    Ok(append_custom_section(
+        wasm,
+        "component-options",
+        contents.as_bytes(),
+    ))
+}
+
+fn check_core_module(wasm: &[u8]) -> Result<()> {
+    match wasmparser::Parser::new(0).parse_all(wasm).next() {
+        Some(Ok(wasmparser::Payload::Version { encoding, .. }))
+            if encoding == wasmparser::Encoding::Module =>
+        {
+            Ok(())
+        }
+        Some(Err(e)) => Err(e).context("failed to parse input module"),
+        _ => bail!("input is not a core WebAssembly module"),
+    }
+}
+
+pub(crate) fn append_custom_section(wasm: &[u8], name: &str, data: &[u8]) -> Vec<u8> {
+    let section = wasm_encoder::CustomSection { name, data };
+    let mut ret = wasm.to_vec();
+    ret.push(section.id());
+    section.encode(&mut ret);
+    ret
+}
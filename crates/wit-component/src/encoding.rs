@@ -53,12 +53,18 @@
 //! component model.
 
 use crate::extract::{extract_module_interfaces, ModuleInterfaces};
+use crate::rename::Renames;
 use crate::{
-    validation::{validate_adapter_module, validate_module, ValidatedAdapter, ValidatedModule},
-    ComponentInterfaces, StringEncoding,
+    validation::{
+        validate_adapter_module, validate_library, validate_module, ValidatedAdapter,
+        ValidatedLibrary, ValidatedModule,
+    },
+    CanonicalOptions, ComponentInterfaces, ImportStub, StringEncoding,
 };
 use anyhow::{anyhow, bail, Context, Result};
 use indexmap::{map::Entry, IndexMap, IndexSet};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 use std::mem;
 use std::path::Path;
@@ -1115,6 +1121,32 @@ impl TypeContents {
     }
 }
 
+/// The canonical ABI options configured for a component, used to determine
+/// the options of each individual lift and lower.
+struct OptionsConfig<'a> {
+    /// The string encoding used when no other is configured.
+    encoding: StringEncoding,
+    /// Configured options keyed by interface name or `INTERFACE#FUNCTION`.
+    options: &'a IndexMap<String, CanonicalOptions>,
+}
+
+impl<'a> OptionsConfig<'a> {
+    /// Returns the string encoding and the name of the `realloc` function, if
+    /// not the default, to use for `func` of `interface`.
+    fn resolve(&self, interface: &str, func: &str) -> (StringEncoding, Option<&'a str>) {
+        let for_interface = self.options.get(interface);
+        let for_func = self.options.get(&format!("{interface}#{func}"));
+        let encoding = for_func
+            .and_then(|o| o.encoding)
+            .or_else(|| for_interface.and_then(|o| o.encoding))
+            .unwrap_or(self.encoding);
+        let realloc = for_func
+            .and_then(|o| o.realloc.as_deref())
+            .or_else(|| for_interface.and_then(|o| o.realloc.as_deref()));
+        (encoding, realloc)
+    }
+}
+
 /// State relating to encoding a component.
 #[derive(Default)]
 struct EncodingState<'a> {
@@ -1136,6 +1168,9 @@ struct EncodingState<'a> {
     ///
     /// If `None`, then the realloc function has not yet been aliased.
     realloc_index: Option<u32>,
+    /// A map of custom realloc functions exported from the core module, as
+    /// configured with canonical options, to their aliased index.
+    custom_reallocs: IndexMap<String, u32>,
     /// The index of the shim instance used for lowering imports into the core instance.
     ///
     /// If `None`, then the shim instance how not yet been encoded.
@@ -1153,28 +1188,62 @@ struct EncodingState<'a> {
     /// A map of the index of the aliased realloc function for each adapter
     /// module.
     adapter_reallocs: IndexMap<&'a str, Option<u32>>,
+
+    /// A map of libraries, in the order they're instantiated, to the index
+    /// that the module was defined at and the other libraries it imports.
+    library_modules: IndexMap<&'a str, (u32, IndexSet<&'a str>)>,
+    /// A map of library instances and the index of their instance.
+    library_instances: IndexMap<&'a str, u32>,
 }
 
 impl<'a> EncodingState<'a> {
     fn encode_core_module(&mut self, module: &[u8]) -> u32 {
         assert!(self.module_index.is_none());
         let ret = self.component.core_module_raw(module);
+        self.component
+            .name(NameSort::CoreModule, ret, MAIN_MODULE_NAME);
         self.module_index = Some(ret);
         ret
     }
 
     fn encode_core_adapter_module(&mut self, name: &'a str, module: &[u8]) -> u32 {
         let index = self.component.core_module_raw(module);
+        self.component.name(NameSort::CoreModule, index, name);
         assert!(self.adapter_modules.insert(name, index).is_none());
         index
     }
 
+    fn encode_core_library(&mut self, name: &'a str, module: &[u8], info: &ValidatedLibrary<'a>) {
+        let index = self.component.core_module_raw(module);
+        self.component.name(NameSort::CoreModule, index, name);
+        let deps = info.imports.iter().map(|(module, _, _)| *module).collect();
+        assert!(self.library_modules.insert(name, (index, deps)).is_none());
+    }
+
+    /// Instantiates all libraries, each with the instances of the libraries it
+    /// depends on.
+    fn instantiate_libraries(&mut self) {
+        for (name, (module, deps)) in self.library_modules.iter() {
+            let args = deps
+                .iter()
+                .map(|dep| (*dep, ModuleArg::Instance(self.library_instances[dep])))
+                .collect::<Vec<_>>();
+            let instance = self.component.instantiate(*module, args);
+            self.component.name(NameSort::CoreInstance, instance, name);
+            self.library_instances.insert(*name, instance);
+        }
+    }
+
     fn encode_core_instantiation(
         &mut self,
-        encoding: StringEncoding,
+        config: &OptionsConfig<'_>,
         imports: &ImportEncoder<'a>,
         info: &ValidatedModule<'a>,
     ) -> Result<()> {
+        // Libraries only depend on each other so they're instantiated first,
+        // in dependency order.
+        self.instantiate_libraries();
+
         // Encode a shim instantiation if needed
         let shims = self.encode_shim_instantiation(imports, info);
 
@@ -1191,6 +1260,13 @@ impl<'a> EncodingState<'a> {
             args.push((*name, ModuleArg::Instance(index)));
         }
 
+        // Libraries are passed directly as instantiation arguments since their
+        // exports are used as-is.
+        for name in info.libraries_required.iter() {
+            let index = self.library_instances[name];
+            args.push((*name, ModuleArg::Instance(index)));
+        }
+
         // For each adapter module instance imported into the core wasm module
         // the appropriate shim is packaged up into a bag-of-exports instance.
         // Note that adapter modules currently don't deal with
@@ -1223,7 +1299,47 @@ impl<'a> EncodingState<'a> {
 
         // With all the core wasm instances in play now the original shim
         // module, if present, can be filled in with lowerings/adapters/etc.
-        self.encode_indirect_lowerings(encoding, imports, shims)
+        self.encode_indirect_lowerings(config, imports, shims)?;
+
+        // Finally the module's initializer, if any, is run now that everything
+        // it may call into is available.
+        if let Some(name) = info.initializer {
+            self.encode_initializer(name);
+        }
+        Ok(())
+    }
+
+    /// Calls the exported function `name` of the main module during
+    /// instantiation of the component.
+    ///
+    /// This is done by instantiating a module whose `start` function is the
+    /// imported initializer.
+    fn encode_initializer(&mut self, name: &str) {
+        let instance_index = self.instance_index.expect("must be instantiated");
+        let func_index = self
+            .component
+            .alias_core_item(instance_index, ExportKind::Func, name);
+
+        let mut types = TypeSection::new();
+        types.function([], []);
+        let mut imports = ImportSection::new();
+        imports.import("", name, EntityType::Function(0));
+        let mut init = Module::default();
+        init.section(&types);
+        init.section(&imports);
+        init.section(&StartSection { function_index: 0 });
+
+        let module_index = self.component.core_module(&init);
+        let args = self
+            .component
+            .instantiate_core_exports([(name, ExportKind::Func, func_index)]);
+        let instance_index = self
+            .component
+            .instantiate(module_index, [("", ModuleArg::Instance(args))]);
+        self.component
+            .name(NameSort::CoreModule, module_index, INIT_MODULE_NAME);
+        self.component
+            .name(NameSort::CoreInstance, instance_index, INIT_MODULE_NAME);
     }
 
     /// Lowers a named imported interface a core wasm instances suitable to
@@ -1267,6 +1383,11 @@ impl<'a> EncodingState<'a> {
                 .component
                 .alias_func(instance_index as u32, lowering.name);
             let core_func_index = self.component.lower_func(func_index, []);
+            self.component.name(
+                NameSort::CoreFunc,
+                core_func_index,
+                &format!("{name}#{}", lowering.name),
+            );
             exports.push((lowering.name, ExportKind::Func, core_func_index));
         }
 
@@ -1275,13 +1396,17 @@ impl<'a> EncodingState<'a> {
 
     fn encode_imports(&mut self, imports: &ImportEncoder) {
         for (name, import) in &imports.map {
-            self.component.import(name, import.ty);
+            let index = self.component.import(name, import.ty);
+            self.component.name(NameSort::Instance, index, name);
+            if let ComponentTypeRef::Instance(ty) = import.ty {
+                self.component.name(NameSort::Type, ty, name);
+            }
         }
     }
 
     fn encode_exports<'b>(
         &mut self,
-        encoding: StringEncoding,
+        config: &OptionsConfig<'_>,
         exports: impl Iterator<Item = (&'b Interface, bool)>,
         types: &TypeEncoder<'b>,
     ) -> Result<()> {
@@ -1303,6 +1428,7 @@ impl<'a> EncodingState<'a> {
                         def,
                     })
                     .expect("the type should be encoded");
+                self.component.name(NameSort::Type, ty, name);
                 interface_exports.push((name.as_str(), ComponentExportKind::Type, ty));
             }
 
@@ -1324,9 +1450,11 @@ impl<'a> EncodingState<'a> {
                     .expect("the type should be encoded");
 
                 let options = RequiredOptions::for_export(export, func);
+                let (encoding, realloc) = config.resolve(&export.name, &func.name);
+                let realloc = self.main_realloc(realloc);
 
                 let mut options = options
-                    .into_iter(encoding, self.memory_index, self.realloc_index)?
+                    .into_iter(encoding, self.memory_index, realloc)?
                     .collect::<Vec<_>>();
                 if export.guest_export_needs_post_return(func) {
                     let post_return = self.component.alias_core_item(
@@ -1337,6 +1465,12 @@ impl<'a> EncodingState<'a> {
                     options.push(CanonicalOption::PostReturn(post_return));
                 }
                 let func_index = self.component.lift_func(core_func_index, ty, options);
+                if is_default {
+                    self.component.name(NameSort::Func, func_index, &func.name);
+                } else {
+                    let name = format!("{}#{}", export.name, func.name);
+                    self.component.name(NameSort::Func, func_index, &name);
+                }
 
                 interface_exports.push((func.name.as_str(), ComponentExportKind::Func, func_index));
             }
@@ -1358,6 +1492,8 @@ impl<'a> EncodingState<'a> {
                 }
 
                 let instance_index = self.component.instantiate_exports(interface_exports);
+                self.component
+                    .name(NameSort::Instance, instance_index, &export.name);
                 self.component
                     .export(&export.name, ComponentExportKind::Instance, instance_index);
             }
@@ -1493,8 +1629,22 @@ impl<'a> EncodingState<'a> {
         fixups.section(&elements);
 
         let shim_module_index = self.component.core_module(&shim);
-        self.fixups_module_index = Some(self.component.core_module(&fixups));
-        self.shim_instance_index = Some(self.component.instantiate(shim_module_index, []));
+        let fixups_module_index = self.component.core_module(&fixups);
+        let shim_instance_index = self.component.instantiate(shim_module_index, []);
+        self.component
+            .name(NameSort::CoreModule, shim_module_index, SHIM_MODULE_NAME);
+        self.component.name(
+            NameSort::CoreModule,
+            fixups_module_index,
+            FIXUPS_MODULE_NAME,
+        );
+        self.component.name(
+            NameSort::CoreInstance,
+            shim_instance_index,
+            SHIM_MODULE_NAME,
+        );
+        self.fixups_module_index = Some(fixups_module_index);
+        self.shim_instance_index = Some(shim_instance_index);
 
         return ret;
 
@@ -1530,7 +1680,7 @@ impl<'a> EncodingState<'a> {
 
     fn encode_indirect_lowerings(
         &mut self,
-        encoding: StringEncoding,
+        config: &OptionsConfig<'_>,
         imports: &ImportEncoder,
         shims: Shims<'_>,
     ) -> Result<()> {
@@ -1564,22 +1714,25 @@ impl<'a> EncodingState<'a> {
                     indirect_index,
                     realloc,
                 } => {
-                    let (instance_index, _, interface) = imports.map.get_full(interface).unwrap();
-                    let func_index = self.component.alias_func(
-                        instance_index as u32,
-                        interface.indirect[*indirect_index].name,
-                    );
+                    let name = *interface;
+                    let (instance_index, _, interface) = imports.map.get_full(name).unwrap();
+                    let func = interface.indirect[*indirect_index].name;
+                    let func_index = self.component.alias_func(instance_index as u32, func);
 
+                    let (encoding, custom_realloc) = config.resolve(name, func);
                     let realloc = match realloc {
-                        CustomModule::Main => self.realloc_index,
+                        CustomModule::Main => self.main_realloc(custom_realloc),
                         CustomModule::Adapter(name) => self.adapter_reallocs[name],
                     };
 
-                    self.component.lower_func(
+                    let index = self.component.lower_func(
                         func_index,
                         shim.options
                             .into_iter(encoding, self.memory_index, realloc)?,
-                    )
+                    );
+                    self.component
+                        .name(NameSort::CoreFunc, index, &format!("{name}#{func}"));
+                    index
                 }
 
                 // Adapter shims are defined by an export from and adapter
@@ -1597,10 +1750,15 @@ impl<'a> EncodingState<'a> {
         }
 
         let instance_index = self.component.instantiate_core_exports(exports);
-        self.component.instantiate(
+        let fixups_instance_index = self.component.instantiate(
             self.fixups_module_index.expect("must have fixup module"),
             [("", ModuleArg::Instance(instance_index))],
         );
+        self.component.name(
+            NameSort::CoreInstance,
+            fixups_instance_index,
+            FIXUPS_MODULE_NAME,
+        );
         Ok(())
     }
 
@@ -1614,6 +1772,8 @@ impl<'a> EncodingState<'a> {
         let instance_index = self
             .component
             .instantiate(self.module_index.expect("core module encoded"), args);
+        self.component
+            .name(NameSort::CoreInstance, instance_index, MAIN_MODULE_NAME);
 
         if info.has_memory {
             self.memory_index = Some(self.component.alias_core_item(
@@ -1634,6 +1794,25 @@ impl<'a> EncodingState<'a> {
         self.instance_index = Some(instance_index);
     }
 
+    /// Returns the index of the `realloc` function of the main module, which
+    /// is either the custom function `name`, if specified, or `cabi_realloc`.
+    fn main_realloc(&mut self, name: Option<&str>) -> Option<u32> {
+        let name = match name {
+            Some(name) => name,
+            None => return self.realloc_index,
+        };
+        if let Some(index) = self.custom_reallocs.get(name) {
+            return Some(*index);
+        }
+        let index = self.component.alias_core_item(
+            self.instance_index.expect("must be instantiated"),
+            ExportKind::Func,
+            name,
+        );
+        self.custom_reallocs.insert(name.to_string(), index);
+        Some(index)
+    }
+
     /// This function will instantiate all required adapter modules required by
     /// the main module (specified by `info`).
     ///
@@ -1676,6 +1855,7 @@ impl<'a> EncodingState<'a> {
                 args.push((import_name, ModuleArg::Instance(instance)));
             }
             let instance = self.component.instantiate(self.adapter_modules[name], args);
+            self.component.name(NameSort::CoreInstance, instance, name);
             self.adapter_instances.insert(name, instance);
             let realloc = if info.has_realloc {
                 Some(
@@ -1821,6 +2001,48 @@ struct ImportedInterface<'a> {
     indirect: Vec<IndirectLowering<'a>>,
 }
 
+/// The name given to the main core module, and its instance, in the
+/// `component-name` section.
+const MAIN_MODULE_NAME: &str = "main";
+
+/// The name given to the synthesized module of shim functions, and its
+/// instance, in the `component-name` section.
+const SHIM_MODULE_NAME: &str = "wit-component:shim";
+
+/// The name given to the synthesized module which fills in the shim functions'
+/// table, and its instance, in the `component-name` section.
+const FIXUPS_MODULE_NAME: &str = "wit-component:fixups";
+
+/// The name given to the synthesized module which calls the main module's
+/// initializer, and its instance, in the `component-name` section.
+const INIT_MODULE_NAME: &str = "wit-component:init";
+
+/// An index space of a component whose items can be named in the
+/// `component-name` custom section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum NameSort {
+    CoreFunc,
+    CoreModule,
+    CoreInstance,
+    Func,
+    Type,
+    Instance,
+}
+
+impl NameSort {
+    /// Returns the encoding of this sort within a `component-name` subsection.
+    fn encoding(&self) -> &'static [u8] {
+        match self {
+            NameSort::CoreFunc => &[0x00, 0x00],
+            NameSort::CoreModule => &[0x00, 0x11],
+            NameSort::CoreInstance => &[0x00, 0x12],
+            NameSort::Func => &[0x01],
+            NameSort::Type => &[0x03],
+            NameSort::Instance => &[0x05],
+        }
+    }
+}
+
 /// Helper type used when encoding a component to have helpers that
 /// simultaneously encode an item while returning its corresponding index in the
 /// generated index spaces as well.
@@ -1847,14 +2069,52 @@ struct ComponentEncoding {
     // Component index spaces
     funcs: u32,
     instances: u32,
+
+    /// Names of items within the index spaces above which are emitted in the
+    /// `component-name` custom section.
+    names: BTreeMap<NameSort, BTreeMap<u32, String>>,
 }
 
 impl ComponentEncoding {
     fn finish(mut self) -> Vec<u8> {
         self.flush();
+        if !self.names.is_empty() {
+            let data = self.encode_names();
+            self.component.section(&CustomSection {
+                name: "component-name",
+                data: &data,
+            });
+        }
         self.component.finish()
     }
 
+    /// Records `name` as the name of the item at `index` within the `sort`
+    /// index space. Only the first name given to an item is kept.
+    fn name(&mut self, sort: NameSort, index: u32, name: &str) {
+        self.names
+            .entry(sort)
+            .or_default()
+            .entry(index)
+            .or_insert_with(|| name.to_string());
+    }
+
+    /// Encodes the contents of the `component-name` custom section, with one
+    /// subsection of names for each index space.
+    fn encode_names(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (sort, names) in self.names.iter() {
+            let mut subsection = sort.encoding().to_vec();
+            names.len().encode(&mut subsection);
+            for (index, name) in names {
+                index.encode(&mut subsection);
+                name.as_str().encode(&mut subsection);
+            }
+            data.push(0x01);
+            subsection.encode(&mut data);
+        }
+        data
+    }
+
     fn instantiate<'a, A>(&mut self, module_index: u32, args: A) -> u32
     where
         A: IntoIterator<Item = (&'a str, ModuleArg)>,
@@ -2084,6 +2344,51 @@ impl<'a> ImportEncoder<'a> {
     }
 }
 
+/// Returns the libraries required by the main module described by `info`,
+/// directly or through other libraries, in the order they must be
+/// instantiated.
+fn library_order<'a>(
+    libraries: &IndexMap<&'a str, ValidatedLibrary<'_>>,
+    info: &ValidatedModule<'_>,
+) -> Result<Vec<&'a str>> {
+    fn visit<'a>(
+        name: &str,
+        libraries: &IndexMap<&'a str, ValidatedLibrary<'_>>,
+        visiting: &mut IndexSet<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<()> {
+        let (_, name, library) = libraries.get_full(name).unwrap();
+        if order.contains(name) {
+            return Ok(());
+        }
+        if !visiting.insert(name) {
+            bail!("cycle in library dependencies involving `{name}`");
+        }
+        for (module, field, item) in library.imports.iter() {
+            match libraries.get(module) {
+                Some(dep) => dep.validate_import(module, field, item)?,
+                // Imports of libraries would need lowerings or adapters of
+                // their own, which aren't supported.
+                None => bail!(
+                    "library `{name}` imports `{module}::{field}`, but libraries can only \
+                     import from other libraries, not from interfaces or adapters"
+                ),
+            }
+            visit(module, libraries, visiting, order)?;
+        }
+        visiting.remove(name);
+        order.push(name);
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    let mut visiting = IndexSet::new();
+    for name in info.libraries_required.iter() {
+        visit(name, libraries, &mut visiting, &mut order)?;
+    }
+    Ok(order)
+}
+
 /// An encoder of components based on `wit` interface definitions.
 #[derive(Default)]
 pub struct ComponentEncoder {
@@ -2095,12 +2400,19 @@ pub struct ComponentEncoder {
     validate: bool,
     types_only: bool,
     adapters: IndexMap<String, (Vec<u8>, Interface)>,
+    stubs: IndexMap<String, ImportStub>,
+    options: IndexMap<String, CanonicalOptions>,
+    libraries: IndexMap<String, Vec<u8>>,
+    renames: Renames,
+    initializer: Option<String>,
 }
 
 impl ComponentEncoder {
     /// Set the core module to encode as a component.
     /// This method will also parse any component type information stored in custom sections
     /// inside the module, and add them as the interface, imports, and exports.
+    /// Canonical ABI options embedded in the module are used unless they're
+    /// configured with [`ComponentEncoder::canonical_options`].
     pub fn module(mut self, module: &[u8]) -> Result<Self> {
         let ModuleInterfaces {
             wasm,
@@ -2110,8 +2422,12 @@ impl ComponentEncoder {
                     exports,
                     default,
                 },
+            options,
         } = extract_module_interfaces(module)?;
         self.module = wasm;
+        for (target, options) in options {
+            self.options.entry(target).or_insert(options);
+        }
         let mut me = self;
         me = me.imports(imports.into_iter().map(|p| p.1))?;
         me = me.exports(exports.into_iter().map(|p| p.1))?;
@@ -2127,6 +2443,24 @@ impl ComponentEncoder {
         self
     }
 
+    /// Set the canonical ABI options used for lifting and lowering the
+    /// functions of an interface.
+    ///
+    /// The `target` is either the name of an imported or exported interface,
+    /// in which case the options apply to all of its functions, or
+    /// `INTERFACE#FUNCTION` to apply to a single function. Options configured
+    /// for a function take precedence over those of its interface, which in
+    /// turn take precedence over [`ComponentEncoder::encoding`] and the
+    /// default `cabi_realloc` function.
+    ///
+    /// A custom `realloc` function must be exported from the main module and
+    /// is only used for lowerings into the main module, so it's an error to
+    /// configure one for an interface imported by an adapter.
+    pub fn canonical_options(mut self, target: &str, options: CanonicalOptions) -> Self {
+        self.options.insert(target.to_string(), options);
+        self
+    }
+
     /// Sets whether or not the encoder will validate its output.
     pub fn validate(mut self, validate: bool) -> Self {
         self.validate = validate;
@@ -2208,6 +2542,7 @@ impl ComponentEncoder {
                     exports,
                     default,
                 },
+            ..
         } = extract_module_interfaces(&wasm)?;
         if !exports.is_empty() || default.is_some() {
             bail!("adapter modules cannot have an exported interface");
@@ -2221,6 +2556,87 @@ impl ComponentEncoder {
         Ok(self)
     }
 
+    /// Adds a library, a core wasm module which is linked with the main
+    /// module within the component.
+    ///
+    /// Imports of the main module from the module `name` are satisfied by the
+    /// exports of the library `bytes`. Unlike interfaces and adapters these
+    /// imports may be memories, tables, and globals in addition to functions,
+    /// for example to share a linear memory defined by a separately built
+    /// libc. Imported functions and globals must have the same type as the
+    /// exports which satisfy them.
+    ///
+    /// Libraries may only import from other libraries. Imports of a library
+    /// from an interface or an adapter aren't supported and are an error,
+    /// so such functions must be passed to the library by the main module
+    /// instead, for example through a table.
+    ///
+    /// Lifted and lowered functions still use the `memory` and `cabi_realloc`
+    /// exports of the main module, which can re-export those it imports from a
+    /// library.
+    ///
+    /// Libraries are instantiated in dependency order before the main module
+    /// and only the libraries which the main module uses, either directly or
+    /// through other libraries, are included in the component.
+    pub fn library(mut self, name: &str, bytes: &[u8]) -> Self {
+        self.libraries.insert(name.to_string(), bytes.to_vec());
+        self
+    }
+
+    /// Renames imports of the core module matching `from` to `to`, for
+    /// example to match the name of an imported interface when a toolchain
+    /// uses a fixed import module name such as `env`.
+    ///
+    /// The rule `from` matches the import module name, or when it contains
+    /// `::` both the module name and field name, for example
+    /// `env::foo_*`. A `*` matches any sequence of characters and the text
+    /// matched by each `*` is substituted for the corresponding `*` in `to`,
+    /// for example `env::foo_*` renamed to `foo::*`. The first matching rule
+    /// applies and each rule must match at least one import.
+    pub fn rename_import(mut self, from: &str, to: &str) -> Self {
+        self.renames
+            .imports
+            .push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Renames exports of the core module matching `from` to `to`, for
+    /// example to match the names of the functions of an exported interface.
+    ///
+    /// Wildcards are supported in the same way as
+    /// [`ComponentEncoder::rename_import`].
+    pub fn rename_export(mut self, from: &str, to: &str) -> Self {
+        self.renames
+            .exports
+            .push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Sets the exported function of the core module which initializes it.
+    ///
+    /// The initializer must have type `() -> ()` and is called while the
+    /// component is being instantiated, after all of its core instances are
+    /// created and before any of its exports can be called. If this isn't
+    /// specified then an `_initialize` export, as found in WASI reactors, is
+    /// used as the initializer if present.
+    pub fn initializer(mut self, name: &str) -> Self {
+        self.initializer = Some(name.to_string());
+        self
+    }
+
+    /// Replaces the imported interface `name` with an internally generated
+    /// implementation whose behavior is described by `stub`.
+    ///
+    /// The resulting component will not import `name` at all, which can be
+    /// used to guarantee that an interface is not reachable from within a
+    /// component. The interface `name` must still be specified as an import,
+    /// either through [`ComponentEncoder::imports`] or the metadata embedded
+    /// in the module, as its definition is used to generate the stubs.
+    pub fn stub_import(mut self, name: &str, stub: ImportStub) -> Self {
+        self.stubs.insert(name.to_string(), stub);
+        self
+    }
+
     /// Indicates whether this encoder is only encoding types and does not
     /// require a `module` as input.
     pub fn types_only(mut self, only: bool) -> Self {
@@ -2230,18 +2646,68 @@ impl ComponentEncoder {
 
     /// Encode the component and return the bytes.
     pub fn encode(&self) -> Result<Vec<u8>> {
+        // Stubbed imports are removed from the set of imported interfaces and
+        // are instead satisfied by a generated adapter module which doesn't
+        // itself import anything.
+        let stub_interface = Interface::default();
+        let mut adapters = IndexMap::new();
+        for (name, (wasm, interface)) in self.adapters.iter() {
+            adapters.insert(name.as_str(), (Cow::Borrowed(&wasm[..]), interface));
+        }
+        let mut import_interfaces = IndexMap::new();
+        for (name, interface) in self.imports.iter() {
+            let stub = match self.stubs.get(name) {
+                Some(stub) => *stub,
+                None => {
+                    import_interfaces.insert(name.clone(), interface.clone());
+                    continue;
+                }
+            };
+            if adapters.contains_key(name.as_str()) {
+                bail!("cannot both stub and adapt the import `{name}`");
+            }
+            let wasm = crate::stub::run(interface, stub)
+                .with_context(|| format!("failed to generate a stub for import `{name}`"))?;
+            adapters.insert(name.as_str(), (Cow::Owned(wasm), &stub_interface));
+        }
+        for name in self.stubs.keys() {
+            if !self.imports.contains_key(name) {
+                bail!("cannot stub `{name}` as it is not an imported interface");
+            }
+        }
+
+        let mut libraries = IndexMap::new();
+        for (name, wasm) in self.libraries.iter() {
+            if import_interfaces.contains_key(name) || adapters.contains_key(name.as_str()) {
+                bail!("library `{name}` conflicts with an import of the same name");
+            }
+            let library = validate_library(wasm)
+                .with_context(|| format!("failed to validate library `{name}`"))?;
+            libraries.insert(name.as_str(), library);
+        }
+
+        let module = if self.module.is_empty() {
+            Cow::Borrowed(&self.module[..])
+        } else {
+            self.renames.apply(&self.module)?
+        };
         let info = if !self.module.is_empty() {
-            let adapters = self
-                .adapters
-                .keys()
-                .map(|s| s.as_str())
+            self.validate_options(&adapters)?;
+            let adapters = adapters.keys().copied().collect::<IndexSet<_>>();
+            let reallocs = self
+                .options
+                .values()
+                .filter_map(|o| o.realloc.as_deref())
                 .collect::<IndexSet<_>>();
             Some(validate_module(
-                &self.module,
+                &module,
                 &self.interface,
-                &self.imports,
+                &import_interfaces,
                 &self.exports,
                 &adapters,
+                &libraries,
+                &reallocs,
+                self.initializer.as_deref(),
             )?)
         } else {
             None
@@ -2257,7 +2723,7 @@ impl ComponentEncoder {
         let mut types = TypeEncoder::default();
         let mut imports = ImportEncoder::default();
         types.encode_func_types(exports.clone().map(|(i, _)| i))?;
-        types.encode_instance_imports(&self.imports, info.as_ref(), &mut imports)?;
+        types.encode_instance_imports(&import_interfaces, info.as_ref(), &mut imports)?;
 
         if self.types_only {
             if !self.module.is_empty() {
@@ -2308,7 +2774,7 @@ impl ComponentEncoder {
             // provided to this encoder, gc it to an appropriate size, and then
             // register its metadata in our data structures.
             for (name, required) in info.adapters_required.iter() {
-                let (wasm, interface) = &self.adapters[*name];
+                let (wasm, interface) = &adapters[*name];
                 let wasm = crate::gc::run(wasm, required)
                     .context("failed to reduce input adapter module to its minimal size")?;
                 let info = validate_adapter_module(&wasm, interface, required)
@@ -2329,9 +2795,16 @@ impl ComponentEncoder {
             types.finish(&mut state.component);
 
             state.encode_imports(&imports);
-            state.encode_core_module(&self.module);
-            state.encode_core_instantiation(self.encoding, &imports, info)?;
-            state.encode_exports(self.encoding, exports, &types)?;
+            for name in library_order(&libraries, info)? {
+                state.encode_core_library(name, &self.libraries[name], &libraries[name]);
+            }
+            state.encode_core_module(&module);
+            let config = OptionsConfig {
+                encoding: self.encoding,
+                options: &self.options,
+            };
+            state.encode_core_instantiation(&config, &imports, info)?;
+            state.encode_exports(&config, exports, &types)?;
         }
 
         let bytes = state.component.finish();
@@ -2349,4 +2822,49 @@ impl ComponentEncoder {
 
         Ok(bytes)
     }
+
+    /// Validates that all configured canonical options refer to an interface,
+    /// or function of an interface, known to this encoder, and that no
+    /// `realloc` is configured for the interface of an adapter.
+    fn validate_options(
+        &self,
+        adapters: &IndexMap<&str, (Cow<'_, [u8]>, &Interface)>,
+    ) -> Result<()> {
+        for (target, options) in self.options.iter() {
+            let (name, func) = match target.split_once('#') {
+                Some((name, func)) => (name, Some(func)),
+                None => (target.as_str(), None),
+            };
+            // Lowerings for an adapter always use the adapter's own
+            // `cabi_realloc`, so a `realloc` for them would be ignored.
+            if options.realloc.is_some() {
+                if let Some((adapter, _)) = adapters.iter().find(|(_, (_, i))| i.name == name) {
+                    bail!(
+                        "canonical option `realloc` specified for interface `{name}`, \
+                         which is imported by adapter `{adapter}` and always lowered with \
+                         the adapter's `cabi_realloc`"
+                    );
+                }
+            }
+            let interface = self
+                .imports
+                .get(name)
+                .or_else(|| self.exports.get(name))
+                .or_else(|| self.interface.as_ref().filter(|i| i.name == name))
+                .or_else(|| adapters.values().map(|(_, i)| *i).find(|i| i.name == name));
+            let interface = match interface {
+                Some(interface) => interface,
+                None => bail!("canonical options specified for unknown interface `{name}`"),
+            };
+            if let Some(func) = func {
+                if !interface.functions.iter().any(|f| f.name == func) {
+                    bail!(
+                        "canonical options specified for unknown function `{func}` \
+                         of interface `{name}`"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
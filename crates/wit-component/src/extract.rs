@@ -1,5 +1,6 @@
-use crate::{decode_component_interfaces, ComponentInterfaces};
+use crate::{decode_component_interfaces, CanonicalOptions, ComponentInterfaces};
 use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
 
 /// Result of extracting interfaces embedded within a core wasm file.
 ///
@@ -11,6 +12,10 @@ pub struct ModuleInterfaces {
 
     /// The interfaces found within the original component.
     pub interfaces: ComponentInterfaces,
+
+    /// The canonical ABI options found within the original component, keyed
+    /// by interface name or `INTERFACE#FUNCTION`.
+    pub options: IndexMap<String, CanonicalOptions>,
 }
 
 /// This function will parse the `wasm` binary given as input and return a
@@ -28,11 +33,13 @@ pub fn extract_module_interfaces(wasm: &[u8]) -> Result<ModuleInterfaces> {
         if let wasmparser::Payload::CustomSection(cs) =
             payload.context("decoding item in module")?
         {
-            if !cs.name().starts_with("component-type") {
-                continue;
+            if cs.name().starts_with("component-type") {
+                ret.decode(cs.data())
+                    .with_context(|| format!("decoding custom section {}", cs.name()))?;
+            } else if cs.name().starts_with("component-options") {
+                ret.decode_options(cs.data())
+                    .with_context(|| format!("decoding custom section {}", cs.name()))?;
             }
-            ret.decode(cs.data())
-                .with_context(|| format!("decoding custom section {}", cs.name()))?;
         }
     }
 
@@ -77,4 +84,18 @@ impl ModuleInterfaces {
         }
         Ok(())
     }
+
+    fn decode_options(&mut self, data: &[u8]) -> Result<()> {
+        let data = std::str::from_utf8(data).context("options are not valid utf-8")?;
+        // Each line is `TARGET OPTIONS` where the target may be empty to refer
+        // to an unnamed default interface.
+        for line in data.lines().filter(|l| !l.trim().is_empty()) {
+            let (target, options) = line.split_once(' ').unwrap_or((line, ""));
+            if self.options.contains_key(target) {
+                bail!("canonical options for `{target}` specified twice");
+            }
+            self.options.insert(target.to_string(), options.parse()?);
+        }
+        Ok(())
+    }
 }
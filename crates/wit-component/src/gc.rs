@@ -14,7 +14,10 @@ use wasmparser::*;
 pub fn run(wasm: &[u8], required: &IndexMap<&str, FuncType>) -> Result<Vec<u8>> {
     assert!(!required.is_empty());
 
-    let mut module = Module::default();
+    let mut module = Module {
+        adapter: true,
+        ..Module::default()
+    };
     module.parse(wasm)?;
 
     // Make sure that all required names are present in the module, and then
@@ -40,6 +43,40 @@ pub fn run(wasm: &[u8], required: &IndexMap<&str, FuncType>) -> Result<Vec<u8>>
     module.encode()
 }
 
+/// Removes all exports from the core wasm module `wasm` except for those named
+/// in `keep_exports`, and then removes all items of the module which are no
+/// longer reachable.
+///
+/// Reachability starts from the remaining exports, the `start` function, and
+/// all active data and element segments. Unused functions, globals, tables,
+/// memories, types, imports, and passive segments are removed and all indices
+/// are renumbered accordingly. The `name` section is preserved for the items
+/// which remain, as are the `component-type` and `component-options` custom
+/// sections embedded for [`ComponentEncoder`](crate::ComponentEncoder). Other
+/// custom sections, such as DWARF debug info, are removed since they may
+/// refer to removed items or code offsets.
+///
+/// This can be used to strip a module down to just the exports needed by a
+/// component, for example `memory`, `cabi_realloc`, and the functions of an
+/// exported interface, before encoding it with
+/// [`ComponentEncoder`](crate::ComponentEncoder).
+pub fn gc_module(wasm: &[u8], keep_exports: &[&str]) -> Result<Vec<u8>> {
+    let mut module = Module {
+        keep_component_sections: true,
+        ..Module::default()
+    };
+    module.parse(wasm)?;
+
+    for name in keep_exports {
+        if !module.exports.contains_key(name) {
+            bail!("module does not have export `{name}`")
+        }
+    }
+    module.exports.retain(|name, _| keep_exports.contains(name));
+    module.liveness()?;
+    module.encode()
+}
+
 // Represents a function called while processing a module work list.
 type WorklistFunc<'a> = fn(&mut Module<'a>, u32) -> Result<()>;
 
@@ -47,20 +84,43 @@ type WorklistFunc<'a> = fn(&mut Module<'a>, u32) -> Result<()>;
 // set of required items necessary to implement the `exports`
 //
 // Note that this is not a complete representation of a wasm module since it
-// doesn't represent everything such as data and element segments. This is only
-// used for adapter modules which otherwise have these restrictions and makes
-// this gc pass a bit easier to write.
+// doesn't represent everything such as tags. Adapter modules are further
+// restricted to not have data or element segments, tables, or a start
+// function, since the adapter is instantiated in a particular way and has a
+// start function injected to initialize its stack pointer.
 #[derive(Default)]
 struct Module<'a> {
+    // Whether this module is an adapter module, which is validated to have the
+    // restricted shape of an adapter.
+    adapter: bool,
+
+    // Whether the `component-type` and `component-options` custom sections,
+    // which don't refer to any indices, are kept in `custom_sections`.
+    keep_component_sections: bool,
+    custom_sections: Vec<(&'a str, &'a [u8])>,
+
     // Definitions found when parsing a module
     types: Vec<wasmparser::Type>,
     tables: Vec<Table<'a>>,
     globals: Vec<Global<'a>>,
     memories: Vec<Memory<'a>>,
     funcs: Vec<Func<'a>>,
+    elements: Vec<Element<'a>>,
+    data: Vec<Data<'a>>,
     exports: IndexMap<&'a str, Export<'a>>,
+    start: Option<u32>,
+    has_data_count: bool,
+
+    // Names found in the `name` section, keyed by index in the original module.
+    module_name: Option<&'a str>,
     func_names: HashMap<u32, &'a str>,
+    local_names: HashMap<u32, Vec<(u32, &'a str)>>,
+    type_names: HashMap<u32, &'a str>,
+    table_names: HashMap<u32, &'a str>,
+    memory_names: HashMap<u32, &'a str>,
     global_names: HashMap<u32, &'a str>,
+    element_names: HashMap<u32, &'a str>,
+    data_names: HashMap<u32, &'a str>,
 
     // Known-live sets of indices after the `liveness` pass has run.
     live_types: BitVec,
@@ -68,6 +128,8 @@ struct Module<'a> {
     live_globals: BitVec,
     live_memories: BitVec,
     live_funcs: BitVec,
+    live_elements: BitVec,
+    live_data: BitVec,
 
     // Helper data structure used during the `liveness` path to avoid recursion.
     // When calculating the liveness of an item this `worklist` is pushed to and
@@ -112,7 +174,10 @@ impl<'a> Module<'a> {
             match payload {
                 Payload::Version { encoding, .. } => {
                     if encoding != Encoding::Module {
-                        bail!("adapter must be a core wasm module, not a component");
+                        if self.adapter {
+                            bail!("adapter must be a core wasm module, not a component");
+                        }
+                        bail!("input must be a core wasm module, not a component");
                     }
                 }
                 Payload::End(_) => {}
@@ -200,10 +265,16 @@ impl<'a> Module<'a> {
                 }
 
                 // Ignore all custom sections except for the `name` section
-                // which we parse, but ignore errors within.
+                // which we parse, but ignore errors within, and those for
+                // components if requested.
                 Payload::CustomSection(s) => {
                     if s.name() == "name" {
                         drop(self.parse_name_section(&s));
+                    } else if self.keep_component_sections
+                        && (s.name().starts_with("component-type")
+                            || s.name().starts_with("component-options"))
+                    {
+                        self.custom_sections.push((s.name(), s.data()));
                     }
                 }
 
@@ -213,11 +284,31 @@ impl<'a> Module<'a> {
                 | Payload::ElementSection(_)
                 | Payload::DataSection(_)
                 | Payload::StartSection { .. }
-                | Payload::TagSection(_)
-                | Payload::UnknownSection { .. } => {
+                    if self.adapter =>
+                {
                     bail!("unsupported section found in adapter module")
                 }
 
+                Payload::DataCountSection { .. } => self.has_data_count = true,
+                Payload::ElementSection(s) => {
+                    for e in s {
+                        self.elements.push(e?);
+                    }
+                }
+                Payload::DataSection(s) => {
+                    for d in s {
+                        self.data.push(d?);
+                    }
+                }
+                Payload::StartSection { func, .. } => self.start = Some(func),
+
+                Payload::TagSection(_) | Payload::UnknownSection { .. } => {
+                    if self.adapter {
+                        bail!("unsupported section found in adapter module")
+                    }
+                    bail!("unsupported section found in module")
+                }
+
                 // component-model related things that shouldn't show up
                 Payload::ModuleSection { .. }
                 | Payload::ComponentSection { .. }
@@ -242,20 +333,27 @@ impl<'a> Module<'a> {
         let section = NameSectionReader::new(section.data(), section.data_offset())?;
         for s in section {
             match s? {
-                Name::Function(map) => {
-                    let mut map = map.get_map()?;
-                    for _ in 0..map.get_count() {
-                        let naming = map.read()?;
-                        self.func_names.insert(naming.index, naming.name);
-                    }
-                }
-                Name::Global(map) => {
-                    let mut map = map.get_map()?;
-                    for _ in 0..map.get_count() {
+                Name::Module { name, .. } => self.module_name = Some(name),
+                Name::Function(map) => read_name_map(&map, &mut self.func_names)?,
+                Name::Local(map) => {
+                    let mut map = map.get_indirect_map()?;
+                    for _ in 0..map.get_indirect_count() {
                         let naming = map.read()?;
-                        self.global_names.insert(naming.index, naming.name);
+                        let mut locals = naming.get_map()?;
+                        let mut names = Vec::new();
+                        for _ in 0..locals.get_count() {
+                            let local = locals.read()?;
+                            names.push((local.index, local.name));
+                        }
+                        self.local_names.insert(naming.indirect_index, names);
                     }
                 }
+                Name::Type(map) => read_name_map(&map, &mut self.type_names)?,
+                Name::Table(map) => read_name_map(&map, &mut self.table_names)?,
+                Name::Memory(map) => read_name_map(&map, &mut self.memory_names)?,
+                Name::Global(map) => read_name_map(&map, &mut self.global_names)?,
+                Name::Element(map) => read_name_map(&map, &mut self.element_names)?,
+                Name::Data(map) => read_name_map(&map, &mut self.data_names)?,
                 _ => {}
             }
         }
@@ -263,7 +361,8 @@ impl<'a> Module<'a> {
     }
 
     /// Iteratively calculates the set of live items within this module
-    /// considering all exports as the root of live functions.
+    /// considering all exports, the start function, and active segments as
+    /// the roots of live items.
     fn liveness(&mut self) -> Result<()> {
         let exports = mem::take(&mut self.exports);
         for (_, e) in exports.iter() {
@@ -277,6 +376,24 @@ impl<'a> Module<'a> {
         }
         self.exports = exports;
 
+        if let Some(start) = self.start {
+            self.func(start);
+        }
+
+        // Active segments are always live since they're applied when the
+        // module is instantiated, which may have observable side effects such
+        // as trapping.
+        for i in 0..self.elements.len() {
+            if let ElementKind::Active { .. } = self.elements[i].kind {
+                self.element(i as u32);
+            }
+        }
+        for i in 0..self.data.len() {
+            if let DataKind::Active { .. } = self.data[i].kind {
+                self.data(i as u32);
+            }
+        }
+
         while let Some((idx, func)) = self.worklist.pop() {
             func(self, idx)?;
         }
@@ -316,6 +433,50 @@ impl<'a> Module<'a> {
         }));
     }
 
+    fn element(&mut self, element: u32) {
+        if !self.live_elements.insert(element) {
+            return;
+        }
+        self.worklist.push((element, |me, element| {
+            let element = &me.elements[element as usize];
+            let items = element.items.get_items_reader()?;
+            if let ElementKind::Active {
+                table_index,
+                offset_expr,
+            } = &element.kind
+            {
+                let (table_index, offset) = (*table_index, offset_expr.get_binary_reader());
+                me.table(table_index);
+                me.operators(offset)?;
+            }
+            for item in items {
+                match item? {
+                    ElementItem::Func(f) => me.func(f),
+                    ElementItem::Expr(e) => me.operators(e.get_binary_reader())?,
+                }
+            }
+            Ok(())
+        }));
+    }
+
+    fn data(&mut self, data: u32) {
+        if !self.live_data.insert(data) {
+            return;
+        }
+        self.worklist.push((data, |me, data| {
+            if let DataKind::Active {
+                memory_index,
+                offset_expr,
+            } = &me.data[data as usize].kind
+            {
+                let (memory_index, offset) = (*memory_index, offset_expr.get_binary_reader());
+                me.memory(memory_index);
+                me.operators(offset)?;
+            }
+            Ok(())
+        }));
+    }
+
     fn table(&mut self, table: u32) {
         self.live_tables.insert(table);
     }
@@ -357,6 +518,10 @@ impl<'a> Module<'a> {
         live_iter(&self.live_tables, self.tables.iter())
     }
 
+    fn live_data(&self) -> impl Iterator<Item = (u32, &Data<'a>)> + '_ {
+        live_iter(&self.live_data, self.data.iter())
+    }
+
     /// Encodes this `Module` to a new wasm module which is gc'd and only
     /// contains the items that are live as calculated by the `liveness` pass.
     fn encode(&mut self) -> Result<Vec<u8>> {
@@ -372,6 +537,8 @@ impl<'a> Module<'a> {
         let mut tables = wasm_encoder::TableSection::new();
         let mut memories = wasm_encoder::MemorySection::new();
         let mut globals = wasm_encoder::GlobalSection::new();
+        let mut elements = wasm_encoder::ElementSection::new();
+        let mut data = wasm_encoder::DataSection::new();
         let mut code = wasm_encoder::CodeSection::new();
 
         let mut empty_type = None;
@@ -467,6 +634,81 @@ impl<'a> Module<'a> {
             num_funcs += 1;
         }
 
+        // Segments are assigned new indices in the same manner as functions,
+        // but since they can only reference functions, and not other segments,
+        // they can be encoded immediately.
+        for (i, element) in self.elements.iter().enumerate() {
+            let i = i as u32;
+            let live = self.live_elements.contains(i);
+            let mut funcs = Vec::new();
+            let mut exprs = Vec::new();
+            for item in element.items.get_items_reader()? {
+                match item? {
+                    ElementItem::Func(f) => {
+                        // Declarative segments which aren't otherwise live only
+                        // serve to declare functions referenced with
+                        // `ref.func`, so filter them to live functions.
+                        if live || self.live_funcs.contains(f) {
+                            funcs.push(map.funcs.remap(f));
+                        }
+                    }
+                    ElementItem::Expr(e) => {
+                        if !live && !self.is_live_func_ref(&e)? {
+                            continue;
+                        }
+                        let mut bytes = map.operators(e.get_binary_reader())?;
+                        assert_eq!(bytes.pop(), Some(0xb));
+                        exprs.push(wasm_encoder::ConstExpr::raw(bytes));
+                    }
+                }
+            }
+            let items = if exprs.is_empty() {
+                wasm_encoder::Elements::Functions(&funcs)
+            } else {
+                wasm_encoder::Elements::Expressions(&exprs)
+            };
+            let ty = valty(element.ty);
+            match &element.kind {
+                ElementKind::Active {
+                    table_index,
+                    offset_expr,
+                } if live => {
+                    let mut bytes = map.operators(offset_expr.get_binary_reader())?;
+                    assert_eq!(bytes.pop(), Some(0xb));
+                    let offset = wasm_encoder::ConstExpr::raw(bytes);
+                    let table = map.tables.remap(*table_index);
+                    elements.active(Some(table), &offset, ty, items);
+                }
+                ElementKind::Passive if live => {
+                    elements.passive(ty, items);
+                }
+                ElementKind::Declared if live || !funcs.is_empty() || !exprs.is_empty() => {
+                    elements.declared(ty, items);
+                }
+                _ => continue,
+            }
+            map.elements.push(i);
+        }
+
+        for (i, segment) in self.live_data() {
+            map.data.push(i);
+            match &segment.kind {
+                DataKind::Active {
+                    memory_index,
+                    offset_expr,
+                } => {
+                    let mut bytes = map.operators(offset_expr.get_binary_reader())?;
+                    assert_eq!(bytes.pop(), Some(0xb));
+                    let offset = wasm_encoder::ConstExpr::raw(bytes);
+                    let memory = map.memories.remap(*memory_index);
+                    data.active(memory, &offset, segment.data.iter().copied());
+                }
+                DataKind::Passive => {
+                    data.passive(segment.data.iter().copied());
+                }
+            }
+        }
+
         for (_, func) in self.live_funcs() {
             let mut body = match &func.def {
                 Definition::Import(..) => continue,
@@ -487,8 +729,14 @@ impl<'a> Module<'a> {
         // Inject a start function to initialize the stack pointer which will be
         // local to this module. This only happens if a memory is preserved and
         // a stack pointer global is found.
-        let mut start = None;
-        let sp = self.find_stack_pointer()?;
+        let mut start = self.start.map(|f| wasm_encoder::StartSection {
+            function_index: map.funcs.remap(f),
+        });
+        let sp = if self.adapter {
+            self.find_stack_pointer()?
+        } else {
+            None
+        };
         if let Some(sp) = sp {
             if num_memories > 0 {
                 use wasm_encoder::Instruction::*;
@@ -545,19 +793,21 @@ impl<'a> Module<'a> {
         // this fails. Note that during parsing we've already validated there
         // are no data segments or element segments.
 
-        // Shouldn't have any tables if there are no element segments since
-        // otherwise there's no meaning to a defined or imported table.
-        if self.live_tables().count() != 0 {
-            bail!("tables should not be present in the final adapter module");
-        }
+        if self.adapter {
+            // Shouldn't have any tables if there are no element segments since
+            // otherwise there's no meaning to a defined or imported table.
+            if self.live_tables().count() != 0 {
+                bail!("tables should not be present in the final adapter module");
+            }
 
-        // multi-memory should not be enabled and if any memory it should be
-        // imported.
-        if self.live_memories().count() > 1 {
-            bail!("the adapter module should not use multi-memory");
-        }
-        if !memories.is_empty() {
-            bail!("locally-defined memories are not allowed define a local memory");
+            // multi-memory should not be enabled and if any memory it should be
+            // imported.
+            if self.live_memories().count() > 1 {
+                bail!("the adapter module should not use multi-memory");
+            }
+            if !memories.is_empty() {
+                bail!("locally-defined memories are not allowed define a local memory");
+            }
         }
 
         let mut ret = wasm_encoder::Module::default();
@@ -611,47 +861,74 @@ impl<'a> Module<'a> {
             ret.section(start);
         }
 
+        if !elements.is_empty() {
+            ret.section(&elements);
+        }
+
+        if self.has_data_count {
+            ret.section(&wasm_encoder::DataCountSection { count: data.len() });
+        }
+
         if !code.is_empty() {
             ret.section(&code);
         }
 
-        // Append a custom `name` section using the names of the functions that
-        // were found prior to the GC pass in the original module.
-        let mut func_names = Vec::new();
-        let mut global_names = Vec::new();
-        for (i, _func) in self.live_funcs() {
-            let name = match self.func_names.get(&i) {
-                Some(name) => name,
-                None => continue,
-            };
-            func_names.push((map.funcs.remap(i), *name));
+        if !data.is_empty() {
+            ret.section(&data);
         }
-        if start.is_some() {
+
+        // Append a custom `name` section using the names of the items that
+        // were found prior to the GC pass in the original module, renumbered
+        // to their new indices.
+        let mut func_names = remap_names(&self.func_names, &map.funcs);
+        if sp.is_some() && start.is_some() {
             func_names.push((num_funcs, "initialize_stack_pointer"));
         }
-        for (i, _global) in self.live_globals() {
-            let name = match self.global_names.get(&i) {
-                Some(name) => name,
-                None => continue,
-            };
-            global_names.push((map.globals.remap(i), *name));
+        let mut local_names = self
+            .local_names
+            .iter()
+            .filter_map(|(i, names)| Some((map.funcs.get(*i)?, names)))
+            .collect::<Vec<_>>();
+        local_names.sort_by_key(|(i, _)| *i);
+
+        let mut subsections = Vec::new();
+        if let Some(name) = self.module_name {
+            let mut subsection = Vec::new();
+            name.encode(&mut subsection);
+            subsections.push((0x00, subsection));
         }
-        let mut section = Vec::new();
-        let mut encode_subsection = |code: u8, names: &[(u32, &str)]| {
+        let mut name_map = |code: u8, names: &[(u32, &str)]| {
             if names.is_empty() {
                 return;
             }
             let mut subsection = Vec::new();
-            names.len().encode(&mut subsection);
-            for (i, name) in names {
+            encode_name_map(names, &mut subsection);
+            subsections.push((code, subsection));
+        };
+        name_map(0x01, &func_names);
+        name_map(0x04, &remap_names(&self.type_names, &map.types));
+        name_map(0x05, &remap_names(&self.table_names, &map.tables));
+        name_map(0x06, &remap_names(&self.memory_names, &map.memories));
+        name_map(0x07, &remap_names(&self.global_names, &map.globals));
+        name_map(0x08, &remap_names(&self.element_names, &map.elements));
+        name_map(0x09, &remap_names(&self.data_names, &map.data));
+        if !local_names.is_empty() {
+            let mut subsection = Vec::new();
+            local_names.len().encode(&mut subsection);
+            for (i, names) in local_names {
                 i.encode(&mut subsection);
-                name.encode(&mut subsection);
+                encode_name_map(names, &mut subsection);
             }
+            subsections.push((0x02, subsection));
+        }
+
+        // Subsections must appear in order of their id.
+        subsections.sort_by_key(|(code, _)| *code);
+        let mut section = Vec::new();
+        for (code, subsection) in subsections {
             section.push(code);
             subsection.encode(&mut section);
-        };
-        encode_subsection(0x01, &func_names);
-        encode_subsection(0x07, &global_names);
+        }
         if !section.is_empty() {
             ret.section(&wasm_encoder::CustomSection {
                 name: "name",
@@ -659,9 +936,22 @@ impl<'a> Module<'a> {
             });
         }
 
+        for (name, data) in self.custom_sections.iter().copied() {
+            ret.section(&wasm_encoder::CustomSection { name, data });
+        }
+
         Ok(ret.finish())
     }
 
+    /// Returns whether the element expression `expr` is a `ref.func` of a
+    /// live function.
+    fn is_live_func_ref(&self, expr: &ConstExpr<'_>) -> Result<bool> {
+        Ok(match expr.get_operators_reader().read()? {
+            Operator::RefFunc { function_index } => self.live_funcs.contains(function_index),
+            _ => false,
+        })
+    }
+
     fn find_stack_pointer(&self) -> Result<Option<u32>> {
         let mutable_i32_globals = self
             .live_globals()
@@ -742,8 +1032,8 @@ macro_rules! define_visit {
     (mark_live $self:ident $arg:ident tag_index) => {};
     (mark_live $self:ident $arg:ident targets) => {};
     (mark_live $self:ident $arg:ident ty) => {};
-    (mark_live $self:ident $arg:ident data_index) => {};
-    (mark_live $self:ident $arg:ident elem_index) => {};
+    (mark_live $self:ident $arg:ident data_index) => {$self.data($arg);};
+    (mark_live $self:ident $arg:ident elem_index) => {$self.element($arg);};
 }
 
 impl<'a> VisitOperator<'a> for Module<'a> {
@@ -768,6 +1058,37 @@ fn live_iter<'a, T>(
     })
 }
 
+/// Reads the names of a `namemap` of the `name` section into `names`, keyed
+/// by the index of the item named.
+fn read_name_map<'a>(map: &NameMap<'a>, names: &mut HashMap<u32, &'a str>) -> Result<()> {
+    let mut map = map.get_map()?;
+    for _ in 0..map.get_count() {
+        let naming = map.read()?;
+        names.insert(naming.index, naming.name);
+    }
+    Ok(())
+}
+
+/// Returns the names in `names` of the items which are still live according
+/// to `remap`, keyed by their new index and sorted by that index.
+fn remap_names<'a>(names: &HashMap<u32, &'a str>, remap: &Remap) -> Vec<(u32, &'a str)> {
+    let mut ret = names
+        .iter()
+        .filter_map(|(i, name)| Some((remap.get(*i)?, *name)))
+        .collect::<Vec<_>>();
+    ret.sort_by_key(|(i, _)| *i);
+    ret
+}
+
+/// Encodes a `namemap` of the `name` section.
+fn encode_name_map(names: &[(u32, &str)], dst: &mut Vec<u8>) {
+    names.len().encode(dst);
+    for (i, name) in names {
+        i.encode(dst);
+        name.encode(dst);
+    }
+}
+
 #[derive(Default)]
 struct Encoder {
     types: Remap,
@@ -775,6 +1096,8 @@ struct Encoder {
     memories: Remap,
     globals: Remap,
     tables: Remap,
+    elements: Remap,
+    data: Remap,
     buf: Vec<u8>,
 }
 
@@ -887,8 +1210,8 @@ macro_rules! define_encode {
     (map $self:ident $arg:ident local_index) => {$arg};
     (map $self:ident $arg:ident lane) => {$arg};
     (map $self:ident $arg:ident lanes) => {$arg};
-    (map $self:ident $arg:ident elem_index) => {$arg};
-    (map $self:ident $arg:ident data_index) => {$arg};
+    (map $self:ident $arg:ident elem_index) => {$self.elements.remap($arg)};
+    (map $self:ident $arg:ident data_index) => {$self.data.remap($arg)};
     (map $self:ident $arg:ident table_byte) => {$arg};
     (map $self:ident $arg:ident mem_byte) => {$arg};
     (map $self:ident $arg:ident value) => {$arg};
@@ -904,7 +1227,7 @@ impl<'a> VisitOperator<'a> for Encoder {
     wasmparser::for_each_operator!(define_encode);
 }
 
-fn valty(ty: wasmparser::ValType) -> wasm_encoder::ValType {
+pub(crate) fn valty(ty: wasmparser::ValType) -> wasm_encoder::ValType {
     match ty {
         wasmparser::ValType::I32 => wasm_encoder::ValType::I32,
         wasmparser::ValType::I64 => wasm_encoder::ValType::I64,
@@ -981,6 +1304,15 @@ struct Remap {
 }
 
 impl Remap {
+    /// Returns the new index corresponding to an old index, or `None` if the
+    /// old index was not added via `push` because it's no longer live.
+    fn get(&self, old: u32) -> Option<u32> {
+        self.map
+            .get(old as usize)
+            .copied()
+            .filter(|i| *i != u32::MAX)
+    }
+
     /// Appends a new live "old index" into this remapping structure.
     ///
     /// This will assign a new index for the old index provided. This method
@@ -3,22 +3,34 @@
 #![deny(missing_docs)]
 
 use anyhow::{bail, Result};
+use std::fmt;
 use std::str::FromStr;
 use wasm_encoder::CanonicalOption;
 
+mod analysis;
 #[cfg(feature = "cli")]
 pub mod cli;
 mod decoding;
+mod embed;
 mod encoding;
 mod extract;
 mod gc;
+mod package;
 mod printing;
+mod rename;
+mod stub;
 mod validation;
+mod world;
 
+pub use analysis::*;
 pub use decoding::{decode_component_interfaces, ComponentInterfaces};
+pub use embed::*;
 pub use encoding::*;
 pub use extract::*;
+pub use gc::gc_module;
+pub use package::*;
 pub use printing::*;
+pub use world::*;
 
 /// Supported string encoding formats.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -50,6 +62,16 @@ impl FromStr for StringEncoding {
     }
 }
 
+impl fmt::Display for StringEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringEncoding::UTF8 => f.write_str("utf8"),
+            StringEncoding::UTF16 => f.write_str("utf16"),
+            StringEncoding::CompactUTF16 => f.write_str("compact-utf16"),
+        }
+    }
+}
+
 impl From<StringEncoding> for wasm_encoder::CanonicalOption {
     fn from(e: StringEncoding) -> wasm_encoder::CanonicalOption {
         match e {
@@ -59,3 +81,84 @@ impl From<StringEncoding> for wasm_encoder::CanonicalOption {
         }
     }
 }
+
+/// Canonical ABI options used when lifting or lowering the functions of an
+/// interface, or of a single function.
+///
+/// Options are configured with [`ComponentEncoder::canonical_options`] or
+/// embedded in a core module with [`embed_module_options`]. Options which
+/// aren't specified for a function fall back to those of its interface and
+/// then to the defaults of the [`ComponentEncoder`].
+///
+/// The textual form of these options, used by the CLI and the embedded
+/// metadata, is a comma-separated list such as `encoding=utf16,realloc=alloc`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CanonicalOptions {
+    /// The string encoding used, overriding [`ComponentEncoder::encoding`].
+    pub encoding: Option<StringEncoding>,
+    /// The name of the function exported from the core module which is used as
+    /// the `realloc` option, instead of `cabi_realloc`.
+    pub realloc: Option<String>,
+}
+
+impl FromStr for CanonicalOptions {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut ret = CanonicalOptions::default();
+        for option in s.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            match option.split_once('=') {
+                Some(("encoding", encoding)) => ret.encoding = Some(encoding.parse()?),
+                Some(("realloc", realloc)) if !realloc.is_empty() => {
+                    ret.realloc = Some(realloc.to_string())
+                }
+                _ => bail!("unknown canonical option `{}`", option),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for CanonicalOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        if let Some(encoding) = &self.encoding {
+            write!(f, "encoding={encoding}")?;
+            sep = ",";
+        }
+        if let Some(realloc) = &self.realloc {
+            write!(f, "{sep}realloc={realloc}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Behavior of an imported interface which has been stubbed out with
+/// [`ComponentEncoder::stub_import`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ImportStub {
+    /// All functions of the interface trap when called.
+    Trap,
+    /// Functions of the interface which return a `result` return its `err`
+    /// case, with the error payload being the "zero" value of its type (e.g.
+    /// the first case of an `enum`). All other functions trap when called.
+    Error,
+}
+
+impl Default for ImportStub {
+    fn default() -> Self {
+        ImportStub::Trap
+    }
+}
+
+impl FromStr for ImportStub {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "trap" => Ok(ImportStub::Trap),
+            "error" => Ok(ImportStub::Error),
+            _ => bail!("unknown import stub `{}`", s),
+        }
+    }
+}
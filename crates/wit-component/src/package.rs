@@ -0,0 +1,227 @@
+use crate::embed::append_custom_section;
+use crate::{decode_component_interfaces, ComponentEncoder, ComponentInterfaces};
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::path::Path;
+use wasm_encoder::Encode;
+use wasmparser::{BinaryReader, Parser, Payload};
+use wit_parser::{Docs, Interface, TypeDefKind};
+
+/// The name of the custom section which marks a component as a WIT package and
+/// contains the documentation of its interfaces.
+const PACKAGE_SECTION: &str = "wit-package";
+
+/// The version of the contents of the `wit-package` custom section.
+const PACKAGE_VERSION: u32 = 1;
+
+/// A WIT package, a set of named interfaces in a binary format suitable for
+/// distributing precompiled interface definitions.
+///
+/// A package is encoded as a "types only" component where each interface is
+/// an exported instance type, the same representation produced by
+/// [`ComponentEncoder::types_only`]. Documentation, which component types
+/// can't represent, is stored alongside in a `wit-package` custom section,
+/// which also marks the component as a package.
+///
+/// Wherever a `*.wit` file is accepted by `wit-bindgen` and its procedural
+/// macros a package can be used instead, see [`read_interface`].
+#[derive(Debug, Clone, Default)]
+pub struct WitPackage {
+    /// The interfaces of this package, keyed by name.
+    pub interfaces: IndexMap<String, Interface>,
+}
+
+impl WitPackage {
+    /// Creates a new package from the named `interfaces`.
+    pub fn new(interfaces: impl IntoIterator<Item = Interface>) -> Result<Self> {
+        let mut ret = WitPackage::default();
+        for interface in interfaces {
+            if interface.name.is_empty() {
+                bail!("interfaces of a WIT package must be named");
+            }
+            if ret.interfaces.contains_key(&interface.name) {
+                bail!(
+                    "WIT package contains interface `{}` more than once",
+                    interface.name
+                );
+            }
+            ret.interfaces.insert(interface.name.clone(), interface);
+        }
+        Ok(ret)
+    }
+
+    /// Encodes this package in its binary format.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let component = ComponentEncoder::default()
+            .types_only(true)
+            .exports(self.interfaces.values().cloned())?
+            .validate(true)
+            .encode()?;
+
+        let mut section = Vec::new();
+        PACKAGE_VERSION.encode(&mut section);
+        self.interfaces.len().encode(&mut section);
+        for (name, interface) in self.interfaces.iter() {
+            let mut interface = interface.clone();
+            let mut docs = Vec::new();
+            visit_docs(&mut interface, &mut |path, d| {
+                if let Some(contents) = &d.contents {
+                    docs.push((path, contents.clone()));
+                }
+            });
+            name.as_str().encode(&mut section);
+            docs.len().encode(&mut section);
+            for (path, contents) in docs {
+                path.as_str().encode(&mut section);
+                contents.as_str().encode(&mut section);
+            }
+        }
+
+        Ok(append_custom_section(&component, PACKAGE_SECTION, &section))
+    }
+
+    /// Decodes a package from its binary format.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let section = match package_section(bytes)? {
+            Some(section) => section,
+            None => bail!("component is not a WIT package"),
+        };
+        let ComponentInterfaces {
+            imports,
+            exports,
+            default,
+        } = decode_component_interfaces(bytes).context("failed to decode WIT package")?;
+        if !imports.is_empty() || default.is_some() {
+            bail!("WIT package must only export interfaces");
+        }
+        let mut ret = WitPackage {
+            interfaces: exports,
+        };
+
+        let mut reader = BinaryReader::new(section);
+        let version = reader.read_var_u32()?;
+        if version != PACKAGE_VERSION {
+            bail!("unsupported WIT package version {version}");
+        }
+        for _ in 0..reader.read_var_u32()? {
+            let name = reader.read_string()?;
+            let mut docs = HashMap::new();
+            for _ in 0..reader.read_var_u32()? {
+                let path = reader.read_string()?;
+                docs.insert(path.to_string(), reader.read_string()?.to_string());
+            }
+            let interface = match ret.interfaces.get_mut(name) {
+                Some(interface) => interface,
+                None => bail!("WIT package has documentation for unknown interface `{name}`"),
+            };
+            visit_docs(interface, &mut |path, d| {
+                d.contents = docs.remove(&path);
+            });
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns the interface of this package named `name`.
+    ///
+    /// If `name` isn't specified then the package must contain a single
+    /// interface, which is returned.
+    pub fn interface(&self, name: Option<&str>) -> Result<&Interface> {
+        match name {
+            Some(name) => match self.interfaces.get(name) {
+                Some(interface) => Ok(interface),
+                None => bail!("WIT package does not contain an interface named `{name}`"),
+            },
+            None if self.interfaces.len() == 1 => Ok(self.interfaces.values().next().unwrap()),
+            None => bail!("WIT package contains multiple interfaces and one must be named"),
+        }
+    }
+}
+
+/// Returns whether `bytes` is a binary WIT package.
+pub fn is_wit_package(bytes: &[u8]) -> bool {
+    matches!(package_section(bytes), Ok(Some(_)))
+}
+
+/// Reads an interface from the file at `path`.
+///
+/// The file is either a `*.wit` file or a binary [`WitPackage`]. For packages
+/// the interface is selected by `name`, as with [`WitPackage::interface`],
+/// and otherwise the returned interface is named `name` if specified.
+pub fn read_interface(path: impl AsRef<Path>, name: Option<&str>) -> Result<Interface> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read: {}", path.display()))?;
+    let mut interface = if bytes.starts_with(b"\0asm") {
+        let package = WitPackage::decode(&bytes)
+            .with_context(|| format!("failed to decode WIT package `{}`", path.display()))?;
+        package.interface(name)?.clone()
+    } else {
+        Interface::parse_file(path)?
+    };
+    if let Some(name) = name {
+        interface.name = name.to_string();
+    }
+    Ok(interface)
+}
+
+/// Returns the contents of the `wit-package` custom section of the component
+/// `bytes`, if present.
+fn package_section(bytes: &[u8]) -> Result<Option<&[u8]>> {
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::Version { .. } => depth += 1,
+            Payload::End(_) => depth -= 1,
+            Payload::CustomSection(s) if depth == 1 && s.name() == PACKAGE_SECTION => {
+                return Ok(Some(s.data()));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Calls `f` with a path identifying each item which can be documented within
+/// `interface`, and that item's documentation.
+fn visit_docs(interface: &mut Interface, f: &mut dyn FnMut(String, &mut Docs)) {
+    for func in interface.functions.iter_mut() {
+        f(format!("func:{}", func.name), &mut func.docs);
+    }
+    for (_, ty) in interface.types.iter_mut() {
+        let name = match &ty.name {
+            Some(name) => format!("type:{name}"),
+            None => continue,
+        };
+        match &mut ty.kind {
+            TypeDefKind::Record(r) => {
+                for field in r.fields.iter_mut() {
+                    f(format!("{name}/{}", field.name), &mut field.docs);
+                }
+            }
+            TypeDefKind::Flags(flags) => {
+                for flag in flags.flags.iter_mut() {
+                    f(format!("{name}/{}", flag.name), &mut flag.docs);
+                }
+            }
+            TypeDefKind::Variant(v) => {
+                for case in v.cases.iter_mut() {
+                    f(format!("{name}/{}", case.name), &mut case.docs);
+                }
+            }
+            TypeDefKind::Enum(e) => {
+                for case in e.cases.iter_mut() {
+                    f(format!("{name}/{}", case.name), &mut case.docs);
+                }
+            }
+            TypeDefKind::Union(u) => {
+                for (i, case) in u.cases.iter_mut().enumerate() {
+                    f(format!("{name}/{i}"), &mut case.docs);
+                }
+            }
+            _ => {}
+        }
+        f(name, &mut ty.docs);
+    }
+}
@@ -2,8 +2,8 @@ use anyhow::{bail, Result};
 use indexmap::IndexSet;
 use std::fmt::Write;
 use wit_parser::{
-    Enum, Flags, Interface, Record, Result_, Results, Tuple, Type, TypeDefKind, TypeId, Union,
-    Variant,
+    Enum, Flags, Function, Interface, Record, Result_, Results, Tuple, Type, TypeDefKind, TypeId,
+    Union, Variant,
 };
 
 /// A utility for printing WebAssembly interface definitions to a string.
@@ -21,46 +21,62 @@ impl InterfacePrinter {
         }
 
         for func in &interface.functions {
-            write!(&mut self.output, "{}: func(", func.name)?;
-            for (i, (name, ty)) in func.params.iter().enumerate() {
-                if i > 0 {
-                    self.output.push_str(", ");
-                }
-                write!(&mut self.output, "{}: ", name)?;
-                self.print_type_name(interface, ty)?;
+            self.print_func(interface, func)?;
+            self.output.push_str("\n\n");
+        }
+
+        self.declared.clear();
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    /// Print the signature of a single function of `interface` to a string,
+    /// for example `foo: func(x: string) -> u32`.
+    pub(crate) fn print_func_signature(
+        &mut self,
+        interface: &Interface,
+        func: &Function,
+    ) -> Result<String> {
+        self.print_func(interface, func)?;
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    fn print_func(&mut self, interface: &Interface, func: &Function) -> Result<()> {
+        write!(&mut self.output, "{}: func(", func.name)?;
+        for (i, (name, ty)) in func.params.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
             }
-            self.output.push(')');
-
-            match &func.results {
-                Results::Named(rs) => match rs.len() {
-                    0 => (),
-                    1 => {
-                        self.output.push_str(" -> ");
-                        self.print_type_name(interface, &rs[0].1)?;
-                    }
-                    _ => {
-                        self.output.push_str(" -> (");
-                        for (i, (name, ty)) in rs.iter().enumerate() {
-                            if i > 0 {
-                                self.output.push_str(", ");
-                            }
-                            write!(&mut self.output, "{name}: ")?;
-                            self.print_type_name(interface, ty)?;
+            write!(&mut self.output, "{}: ", name)?;
+            self.print_type_name(interface, ty)?;
+        }
+        self.output.push(')');
+
+        match &func.results {
+            Results::Named(rs) => match rs.len() {
+                0 => (),
+                1 => {
+                    self.output.push_str(" -> ");
+                    self.print_type_name(interface, &rs[0].1)?;
+                }
+                _ => {
+                    self.output.push_str(" -> (");
+                    for (i, (name, ty)) in rs.iter().enumerate() {
+                        if i > 0 {
+                            self.output.push_str(", ");
                         }
-                        self.output.push(')');
+                        write!(&mut self.output, "{name}: ")?;
+                        self.print_type_name(interface, ty)?;
                     }
-                },
-                Results::Anon(ty) => {
-                    self.output.push_str(" -> ");
-                    self.print_type_name(interface, ty)?;
+                    self.output.push(')');
                 }
+            },
+            Results::Anon(ty) => {
+                self.output.push_str(" -> ");
+                self.print_type_name(interface, ty)?;
             }
-
-            self.output.push_str("\n\n");
         }
 
-        self.declared.clear();
-        Ok(std::mem::take(&mut self.output))
+        Ok(())
     }
 
     fn print_type_name(&mut self, interface: &Interface, ty: &Type) -> Result<()> {
@@ -0,0 +1,223 @@
+use crate::gc::valty;
+use anyhow::{bail, Result};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use wasm_encoder::{EntityType, ExportKind, Module, RawSection};
+use wasmparser::{BinaryReader, ExportSectionReader, ExternalKind, ImportSectionReader, TypeRef};
+
+/// Rules which rename the imports and exports of a core module before it's
+/// encoded as a component.
+///
+/// Each rule renames names matching `from` to `to`, where a `*` in `from`
+/// matches any sequence of characters. The text matched by each `*` is
+/// substituted, in order, for each `*` in `to`. The first rule matching a
+/// name is used and every rule must match at least one name.
+///
+/// Import rules match the module name of an import, for example `env=foo`,
+/// unless they contain `::` in which case they match and rename both the
+/// module name and the field name, for example `env::foo_*=foo::*`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Renames {
+    pub(crate) imports: Vec<(String, String)>,
+    pub(crate) exports: Vec<(String, String)>,
+}
+
+impl Renames {
+    /// Applies these rules to the core module `wasm`, returning the renamed
+    /// module.
+    ///
+    /// Only the import and export sections of the module are rewritten, all
+    /// other sections, such as DWARF debug information, are kept as-is. It's
+    /// an error for the rules to rename two imports, or two exports, to the
+    /// same name.
+    pub(crate) fn apply<'a>(&self, wasm: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        if self.imports.is_empty() && self.exports.is_empty() {
+            return Ok(Cow::Borrowed(wasm));
+        }
+        for (from, to) in self.imports.iter() {
+            if from.contains("::") != to.contains("::") {
+                bail!(
+                    "import rename rule `{from}={to}` must rename either both the module \
+                     and field name with `::` or neither"
+                );
+            }
+        }
+        for (from, to) in self.imports.iter().chain(self.exports.iter()) {
+            let halves = match (from.split_once("::"), to.split_once("::")) {
+                (Some((a, b)), Some((c, d))) => vec![(a, c), (b, d)],
+                _ => vec![(from.as_str(), to.as_str())],
+            };
+            for (from_half, to_half) in halves {
+                let wildcards = to_half.matches('*').count();
+                if wildcards != 0 && wildcards != from_half.matches('*').count() {
+                    bail!(
+                        "rename rule `{from}={to}` must use the same number of wildcards on \
+                         both sides or none in the replacement"
+                    );
+                }
+            }
+        }
+
+        let mut import_used = vec![false; self.imports.len()];
+        let mut export_used = vec![false; self.exports.len()];
+        let mut ret = Module::new();
+        let mut reader = BinaryReader::new(wasm);
+        reader.read_bytes(8)?;
+        while !reader.eof() {
+            let id = reader.read_u8()?;
+            let len = reader.read_var_u32()?;
+            let offset = reader.original_position();
+            let data = reader.read_bytes(len as usize)?;
+            match id {
+                2 => {
+                    let mut imports = wasm_encoder::ImportSection::new();
+                    let mut names = HashSet::new();
+                    for import in ImportSectionReader::new(data, offset)? {
+                        let import = import?;
+                        let (module, name) =
+                            self.rename_import(import.module, import.name, &mut import_used);
+                        imports.import(&module, &name, entity_type(import.ty));
+                        if !names.insert((module.clone(), name.clone())) {
+                            bail!(
+                                "renaming import `{}::{}` results in a duplicate import \
+                                 `{module}::{name}`",
+                                import.module,
+                                import.name,
+                            );
+                        }
+                    }
+                    ret.section(&imports);
+                }
+                7 => {
+                    let mut exports = wasm_encoder::ExportSection::new();
+                    let mut names = HashSet::new();
+                    for export in ExportSectionReader::new(data, offset)? {
+                        let export = export?;
+                        let name = rename(&self.exports, export.name, &mut export_used)
+                            .unwrap_or_else(|| export.name.to_string());
+                        exports.export(&name, export_kind(export.kind), export.index);
+                        if !names.insert(name.clone()) {
+                            bail!(
+                                "renaming export `{}` results in a duplicate export `{name}`",
+                                export.name,
+                            );
+                        }
+                    }
+                    ret.section(&exports);
+                }
+                _ => {
+                    ret.section(&RawSection { id, data });
+                }
+            }
+        }
+
+        for (used, (from, _)) in import_used.iter().zip(&self.imports) {
+            if !used {
+                bail!("import rename rule `{from}` does not match any import");
+            }
+        }
+        for (used, (from, _)) in export_used.iter().zip(&self.exports) {
+            if !used {
+                bail!("export rename rule `{from}` does not match any export");
+            }
+        }
+
+        Ok(Cow::Owned(ret.finish()))
+    }
+
+    fn rename_import(&self, module: &str, name: &str, used: &mut [bool]) -> (String, String) {
+        for (i, (from, to)) in self.imports.iter().enumerate() {
+            let renamed = match (from.split_once("::"), to.split_once("::")) {
+                (Some((from_module, from_name)), Some((to_module, to_name))) => {
+                    match (glob(from_module, module), glob(from_name, name)) {
+                        (Some(m), Some(n)) => (substitute(to_module, &m), substitute(to_name, &n)),
+                        _ => continue,
+                    }
+                }
+                _ => match glob(from, module) {
+                    Some(m) => (substitute(to, &m), name.to_string()),
+                    None => continue,
+                },
+            };
+            used[i] = true;
+            return renamed;
+        }
+        (module.to_string(), name.to_string())
+    }
+}
+
+/// Renames `name` with the first rule of `rules` which matches it, if any.
+fn rename(rules: &[(String, String)], name: &str, used: &mut [bool]) -> Option<String> {
+    for (i, (from, to)) in rules.iter().enumerate() {
+        if let Some(captures) = glob(from, name) {
+            used[i] = true;
+            return Some(substitute(to, &captures));
+        }
+    }
+    None
+}
+
+/// Matches `s` against `pattern`, where `*` matches any sequence of
+/// characters, returning the text matched by each `*`.
+fn glob<'a>(pattern: &str, s: &'a str) -> Option<Vec<&'a str>> {
+    let parts = pattern.split('*').collect::<Vec<_>>();
+    if parts.len() == 1 {
+        return (pattern == s).then(Vec::new);
+    }
+    let mut rest = s.strip_prefix(parts[0])?;
+    let mut captures = Vec::new();
+    for part in &parts[1..parts.len() - 1] {
+        let i = rest.find(part)?;
+        captures.push(&rest[..i]);
+        rest = &rest[i + part.len()..];
+    }
+    captures.push(rest.strip_suffix(parts[parts.len() - 1])?);
+    Some(captures)
+}
+
+/// Replaces each `*` within `to` with the corresponding capture of `glob`.
+fn substitute(to: &str, captures: &[&str]) -> String {
+    let mut ret = String::new();
+    for (i, part) in to.split('*').enumerate() {
+        if i > 0 {
+            ret.push_str(captures[i - 1]);
+        }
+        ret.push_str(part);
+    }
+    ret
+}
+
+fn entity_type(ty: TypeRef) -> EntityType {
+    match ty {
+        TypeRef::Func(ty) => EntityType::Function(ty),
+        TypeRef::Table(ty) => EntityType::Table(wasm_encoder::TableType {
+            minimum: ty.initial,
+            maximum: ty.maximum,
+            element_type: valty(ty.element_type),
+        }),
+        TypeRef::Memory(ty) => EntityType::Memory(wasm_encoder::MemoryType {
+            minimum: ty.initial,
+            maximum: ty.maximum,
+            shared: ty.shared,
+            memory64: ty.memory64,
+        }),
+        TypeRef::Global(ty) => EntityType::Global(wasm_encoder::GlobalType {
+            mutable: ty.mutable,
+            val_type: valty(ty.content_type),
+        }),
+        TypeRef::Tag(ty) => EntityType::Tag(wasm_encoder::TagType {
+            kind: wasm_encoder::TagKind::Exception,
+            func_type_idx: ty.func_type_idx,
+        }),
+    }
+}
+
+fn export_kind(kind: ExternalKind) -> ExportKind {
+    match kind {
+        ExternalKind::Func => ExportKind::Func,
+        ExternalKind::Table => ExportKind::Table,
+        ExternalKind::Memory => ExportKind::Memory,
+        ExternalKind::Global => ExportKind::Global,
+        ExternalKind::Tag => ExportKind::Tag,
+    }
+}
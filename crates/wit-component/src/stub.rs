@@ -0,0 +1,143 @@
+//! Generation of core wasm modules which stand in for an imported interface.
+//!
+//! A stubbed import is not imported by the final component at all. Instead a
+//! core wasm module is synthesized here which exports a function for each
+//! function in the interface, with the same core signature the main module
+//! imports it with. This module is then treated exactly like an adapter module
+//! during encoding, meaning that it's gc'd down to the functions the main
+//! module actually uses and instantiated with the main module's memory if
+//! necessary.
+
+use crate::ImportStub;
+use anyhow::Result;
+use indexmap::IndexMap;
+use wasm_encoder::*;
+use wit_parser::{
+    abi::{AbiVariant, WasmType},
+    Function, Interface, Results, SizeAlign, Type, TypeDefKind,
+};
+
+/// The module and field name that a stub module imports its memory from.
+///
+/// This is only imported if a stub needs to write a `result` through a return
+/// pointer.
+const MEMORY_MODULE: &str = "env";
+const MEMORY_NAME: &str = "memory";
+
+/// Creates a core wasm module which implements all functions of `interface`
+/// according to the `stub` behavior specified.
+pub fn run(interface: &Interface, stub: ImportStub) -> Result<Vec<u8>> {
+    let mut sizes = SizeAlign::default();
+    sizes.fill(interface);
+
+    let mut types = TypeSection::new();
+    let mut imports = ImportSection::new();
+    let mut funcs = FunctionSection::new();
+    let mut exports = ExportSection::new();
+    let mut code = CodeSection::new();
+    let mut sigs = IndexMap::new();
+    let mut needs_memory = false;
+
+    for (i, func) in interface.functions.iter().enumerate() {
+        let sig = interface.wasm_signature(AbiVariant::GuestImport, func);
+        let type_index = *sigs.entry(sig.clone()).or_insert_with(|| {
+            let index = types.len();
+            types.function(
+                sig.params.iter().map(to_val_type),
+                sig.results.iter().map(to_val_type),
+            );
+            index
+        });
+        funcs.function(type_index);
+        exports.export(&func.name, ExportKind::Func, i as u32);
+
+        let mut body = wasm_encoder::Function::new(std::iter::empty());
+        match (stub, result_type(interface, func)) {
+            // Functions which return a `result` are stubbed to return the
+            // `err` case with an all-zero payload. All-zeros is a valid
+            // representation of every type in the canonical ABI so this is
+            // always a valid value to return.
+            (ImportStub::Error, Some(ty)) => {
+                if sig.retptr {
+                    needs_memory = true;
+                    let retptr = sig.params.len() as u32 - 1;
+                    body.instruction(&Instruction::LocalGet(retptr));
+                    body.instruction(&Instruction::I32Const(0));
+                    body.instruction(&Instruction::I32Const(sizes.size(&ty) as i32));
+                    body.instruction(&Instruction::MemoryFill(0));
+                    body.instruction(&Instruction::LocalGet(retptr));
+                    body.instruction(&Instruction::I32Const(1));
+                    body.instruction(&Instruction::I32Store8(MemArg {
+                        offset: 0,
+                        align: 0,
+                        memory_index: 0,
+                    }));
+                } else {
+                    // Without a return pointer the `result` has no payloads
+                    // and is returned as a single discriminant.
+                    assert_eq!(sig.results, [WasmType::I32]);
+                    body.instruction(&Instruction::I32Const(1));
+                }
+            }
+
+            // Everything else, and everything in "trap" mode, immediately
+            // traps when called.
+            (ImportStub::Trap, _) | (ImportStub::Error, None) => {
+                body.instruction(&Instruction::Unreachable);
+            }
+        }
+        body.instruction(&Instruction::End);
+        code.function(&body);
+    }
+
+    if needs_memory {
+        imports.import(
+            MEMORY_MODULE,
+            MEMORY_NAME,
+            MemoryType {
+                minimum: 0,
+                maximum: None,
+                memory64: false,
+                shared: false,
+            },
+        );
+    }
+
+    let mut module = Module::new();
+    module.section(&types);
+    if !imports.is_empty() {
+        module.section(&imports);
+    }
+    module.section(&funcs);
+    module.section(&exports);
+    module.section(&code);
+    Ok(module.finish())
+}
+
+/// Returns the `result` type returned by `func`, if any, looking through type
+/// aliases.
+fn result_type(interface: &Interface, func: &Function) -> Option<Type> {
+    let mut ty = match &func.results {
+        Results::Anon(ty) => *ty,
+        Results::Named(_) => return None,
+    };
+    loop {
+        match ty {
+            Type::Id(id) => match &interface.types[id].kind {
+                TypeDefKind::Type(t) => ty = *t,
+                TypeDefKind::Result(_) => return Some(ty),
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+fn to_val_type(ty: &WasmType) -> ValType {
+    match ty {
+        WasmType::I32 => ValType::I32,
+        WasmType::I64 => ValType::I64,
+        WasmType::F32 => ValType::F32,
+        WasmType::F64 => ValType::F64,
+    }
+}
@@ -1,12 +1,14 @@
+use crate::InterfacePrinter;
 use anyhow::{anyhow, bail, Result};
 use indexmap::{map::Entry, IndexMap, IndexSet};
+use std::fmt::Write;
 use wasmparser::{
-    types::Types, Encoding, ExternalKind, FuncType, Parser, Payload, TypeRef, ValType,
+    types::Types, Encoding, ExternalKind, FuncType, GlobalType, Parser, Payload, TypeRef, ValType,
     ValidPayload, Validator,
 };
 use wit_parser::{
     abi::{AbiVariant, WasmSignature, WasmType},
-    Interface,
+    Function, Interface,
 };
 
 fn is_canonical_function(name: &str) -> bool {
@@ -56,20 +58,34 @@ pub struct ValidatedModule<'a> {
     /// export (and subsequently import) extraneous functions.
     pub adapters_required: IndexMap<&'a str, IndexMap<&'a str, FuncType>>,
 
+    /// The set of libraries which are imported from by this module.
+    ///
+    /// These are instantiated, along with the libraries they themselves
+    /// import from, before this module.
+    pub libraries_required: IndexSet<&'a str>,
+
     /// Whether or not this module exported a linear memory.
     pub has_memory: bool,
 
     /// Whether or not this module exported a `cabi_realloc` function.
     pub has_realloc: bool,
+
+    /// The exported function which initializes this module, called once all
+    /// instances of the component have been created.
+    pub initializer: Option<&'a str>,
 }
 
 /// This function validates the following:
 ///
 /// * The `bytes` represent a valid core WebAssembly module.
-/// * The module's imports are all satisfied by the given `imports` interfaces
-///   or the `adapters` set.
+/// * The module's imports are all satisfied by the given `imports` interfaces,
+///   the `adapters` set, or the exports of `libraries`.
 /// * The given default and exported interfaces are satisfied by the module's
 ///   exports.
+/// * The module exports all of the custom `reallocs` with the correct type.
+/// * The module exports the `initializer` function, if specified, with the
+///   correct type. Otherwise an `_initialize` export, as found in WASI
+///   reactors, is used as the initializer.
 ///
 /// The `ValidatedModule` return value contains the metadata which describes the
 /// input module on success. This is then further used to generate a component
@@ -80,11 +96,16 @@ pub fn validate_module<'a>(
     imports: &IndexMap<String, Interface>,
     exports: &IndexMap<String, Interface>,
     adapters: &IndexSet<&str>,
+    libraries: &IndexMap<&str, ValidatedLibrary<'_>>,
+    reallocs: &IndexSet<&str>,
+    initializer: Option<&str>,
 ) -> Result<ValidatedModule<'a>> {
     let mut validator = Validator::new();
     let mut types = None;
     let mut import_funcs = IndexMap::new();
     let mut export_funcs = IndexMap::new();
+    let mut realloc_funcs = IndexMap::new();
+    let mut library_imports = Vec::new();
     let mut ret = ValidatedModule::default();
 
     for payload in Parser::new(0).parse_all(bytes) {
@@ -101,6 +122,11 @@ pub fn validate_module<'a>(
             Payload::ImportSection(s) => {
                 for import in s {
                     let import = import?;
+                    if libraries.contains_key(import.module) {
+                        library_imports.push((import.module, import.name, import.ty));
+                        ret.libraries_required.insert(import.module);
+                        continue;
+                    }
                     match import.ty {
                         TypeRef::Func(ty) => {
                             let map = match import_funcs.entry(import.module) {
@@ -120,6 +146,9 @@ pub fn validate_module<'a>(
 
                     match export.kind {
                         ExternalKind::Func => {
+                            if reallocs.contains(export.name) {
+                                realloc_funcs.insert(export.name, export.index);
+                            }
                             if is_canonical_function(export.name) {
                                 if export.name == "cabi_realloc" {
                                     // TODO: validate that the cabi_realloc function is [i32, i32, i32, i32] -> [i32]
@@ -145,6 +174,11 @@ pub fn validate_module<'a>(
 
     let types = types.unwrap();
 
+    for (module, name, ty) in library_imports {
+        let item = LibraryItem::new(&types, ty);
+        libraries[module].validate_import(module, name, &item)?;
+    }
+
     for (name, funcs) in &import_funcs {
         if name.is_empty() {
             bail!("module imports from an empty module name");
@@ -172,6 +206,41 @@ pub fn validate_module<'a>(
         validate_exported_interface(interface, true, &export_funcs, &types)?;
     }
 
+    for name in reallocs {
+        let index = match realloc_funcs.get(name) {
+            Some(index) => *index,
+            None => bail!("module does not export a function named `{name}` to use as `realloc`"),
+        };
+        let ty = types.function_at(index).unwrap();
+        if ty.params() != [ValType::I32; 4] || ty.results() != [ValType::I32] {
+            bail!(
+                "function `{name}` used as `realloc` must have type \
+                 `(i32, i32, i32, i32) -> (i32)` but found `{}`",
+                func_type_to_string(ty)
+            );
+        }
+    }
+
+    let initializer = match initializer {
+        Some(name) => match export_funcs.get_key_value(name) {
+            Some(initializer) => Some(initializer),
+            None => {
+                bail!("module does not export a function named `{name}` to use as an initializer")
+            }
+        },
+        None => export_funcs.get_key_value("_initialize"),
+    };
+    if let Some((name, index)) = initializer {
+        let ty = types.function_at(*index).unwrap();
+        if !ty.params().is_empty() || !ty.results().is_empty() {
+            bail!(
+                "function `{name}` used as an initializer must have type `() -> ()` but found `{}`",
+                func_type_to_string(ty)
+            );
+        }
+        ret.initializer = Some(*name);
+    }
+
     for (name, interface) in exports {
         if name.is_empty() {
             bail!("cannot export an interface with an empty name");
@@ -183,6 +252,170 @@ pub fn validate_module<'a>(
     Ok(ret)
 }
 
+/// Validation information from a "library", a core module which is linked with
+/// the main module by being instantiated first and satisfying some of the main
+/// module's imports with its exports.
+///
+/// This is created by the `validate_library` function.
+#[derive(Default, Debug)]
+pub struct ValidatedLibrary<'a> {
+    /// The module name, field name, and type of each import of this library.
+    ///
+    /// Imports must all be satisfied by other libraries.
+    pub imports: Vec<(&'a str, &'a str, LibraryItem)>,
+
+    /// The type of each item exported from this library.
+    pub exports: IndexMap<&'a str, LibraryItem>,
+}
+
+impl ValidatedLibrary<'_> {
+    /// Validates that this library, named `library`, exports an item `name`
+    /// matching `item`.
+    pub fn validate_import(&self, library: &str, name: &str, item: &LibraryItem) -> Result<()> {
+        match self.exports.get(name) {
+            Some(export) if export == item => Ok(()),
+            Some(export) if export.kind() == item.kind() => bail!(
+                "library `{library}` export `{name}` has type `{}` but `{}` is expected",
+                export.describe(),
+                item.describe(),
+            ),
+            _ => bail!(
+                "library `{library}` does not export a {} named `{name}`",
+                kind_name(item.kind())
+            ),
+        }
+    }
+}
+
+/// The type of an item imported or exported by a library.
+///
+/// Only the types of functions and globals are compared when linking
+/// libraries. The limits of tables and memories are instead checked when the
+/// final component is validated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LibraryItem {
+    /// A function with the signature specified.
+    Func(FuncType),
+    /// A table of any type.
+    Table,
+    /// A memory of any type.
+    Memory,
+    /// A global of the type specified.
+    Global(GlobalType),
+    /// A tag, which libraries can only export.
+    Tag,
+}
+
+impl LibraryItem {
+    fn new(types: &Types, ty: TypeRef) -> LibraryItem {
+        match ty {
+            TypeRef::Func(ty) => LibraryItem::Func(types.func_type_at(ty).unwrap().clone()),
+            TypeRef::Table(_) => LibraryItem::Table,
+            TypeRef::Memory(_) => LibraryItem::Memory,
+            TypeRef::Global(ty) => LibraryItem::Global(ty),
+            TypeRef::Tag(_) => LibraryItem::Tag,
+        }
+    }
+
+    fn kind(&self) -> ExternalKind {
+        match self {
+            LibraryItem::Func(_) => ExternalKind::Func,
+            LibraryItem::Table => ExternalKind::Table,
+            LibraryItem::Memory => ExternalKind::Memory,
+            LibraryItem::Global(_) => ExternalKind::Global,
+            LibraryItem::Tag => ExternalKind::Tag,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            LibraryItem::Func(ty) => func_type_to_string(ty),
+            LibraryItem::Global(ty) => format!(
+                "{}{}",
+                if ty.mutable { "mut " } else { "" },
+                val_type_to_str(&ty.content_type)
+            ),
+            item => kind_name(item.kind()).to_string(),
+        }
+    }
+}
+
+/// This function validates that `bytes` is a core wasm module which can be
+/// used as a library, returning its imports and exports.
+pub fn validate_library(bytes: &[u8]) -> Result<ValidatedLibrary<'_>> {
+    let mut validator = Validator::new();
+    let mut types = None;
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    let mut globals = Vec::new();
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        let payload = payload?;
+        if let ValidPayload::End(tys) = validator.payload(&payload)? {
+            types = Some(tys);
+            break;
+        }
+
+        match payload {
+            Payload::Version { encoding, .. } if encoding != Encoding::Module => {
+                bail!("data is not a WebAssembly module");
+            }
+            Payload::ImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    match import.ty {
+                        TypeRef::Tag(_) => bail!("library is not allowed to import tags"),
+                        TypeRef::Global(ty) => globals.push(ty),
+                        _ => {}
+                    }
+                    imports.push((import.module, import.name, import.ty));
+                }
+            }
+            Payload::GlobalSection(s) => {
+                for global in s {
+                    globals.push(global?.ty);
+                }
+            }
+            Payload::ExportSection(s) => {
+                for export in s {
+                    let export = export?;
+                    exports.push((export.name, export.kind, export.index));
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    let types = types.unwrap();
+    let mut ret = ValidatedLibrary::default();
+    for (module, name, ty) in imports {
+        ret.imports
+            .push((module, name, LibraryItem::new(&types, ty)));
+    }
+    for (name, kind, index) in exports {
+        let item = match kind {
+            ExternalKind::Func => LibraryItem::Func(types.function_at(index).unwrap().clone()),
+            ExternalKind::Table => LibraryItem::Table,
+            ExternalKind::Memory => LibraryItem::Memory,
+            ExternalKind::Global => LibraryItem::Global(globals[index as usize]),
+            ExternalKind::Tag => LibraryItem::Tag,
+        };
+        ret.exports.insert(name, item);
+    }
+
+    Ok(ret)
+}
+
+fn kind_name(kind: ExternalKind) -> &'static str {
+    match kind {
+        ExternalKind::Func => "function",
+        ExternalKind::Table => "table",
+        ExternalKind::Memory => "memory",
+        ExternalKind::Global => "global",
+        ExternalKind::Tag => "tag",
+    }
+}
+
 /// Validation information from an "adapter module" which is distinct from a
 /// "main module" validated above.
 ///
@@ -369,18 +602,20 @@ fn validate_imported_interface<'a>(
                 )
             })?;
 
-        let expected = wasm_sig_to_func_type(interface.wasm_signature(AbiVariant::GuestImport, f));
+        let expected = interface.wasm_signature(AbiVariant::GuestImport, f);
         let ty = types.func_type_at(*ty).unwrap();
-        if ty != &expected {
-            bail!(
-                "type mismatch for function `{}` on imported interface `{}`: expected `{:?} -> {:?}` but found `{:?} -> {:?}`",
-                f.name,
-                name,
-                expected.params(),
-                expected.results(),
-                ty.params(),
-                ty.results()
-            );
+        if ty != &wasm_sig_to_func_type(expected.clone()) {
+            return Err(signature_mismatch(
+                format!(
+                    "type mismatch for function `{}` on imported interface `{}`",
+                    f.name, name
+                ),
+                interface,
+                f,
+                &expected,
+                ty,
+                AbiVariant::GuestImport,
+            ));
         }
 
         funcs.insert(f.name.as_str());
@@ -399,30 +634,25 @@ fn validate_exported_interface(
         let expected_export_name = interface.core_export_name(default_export, f);
         match exports.get(expected_export_name.as_ref()) {
             Some(func_index) => {
-                let expected_ty =
-                    wasm_sig_to_func_type(interface.wasm_signature(AbiVariant::GuestExport, f));
+                let expected = interface.wasm_signature(AbiVariant::GuestExport, f);
                 let ty = types.function_at(*func_index).unwrap();
-                if ty != &expected_ty {
-                    if default_export {
-                        bail!(
-                            "type mismatch for default interface function `{}`: expected `{:?} -> {:?}` but found `{:?} -> {:?}`",
-                            f.name,
-                            expected_ty.params(),
-                            expected_ty.results(),
-                            ty.params(),
-                            ty.results()
-                        );
+                if ty != &wasm_sig_to_func_type(expected.clone()) {
+                    let description = if default_export {
+                        format!("type mismatch for default interface function `{}`", f.name)
                     } else {
-                        bail!(
-                            "type mismatch for function `{}` from exported interface `{}`: expected `{:?} -> {:?}` but found `{:?} -> {:?}`",
-                            f.name,
-                            interface.name,
-                            expected_ty.params(),
-                            expected_ty.results(),
-                            ty.params(),
-                            ty.results()
-                        );
-                    }
+                        format!(
+                            "type mismatch for function `{}` from exported interface `{}`",
+                            f.name, interface.name
+                        )
+                    };
+                    return Err(signature_mismatch(
+                        description,
+                        interface,
+                        f,
+                        &expected,
+                        ty,
+                        AbiVariant::GuestExport,
+                    ));
                 }
             }
             None => bail!(
@@ -434,3 +664,131 @@ fn validate_exported_interface(
 
     Ok(())
 }
+
+/// Creates an error explaining why the core wasm type `actual` of `func` does
+/// not match the `expected` signature it's lowered to by the canonical ABI.
+///
+/// The error starts with `description` and is followed by the WIT signature of
+/// the function, both core wasm types, where they first diverge, and hints
+/// about common causes of the mismatch.
+fn signature_mismatch(
+    description: String,
+    interface: &Interface,
+    func: &Function,
+    expected: &WasmSignature,
+    actual: &FuncType,
+    variant: AbiVariant,
+) -> anyhow::Error {
+    let expected_ty = wasm_sig_to_func_type(expected.clone());
+    let mut msg = description;
+
+    if let Ok(sig) = InterfacePrinter::default().print_func_signature(interface, func) {
+        write!(msg, "\n  wit signature:      {sig}").unwrap();
+    }
+    write!(
+        msg,
+        "\n  expected core type: {}\n  found core type:    {}",
+        func_type_to_string(&expected_ty),
+        func_type_to_string(actual),
+    )
+    .unwrap();
+
+    if let Some(divergence) = first_divergence("parameter", expected_ty.params(), actual.params()) {
+        write!(msg, "\n  {divergence}").unwrap();
+    } else if let Some(divergence) =
+        first_divergence("result", expected_ty.results(), actual.results())
+    {
+        write!(msg, "\n  {divergence}").unwrap();
+    }
+
+    let mut hints = Vec::new();
+    if actual.results().len() > 1 {
+        hints.push(
+            "this looks like a multi-value return, but the canonical ABI returns at most one \
+             core value and otherwise uses a return pointer",
+        );
+    }
+    // The return pointer is only hinted at when it's all that's missing, as
+    // opposed to signatures which differ in other ways too.
+    if expected.retptr {
+        match variant {
+            AbiVariant::GuestImport
+                if expected_ty.params().split_last() == Some((&ValType::I32, actual.params())) =>
+            {
+                hints.push(
+                    "retptr expected: the results of this function are written to memory \
+                     through a pointer passed as the last parameter",
+                )
+            }
+            AbiVariant::GuestExport
+                if actual.params() == expected_ty.params()
+                    && actual.results() != expected_ty.results() =>
+            {
+                hints.push(
+                    "retptr expected: the results of this function are written to memory and a \
+                     pointer to them is returned",
+                )
+            }
+            _ => {}
+        }
+    }
+    if expected.indirect_params {
+        hints.push(
+            "the parameters of this function are passed indirectly through a single pointer \
+             since they flatten to too many core parameters",
+        );
+    }
+    for hint in hints {
+        write!(msg, "\n  hint: {hint}").unwrap();
+    }
+
+    anyhow!(msg)
+}
+
+/// Describes the first position where the `expected` and `actual` core types
+/// differ, if any.
+fn first_divergence(kind: &str, expected: &[ValType], actual: &[ValType]) -> Option<String> {
+    let mismatch = expected
+        .iter()
+        .zip(actual)
+        .position(|(a, b)| a != b)
+        .unwrap_or(expected.len().min(actual.len()));
+    match (expected.get(mismatch), actual.get(mismatch)) {
+        (Some(e), Some(a)) => Some(format!(
+            "{kind} {mismatch} is expected to be `{}` but found `{}`",
+            val_type_to_str(e),
+            val_type_to_str(a),
+        )),
+        (Some(e), None) => Some(format!(
+            "{kind} {mismatch} of type `{}` is missing",
+            val_type_to_str(e)
+        )),
+        (None, Some(a)) => Some(format!(
+            "unexpected {kind} {mismatch} of type `{}`",
+            val_type_to_str(a)
+        )),
+        (None, None) => None,
+    }
+}
+
+fn func_type_to_string(ty: &FuncType) -> String {
+    let list = |tys: &[ValType]| {
+        tys.iter()
+            .map(val_type_to_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("({}) -> ({})", list(ty.params()), list(ty.results()))
+}
+
+fn val_type_to_str(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::FuncRef => "funcref",
+        ValType::ExternRef => "externref",
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{read_interface, ComponentInterfaces};
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use wit_parser::Interface;
+
+/// A world, the interfaces imported and exported by a component, read from a
+/// `*.wit` file.
+///
+/// A world file contains a single `world` item listing the interfaces of the
+/// world, each of which refers to an interface file in the same directory:
+///
+/// ```wit
+/// world my-world {
+///     // Imports the interface in `host.wit` as `host`.
+///     import host
+///     // Exports the interface in `handler.wit` as `http`.
+///     export http: handler
+///     // The component's default interface, in `main.wit`.
+///     default export main
+/// }
+/// ```
+///
+/// Interfaces are read with [`read_interface`], so they can also refer to a
+/// binary `WitPackage` by including its file extension, and any interfaces
+/// they `use` are resolved relative to the world's directory as usual.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    /// The name of the world.
+    pub name: String,
+    /// The interfaces of the world.
+    pub interfaces: ComponentInterfaces,
+    /// All files read to resolve the world, including the world file itself.
+    pub files: Vec<PathBuf>,
+}
+
+/// Reads a world from `path`, either a world file or a directory.
+///
+/// For a directory, the world named `name` is read from the `*.wit` files in
+/// the directory which contain a world, and `name` can only be omitted if
+/// there's exactly one. For a file, `name` must match the world's name if
+/// specified.
+pub fn read_world(path: impl AsRef<Path>, name: Option<&str>) -> Result<World> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        let world = parse_world_file(path)?;
+        if let Some(name) = name {
+            if world.name != name {
+                bail!(
+                    "world file `{}` defines world `{}`, not `{name}`",
+                    path.display(),
+                    world.name
+                );
+            }
+        }
+        return Ok(world);
+    }
+
+    let mut worlds = Vec::new();
+    let mut entries = fs::read_dir(path)
+        .with_context(|| format!("failed to read directory: {}", path.display()))?
+        .map(|e| Ok(e?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort();
+    for file in entries {
+        if file.extension().and_then(|s| s.to_str()) != Some("wit") {
+            continue;
+        }
+        let contents = fs::read_to_string(&file)
+            .with_context(|| format!("failed to read: {}", file.display()))?;
+        let tokens = tokenize(&contents)
+            .with_context(|| format!("failed to tokenize: {}", file.display()))?;
+        if tokens.first() == Some(&"world") {
+            worlds.push(file);
+        }
+    }
+    let mut found = Vec::new();
+    for file in worlds {
+        let world = parse_world_file(&file)?;
+        match name {
+            Some(name) if world.name == name => return Ok(world),
+            Some(_) => {}
+            None => found.push(world),
+        }
+    }
+    match (name, found.len()) {
+        (Some(name), _) => bail!(
+            "directory `{}` does not contain a world named `{name}`",
+            path.display()
+        ),
+        (None, 1) => Ok(found.pop().unwrap()),
+        (None, 0) => bail!("directory `{}` does not contain a world", path.display()),
+        (None, _) => bail!(
+            "directory `{}` contains multiple worlds and one must be named",
+            path.display()
+        ),
+    }
+}
+
+fn parse_world_file(path: &Path) -> Result<World> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read: {}", path.display()))?;
+    let dir = path.parent().unwrap();
+    let mut ret = World::default();
+    ret.files.push(path.to_path_buf());
+    parse_world(&contents, dir, &mut ret)
+        .with_context(|| format!("failed to parse world file `{}`", path.display()))?;
+    Ok(ret)
+}
+
+fn parse_world(contents: &str, dir: &Path, ret: &mut World) -> Result<()> {
+    let mut tokens = tokenize(contents)?.into_iter().peekable();
+    expect(tokens.next(), "world")?;
+    ret.name = ident(tokens.next(), "a world name")?.to_string();
+    expect(tokens.next(), "{")?;
+
+    loop {
+        let kind = match tokens.next() {
+            Some("}") => break,
+            Some("import") => "import",
+            Some("export") => "export",
+            Some("default") => {
+                expect(tokens.next(), "export")?;
+                "default export"
+            }
+            t => bail!("expected `import`, `export` or `}}`, found {}", describe(t)),
+        };
+        let name = ident(tokens.next(), "an interface name")?;
+        let file = if kind != "default export" && tokens.peek() == Some(&":") {
+            tokens.next();
+            ident(tokens.next(), "an interface file")?
+        } else {
+            name
+        };
+        let mut interface = read_world_interface(dir, file, &mut ret.files)?;
+        let interfaces = &mut ret.interfaces;
+        let prev = match kind {
+            "import" => {
+                interface.name = name.to_string();
+                interfaces.imports.insert(name.to_string(), interface)
+            }
+            "export" => {
+                interface.name = name.to_string();
+                interfaces.exports.insert(name.to_string(), interface)
+            }
+            _ => interfaces.default.replace(interface),
+        };
+        if prev.is_some() {
+            match kind {
+                "import" | "export" => bail!("world {kind}s `{name}` more than once"),
+                _ => bail!("world has more than one default export"),
+            }
+        }
+    }
+    if let Some(t) = tokens.next() {
+        bail!("expected end of file after the world, found `{t}`");
+    }
+    Ok(())
+}
+
+/// Reads the interface which a world refers to as `file`, recording every
+/// file read into `files`.
+fn read_world_interface(dir: &Path, file: &str, files: &mut Vec<PathBuf>) -> Result<Interface> {
+    let path = if Path::new(file).extension().is_some() {
+        dir.join(file)
+    } else {
+        dir.join(file).with_extension("wit")
+    };
+    files.push(path.clone());
+    let is_wit = matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("wit") | Some("md")
+    );
+    if !is_wit {
+        return read_interface(&path, None);
+    }
+
+    // Parse the interface here rather than through `read_interface` to learn
+    // about the files it `use`s.
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read: {}", path.display()))?;
+    Interface::parse_with(&path, &contents, |name| {
+        let wit = dir.join(name).with_extension("wit");
+        let (path, contents) = match fs::read_to_string(&wit) {
+            Ok(contents) => (wit, contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let wit_md = wit.with_extension("wit.md");
+                match fs::read_to_string(&wit_md) {
+                    Ok(contents) => (wit_md, contents),
+                    Err(_) => return Err(err.into()),
+                }
+            }
+            Err(err) => return Err(err.into()),
+        };
+        files.push(path.clone());
+        Ok((path, contents))
+    })
+}
+
+fn expect(token: Option<&str>, expected: &str) -> Result<()> {
+    match token {
+        Some(t) if t == expected => Ok(()),
+        t => bail!("expected `{expected}`, found {}", describe(t)),
+    }
+}
+
+fn ident<'a>(token: Option<&'a str>, expected: &str) -> Result<&'a str> {
+    match token {
+        Some(t) if !matches!(t, "{" | "}" | ":") => Ok(t),
+        t => bail!("expected {expected}, found {}", describe(t)),
+    }
+}
+
+fn describe(token: Option<&str>) -> String {
+    match token {
+        Some(t) => format!("`{t}`"),
+        None => "end of file".to_string(),
+    }
+}
+
+/// Splits a world file into its tokens, dropping comments.
+fn tokenize(contents: &str) -> Result<Vec<&str>> {
+    let mut tokens = Vec::new();
+    let mut rest = contents;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(tokens);
+        }
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.split_once('\n').map(|(_, r)| r).unwrap_or("");
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            rest = match comment.split_once("*/") {
+                Some((_, r)) => r,
+                None => bail!("unterminated block comment"),
+            };
+            continue;
+        }
+        let len = rest
+            .char_indices()
+            .find(|(i, c)| {
+                c.is_whitespace()
+                    || "{}:".contains(*c)
+                    || rest[*i..].starts_with("//")
+                    || rest[*i..].starts_with("/*")
+            })
+            .map(|(i, _)| i.max(1))
+            .unwrap_or(rest.len());
+        tokens.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+}
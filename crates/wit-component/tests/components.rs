@@ -1,7 +1,11 @@
 use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
 use pretty_assertions::assert_eq;
 use std::{fs, path::Path};
-use wit_component::ComponentEncoder;
+use wit_component::{
+    analyze_component, embed_module_interfaces, embed_module_options, CanonicalOptions,
+    ComponentEncoder, ComponentInterfaces, ComponentReport,
+};
 use wit_parser::Interface;
 
 fn read_interface(path: &Path) -> Result<Interface> {
@@ -64,6 +68,16 @@ fn read_adapters(dir: &Path) -> Result<Vec<(String, Vec<u8>, Interface)>> {
 /// * [optional] `default.wit` - represents the component's default interface.
 /// * [optional] `export-<name>.wit` - represents an interface exported by the component.
 /// * [optional] `import-<name>.wit` - represents an interface imported by the component.
+/// * [optional] `stub-<name>.txt` - the imported interface `<name>` is stubbed
+///   with the behavior contained in the file.
+/// * [optional] `library-<name>.wat` - a library module linked with the main
+///   module which satisfies its imports from `<name>`.
+/// * [optional] `renames.txt` - rules renaming the module's imports and
+///   exports, one `import FROM=TO` or `export FROM=TO` per line.
+/// * [optional] `initializer.txt` - the name of the module's initializer
+///   function.
+/// * [optional] `options.txt` - canonical ABI options, one `TARGET OPTIONS`
+///   per line.
 ///
 /// And the output files are one of the following:
 ///
@@ -109,6 +123,13 @@ fn component_encoding_via_flags() -> Result<()> {
             .exports(exports)?
             .validate(true);
         encoder = add_adapters(encoder, &path)?;
+        encoder = add_stubs(encoder, &path)?;
+        encoder = add_libraries(encoder, &path)?;
+        encoder = add_renames(encoder, &path)?;
+        encoder = add_initializer(encoder, &path)?;
+        for (target, options) in read_options(&path)? {
+            encoder = encoder.canonical_options(&target, options);
+        }
 
         if let Some(interface) = interface {
             encoder = encoder.interface(interface)?;
@@ -136,8 +157,6 @@ fn component_encoding_via_flags() -> Result<()> {
 /// needing the wit files passed in as well.
 #[test]
 fn component_encoding_via_custom_sections() -> Result<()> {
-    use wasm_encoder::{Encode, Section};
-
     for entry in fs::read_dir("tests/components")? {
         let path = entry?.path();
         if !path.is_dir() {
@@ -154,28 +173,33 @@ fn component_encoding_via_custom_sections() -> Result<()> {
         let mut module = wat::parse_file(&module_path)
             .with_context(|| format!("expected file `{}`", module_path.display()))?;
 
-        // Use a first `encoder` to encode all the `*.wit` interface information
-        // into a "types only" component which is then placed into a custom
-        // section.
-        // of passing them to the ComponentEncoder explicitly.
-        let mut encoder = ComponentEncoder::default().types_only(true).validate(true);
-        encoder = encoder.imports(read_interfaces(&path, "import-*.wit")?)?;
-        encoder = encoder.exports(read_interfaces(&path, "export-*.wit")?)?;
+        // Embed all the `*.wit` interface information into the module as a
+        // "types only" component placed into a custom section instead of
+        // passing them to the ComponentEncoder explicitly.
+        let mut interfaces = ComponentInterfaces::default();
+        for i in read_interfaces(&path, "import-*.wit")? {
+            interfaces.imports.insert(i.name.clone(), i);
+        }
+        for i in read_interfaces(&path, "export-*.wit")? {
+            interfaces.exports.insert(i.name.clone(), i);
+        }
         if interface_path.is_file() {
-            encoder = encoder.interface(read_interface(&interface_path)?)?;
+            interfaces.default = Some(read_interface(&interface_path)?);
+        }
+        module = embed_module_interfaces(&module, &interfaces)?;
+        let options = read_options(&path)?;
+        if !options.is_empty() {
+            module = embed_module_options(&module, &options)?;
         }
-        let contents = encoder.encode()?;
-        let section = wasm_encoder::CustomSection {
-            name: "component-type",
-            data: &contents,
-        };
-        module.push(section.id());
-        section.encode(&mut module);
 
         // Now parse run the `module` alone through the encoder without extra
         // information about interfaces to ensure it still works as before.
         let mut encoder = ComponentEncoder::default().module(&module)?.validate(true);
         encoder = add_adapters(encoder, &path)?;
+        encoder = add_stubs(encoder, &path)?;
+        encoder = add_libraries(encoder, &path)?;
+        encoder = add_renames(encoder, &path)?;
+        encoder = add_initializer(encoder, &path)?;
 
         assert_output(test_case, &encoder, &component_path, &error_path)?;
     }
@@ -183,6 +207,19 @@ fn component_encoding_via_custom_sections() -> Result<()> {
     Ok(())
 }
 
+fn read_options(path: &Path) -> Result<IndexMap<String, CanonicalOptions>> {
+    let path = path.join("options.txt");
+    let mut ret = IndexMap::new();
+    if !path.is_file() {
+        return Ok(ret);
+    }
+    for line in fs::read_to_string(&path)?.lines() {
+        let (target, options) = line.split_once(' ').unwrap_or((line, ""));
+        ret.insert(target.to_string(), options.parse()?);
+    }
+    Ok(ret)
+}
+
 fn add_adapters(mut encoder: ComponentEncoder, path: &Path) -> Result<ComponentEncoder> {
     let adapters = read_adapters(path)?;
     for (name, wasm, interface) in adapters.iter() {
@@ -191,6 +228,64 @@ fn add_adapters(mut encoder: ComponentEncoder, path: &Path) -> Result<ComponentE
     Ok(encoder)
 }
 
+fn add_libraries(mut encoder: ComponentEncoder, path: &Path) -> Result<ComponentEncoder> {
+    for p in glob::glob(path.join("library-*.wat").to_str().unwrap())? {
+        let p = p?;
+        let name = p
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .trim_start_matches("library-");
+        let wasm =
+            wat::parse_file(&p).with_context(|| format!("expected file `{}`", p.display()))?;
+        encoder = encoder.library(name, &wasm);
+    }
+    Ok(encoder)
+}
+
+fn add_renames(mut encoder: ComponentEncoder, path: &Path) -> Result<ComponentEncoder> {
+    let path = path.join("renames.txt");
+    if !path.is_file() {
+        return Ok(encoder);
+    }
+    for line in fs::read_to_string(&path)?.lines() {
+        let (from, to) = match line.split_once('=') {
+            Some(rule) => rule,
+            None => bail!("invalid rename rule `{line}`"),
+        };
+        encoder = match from.split_once(' ') {
+            Some(("import", from)) => encoder.rename_import(from, to),
+            Some(("export", from)) => encoder.rename_export(from, to),
+            _ => bail!("invalid rename rule `{line}`"),
+        };
+    }
+    Ok(encoder)
+}
+
+fn add_initializer(encoder: ComponentEncoder, path: &Path) -> Result<ComponentEncoder> {
+    let path = path.join("initializer.txt");
+    if !path.is_file() {
+        return Ok(encoder);
+    }
+    Ok(encoder.initializer(fs::read_to_string(&path)?.trim()))
+}
+
+fn add_stubs(mut encoder: ComponentEncoder, path: &Path) -> Result<ComponentEncoder> {
+    for p in glob::glob(path.join("stub-*.txt").to_str().unwrap())? {
+        let p = p?;
+        let name = p
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .trim_start_matches("stub-");
+        let stub = fs::read_to_string(&p)?.trim().parse()?;
+        encoder = encoder.stub_import(name, stub);
+    }
+    Ok(encoder)
+}
+
 fn assert_output(
     test_case: &str,
     encoder: &ComponentEncoder,
@@ -205,11 +300,12 @@ fn assert_output(
             Err(e) => (e.to_string(), &error_path),
         }
     } else {
+        let bytes = r.with_context(|| format!("failed to encode for test case `{}`", test_case))?;
+        analyze_component(&bytes).with_context(|| {
+            format!("failed to analyze component for test case `{}`", test_case)
+        })?;
         (
-            wasmprinter::print_bytes(
-                &r.with_context(|| format!("failed to encode for test case `{}`", test_case))?,
-            )
-            .with_context(|| {
+            wasmprinter::print_bytes(&bytes).with_context(|| {
                 format!(
                     "failed to print component bytes for test case `{}`",
                     test_case
@@ -232,3 +328,94 @@ fn assert_output(
     }
     Ok(())
 }
+
+/// Tests the analysis of a component with an adapter, whose imports are
+/// called through a shim table filled in by a later fixup instantiation.
+#[test]
+fn analyze_adapter() -> Result<()> {
+    let bytes = wat::parse_file("tests/components/adapt-memory-simple/component.wat")?;
+    let report = analyze_component(&bytes)?;
+
+    assert_eq!(report.size, bytes.len());
+    assert_eq!(report.imports, ["new"]);
+    assert!(report.exports.is_empty());
+
+    // Modules are the adapter, the main module, the shim and the fixup, and
+    // the size of each is that of its sections and their framing.
+    assert_eq!(report.modules.len(), 4);
+    fn leb_len(n: usize) -> usize {
+        if n < 0x80 {
+            1
+        } else {
+            1 + leb_len(n >> 7)
+        }
+    }
+    for (i, module) in report.modules.iter().enumerate() {
+        assert_eq!(module.index as usize, i);
+        let sections = module
+            .sections
+            .iter()
+            .map(|(_, size)| 1 + leb_len(*size) + size)
+            .sum::<usize>();
+        assert_eq!(module.size, 8 + sections, "size of module {i}");
+    }
+    assert!(report.modules.iter().map(|m| m.size).sum::<usize>() < report.size);
+
+    let json = report.to_json();
+    // The shim is instantiated first, then the main module, the adapter and
+    // finally the fixup.
+    assert!(json.contains(
+        r#""instances":[{"index":0,"module":2,"args":[]},{"index":1,"exports":["log"]},{"index":2,"module":1,"args":[{"name":"old","instance":1}]},{"index":3,"exports":["log"]},{"index":4,"module":0,"args":[{"name":"new","instance":3}]},{"index":5,"exports":["$imports","0","1"]},{"index":6,"module":3,"args":[{"name":"","instance":5}]}]"#
+    ), "{json}");
+    // The main module's `old` import is attributed to the adapter rather than
+    // to the shim it's called through, and the adapter's to the import.
+    assert!(json.contains(
+        r#""links":[{"instance":2,"module":1,"name":"old","providers":[{"instance":4,"module":0}]},{"instance":4,"module":0,"name":"new","providers":[{"import":"new"}]},{"instance":6,"module":3,"name":"","providers":[{"instance":0,"module":2},{"import":"new"},{"instance":4,"module":0}]}]"#
+    ), "{json}");
+    assert!(json.ends_with(
+        r#""canonicals":[{"kind":"lower","func":"lowered `new::log`","options":["memory `memory` of core instance 2 (module 1)","string-encoding=utf8"]}]}
+"#
+    ), "{json}");
+
+    let dot = report.to_dot();
+    for line in [
+        r#"  import0 [shape=ellipse, label="import new"];"#,
+        r#"  instance4 -> instance2 [label="old"];"#,
+        r#"  import0 -> instance4 [label="new"];"#,
+        r#"  instance0 -> instance6 [label=""];"#,
+        r#"  import0 -> instance6 [label=""];"#,
+        r#"  instance4 -> instance6 [label=""];"#,
+    ] {
+        assert!(
+            dot.lines().any(|l| l == line),
+            "missing `{line}` in:\n{dot}"
+        );
+    }
+
+    let text = report.to_string();
+    assert!(
+        text.contains("    `old` <- instance 4 (module 0)\n"),
+        "{text}"
+    );
+    Ok(())
+}
+
+#[test]
+fn analysis_escaping() {
+    let report = ComponentReport {
+        size: 1,
+        imports: vec!["a\"b\\c\nd\te\u{1}".to_string()],
+        ..ComponentReport::default()
+    };
+    assert_eq!(
+        report.to_json(),
+        concat!(
+            r#"{"size":1,"imports":["a\"b\\c\nd\u0009e\u0001"],"exports":[],"#,
+            r#""modules":[],"instances":[],"links":[],"canonicals":[]}"#,
+            "\n"
+        )
+    );
+    assert!(report
+        .to_dot()
+        .contains("  import0 [shape=ellipse, label=\"import a\\\"b\\\\c\\nd\te\u{1}\"];\n"));
+}
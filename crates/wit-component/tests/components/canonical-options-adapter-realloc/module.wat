@@ -0,0 +1,4 @@
+(module
+  (import "old" "read" (func (param i32 i32)))
+  (memory (export "memory") 1)
+)
@@ -0,0 +1,79 @@
+(component
+  (type (;0;) (func (result string)))
+  (type (;1;) 
+    (instance
+      (alias outer 1 0 (type (;0;)))
+      (export "a" (func (type 0)))
+      (export "b" (func (type 0)))
+    )
+  )
+  (import "foo" (instance (;0;) (type 1)))
+  (core module (;0;)
+    (type (;0;) (func (param i32)))
+    (type (;1;) (func (param i32 i32 i32 i32) (result i32)))
+    (import "foo" "a" (func (;0;) (type 0)))
+    (import "foo" "b" (func (;1;) (type 0)))
+    (func (;2;) (type 1) (param i32 i32 i32 i32) (result i32)
+      unreachable
+    )
+    (func (;3;) (type 1) (param i32 i32 i32 i32) (result i32)
+      unreachable
+    )
+    (memory (;0;) 1)
+    (export "memory" (memory 0))
+    (export "cabi_realloc" (func 2))
+    (export "utf16_realloc" (func 3))
+  )
+  (core module (;1;)
+    (type (;0;) (func (param i32)))
+    (func (;0;) (type 0) (param i32)
+      local.get 0
+      i32.const 0
+      call_indirect (type 0)
+    )
+    (func (;1;) (type 0) (param i32)
+      local.get 0
+      i32.const 1
+      call_indirect (type 0)
+    )
+    (table (;0;) 2 2 funcref)
+    (export "0" (func 0))
+    (export "1" (func 1))
+    (export "$imports" (table 0))
+  )
+  (core module (;2;)
+    (type (;0;) (func (param i32)))
+    (import "" "0" (func (;0;) (type 0)))
+    (import "" "1" (func (;1;) (type 0)))
+    (import "" "$imports" (table (;0;) 2 2 funcref))
+    (elem (;0;) (i32.const 0) func 0 1)
+  )
+  (core instance (;0;) (instantiate 1))
+  (alias core export 0 "0" (core func (;0;)))
+  (alias core export 0 "1" (core func (;1;)))
+  (core instance (;1;) 
+    (export "a" (func 0))
+    (export "b" (func 1))
+  )
+  (core instance (;2;) (instantiate 0
+      (with "foo" (instance 1))
+    )
+  )
+  (alias core export 2 "memory" (core memory (;0;)))
+  (alias core export 2 "cabi_realloc" (core func (;2;)))
+  (alias core export 0 "$imports" (core table (;0;)))
+  (alias export 0 "a" (func (;0;)))
+  (alias core export 2 "utf16_realloc" (core func (;3;)))
+  (core func (;4;) (canon lower (func 0) (memory 0) (realloc 3) string-encoding=utf16))
+  (alias export 0 "b" (func (;1;)))
+  (core func (;5;) (canon lower (func 1) (memory 0) (realloc 3) string-encoding=utf8))
+  (core instance (;3;) 
+    (export "$imports" (table 0))
+    (export "0" (func 4))
+    (export "1" (func 5))
+  )
+  (core instance (;4;) (instantiate 2
+      (with "" (instance 3))
+    )
+  )
+)
\ No newline at end of file
@@ -0,0 +1,7 @@
+(module
+  (import "foo" "a" (func (param i32)))
+  (import "foo" "b" (func (param i32)))
+  (memory (export "memory") 1)
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+  (func (export "utf16_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+)
@@ -0,0 +1,4 @@
+(module
+  (memory (export "memory") 1)
+  (func (export "foo#a") (param i32 i32) unreachable)
+)
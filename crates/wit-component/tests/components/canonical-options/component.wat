@@ -0,0 +1,38 @@
+(component
+  (type (;0;) (func (param "s" string)))
+  (core module (;0;)
+    (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+    (type (;1;) (func (param i32 i32)))
+    (func (;0;) (type 0) (param i32 i32 i32 i32) (result i32)
+      unreachable
+    )
+    (func (;1;) (type 0) (param i32 i32 i32 i32) (result i32)
+      unreachable
+    )
+    (func (;2;) (type 1) (param i32 i32)
+      unreachable
+    )
+    (func (;3;) (type 1) (param i32 i32)
+      unreachable
+    )
+    (memory (;0;) 1)
+    (export "memory" (memory 0))
+    (export "cabi_realloc" (func 0))
+    (export "utf16_realloc" (func 1))
+    (export "foo#a" (func 2))
+    (export "foo#b" (func 3))
+  )
+  (core instance (;0;) (instantiate 0))
+  (alias core export 0 "memory" (core memory (;0;)))
+  (alias core export 0 "cabi_realloc" (core func (;0;)))
+  (alias core export 0 "foo#a" (core func (;1;)))
+  (alias core export 0 "utf16_realloc" (core func (;2;)))
+  (func (;0;) (type 0) (canon lift (core func 1) (memory 0) (realloc 2) string-encoding=utf16))
+  (alias core export 0 "foo#b" (core func (;3;)))
+  (func (;1;) (type 0) (canon lift (core func 3) (memory 0) (realloc 2) string-encoding=utf8))
+  (instance (;0;)
+    (export "a" (func 0))
+    (export "b" (func 1))
+  )
+  (export "foo" (instance 0))
+)
\ No newline at end of file
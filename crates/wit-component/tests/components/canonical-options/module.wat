@@ -0,0 +1,7 @@
+(module
+  (memory (export "memory") 1)
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+  (func (export "utf16_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+  (func (export "foo#a") (param i32 i32) unreachable)
+  (func (export "foo#b") (param i32 i32) unreachable)
+)
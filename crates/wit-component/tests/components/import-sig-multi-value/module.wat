@@ -0,0 +1,3 @@
+(module
+  (import "foo" "f" (func (result i32 i32)))
+)
@@ -0,0 +1,21 @@
+(component
+  (core module (;0;)
+    (type (;0;) (func))
+    (func (;0;) (type 0))
+    (export "_initialize" (func 0))
+  )
+  (core instance (;0;) (instantiate 0))
+  (alias core export 0 "_initialize" (core func (;0;)))
+  (core module (;1;)
+    (type (;0;) (func))
+    (import "" "_initialize" (func (;0;) (type 0)))
+    (start 0)
+  )
+  (core instance (;1;) 
+    (export "_initialize" (func 0))
+  )
+  (core instance (;2;) (instantiate 1
+      (with "" (instance 1))
+    )
+  )
+)
\ No newline at end of file
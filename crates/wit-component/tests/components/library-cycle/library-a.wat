@@ -0,0 +1,4 @@
+(module
+  (import "b" "g" (func))
+  (func (export "f"))
+)
@@ -0,0 +1,4 @@
+(module
+  (import "a" "f" (func))
+  (func (export "g"))
+)
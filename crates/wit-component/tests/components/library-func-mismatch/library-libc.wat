@@ -0,0 +1,4 @@
+(module
+  (func (export "strlen") (param i32) (result i32)
+    unreachable)
+)
@@ -0,0 +1,3 @@
+(module
+  (import "libc" "strlen" (func (param i32 i32) (result i32)))
+)
@@ -0,0 +1,3 @@
+(module
+  (global (export "stack_pointer") (mut i32) (i32.const 0))
+)
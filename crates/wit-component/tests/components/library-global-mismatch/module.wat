@@ -0,0 +1,3 @@
+(module
+  (import "libc" "stack_pointer" (global i32))
+)
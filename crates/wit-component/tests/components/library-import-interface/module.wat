@@ -0,0 +1,4 @@
+(module
+  (import "host" "log" (func))
+  (import "libc" "malloc" (func (param i32) (result i32)))
+)
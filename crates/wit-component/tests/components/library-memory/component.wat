@@ -0,0 +1,37 @@
+(component
+  (type (;0;) (func (param "s" string)))
+  (core module (;0;)
+    (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+    (func (;0;) (type 0) (param i32 i32 i32 i32) (result i32)
+      unreachable
+    )
+    (memory (;0;) 1)
+    (export "memory" (memory 0))
+    (export "cabi_realloc" (func 0))
+  )
+  (core module (;1;)
+    (type (;0;) (func (param i32 i32 i32 i32) (result i32)))
+    (type (;1;) (func (param i32 i32)))
+    (import "libc" "memory" (memory (;0;) 1))
+    (import "libc" "cabi_realloc" (func (;0;) (type 0)))
+    (func (;1;) (type 1) (param i32 i32)
+      unreachable
+    )
+    (export "foo#log" (func 1))
+    (export "memory" (memory 0))
+    (export "cabi_realloc" (func 0))
+  )
+  (core instance (;0;) (instantiate 0))
+  (core instance (;1;) (instantiate 1
+      (with "libc" (instance 0))
+    )
+  )
+  (alias core export 1 "memory" (core memory (;0;)))
+  (alias core export 1 "cabi_realloc" (core func (;0;)))
+  (alias core export 1 "foo#log" (core func (;1;)))
+  (func (;0;) (type 0) (canon lift (core func 1) (memory 0) (realloc 0) string-encoding=utf8))
+  (instance (;0;)
+    (export "log" (func 0))
+  )
+  (export "foo" (instance 0))
+)
\ No newline at end of file
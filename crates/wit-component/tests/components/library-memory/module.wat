@@ -0,0 +1,8 @@
+(module
+  (import "libc" "memory" (memory 1))
+  (import "libc" "cabi_realloc" (func (param i32 i32 i32 i32) (result i32)))
+  (func (export "foo#log") (param i32 i32)
+    unreachable)
+  (export "memory" (memory 0))
+  (export "cabi_realloc" (func 0))
+)
@@ -0,0 +1,3 @@
+(module
+  (import "libc" "malloc" (func (param i32) (result i32)))
+)
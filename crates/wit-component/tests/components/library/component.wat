@@ -0,0 +1,21 @@
+(component
+  (core module (;0;)
+    (type (;0;) (func (param i32) (result i32)))
+    (func (;0;) (type 0) (param i32) (result i32)
+      unreachable
+    )
+    (memory (;0;) 1)
+    (export "memory" (memory 0))
+    (export "strlen" (func 0))
+  )
+  (core module (;1;)
+    (type (;0;) (func (param i32) (result i32)))
+    (import "libc" "memory" (memory (;0;) 1))
+    (import "libc" "strlen" (func (;0;) (type 0)))
+  )
+  (core instance (;0;) (instantiate 0))
+  (core instance (;1;) (instantiate 1
+      (with "libc" (instance 0))
+    )
+  )
+)
\ No newline at end of file
@@ -0,0 +1,5 @@
+(module
+  (memory (export "memory") 1)
+  (func (export "strlen") (param i32) (result i32)
+    unreachable)
+)
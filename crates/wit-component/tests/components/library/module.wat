@@ -0,0 +1,4 @@
+(module
+  (import "libc" "memory" (memory 1))
+  (import "libc" "strlen" (func (param i32) (result i32)))
+)
@@ -0,0 +1,4 @@
+(module
+  (func (export "f_a"))
+  (func (export "g_a"))
+)
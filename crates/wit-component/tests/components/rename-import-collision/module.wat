@@ -0,0 +1,4 @@
+(module
+  (import "a" "x" (func))
+  (import "a" "y" (func))
+)
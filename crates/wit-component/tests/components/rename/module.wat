@@ -0,0 +1,12 @@
+(module
+  (import "env" "foo1" (func))
+  (import "env" "foo2" (func (param i32)))
+  (import "env" "foo3" (func (param f32)))
+  (import "host" "bar_1" (func (param i32 i32)))
+  (import "host" "bar_2" (func (param i32)))
+  (import "baz" "baz1" (func (param i32 i32)))
+  (import "baz" "baz2" (func))
+  (import "baz" "baz3" (func (param i32)))
+  (memory (export "memory") 1)
+  (func (export "my_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+)
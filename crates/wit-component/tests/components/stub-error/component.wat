@@ -0,0 +1,105 @@
+(component
+  (core module (;0;)
+    (type (;0;) (func (param i32 i32)))
+    (type (;1;) (func (result i32)))
+    (type (;2;) (func))
+    (import "env" "memory" (memory (;0;) 0))
+    (func (;0;) (type 0) (param i32 i32)
+      local.get 1
+      i32.const 0
+      i32.const 12
+      memory.fill
+      local.get 1
+      i32.const 1
+      i32.store8
+    )
+    (func (;1;) (type 1) (result i32)
+      i32.const 1
+    )
+    (func (;2;) (type 2)
+      unreachable
+    )
+    (export "read" (func 0))
+    (export "check" (func 1))
+    (export "thunk" (func 2))
+  )
+  (core module (;1;)
+    (type (;0;) (func (param i32 i32)))
+    (type (;1;) (func (result i32)))
+    (type (;2;) (func))
+    (import "foo" "read" (func (;0;) (type 0)))
+    (import "foo" "check" (func (;1;) (type 1)))
+    (import "foo" "thunk" (func (;2;) (type 2)))
+    (memory (;0;) 1)
+    (export "memory" (memory 0))
+  )
+  (core module (;2;)
+    (type (;0;) (func (param i32 i32)))
+    (type (;1;) (func (result i32)))
+    (type (;2;) (func))
+    (func (;0;) (type 0) (param i32 i32)
+      local.get 0
+      local.get 1
+      i32.const 0
+      call_indirect (type 0)
+    )
+    (func (;1;) (type 1) (result i32)
+      i32.const 1
+      call_indirect (type 1)
+    )
+    (func (;2;) (type 2)
+      i32.const 2
+      call_indirect (type 2)
+    )
+    (table (;0;) 3 3 funcref)
+    (export "0" (func 0))
+    (export "1" (func 1))
+    (export "2" (func 2))
+    (export "$imports" (table 0))
+  )
+  (core module (;3;)
+    (type (;0;) (func (param i32 i32)))
+    (type (;1;) (func (result i32)))
+    (type (;2;) (func))
+    (import "" "0" (func (;0;) (type 0)))
+    (import "" "1" (func (;1;) (type 1)))
+    (import "" "2" (func (;2;) (type 2)))
+    (import "" "$imports" (table (;0;) 3 3 funcref))
+    (elem (;0;) (i32.const 0) func 0 1 2)
+  )
+  (core instance (;0;) (instantiate 2))
+  (alias core export 0 "0" (core func (;0;)))
+  (alias core export 0 "1" (core func (;1;)))
+  (alias core export 0 "2" (core func (;2;)))
+  (core instance (;1;) 
+    (export "read" (func 0))
+    (export "check" (func 1))
+    (export "thunk" (func 2))
+  )
+  (core instance (;2;) (instantiate 1
+      (with "foo" (instance 1))
+    )
+  )
+  (alias core export 2 "memory" (core memory (;0;)))
+  (core instance (;3;) 
+    (export "memory" (memory 0))
+  )
+  (core instance (;4;) (instantiate 0
+      (with "env" (instance 3))
+    )
+  )
+  (alias core export 0 "$imports" (core table (;0;)))
+  (alias core export 4 "read" (core func (;3;)))
+  (alias core export 4 "check" (core func (;4;)))
+  (alias core export 4 "thunk" (core func (;5;)))
+  (core instance (;5;) 
+    (export "$imports" (table 0))
+    (export "0" (func 3))
+    (export "1" (func 4))
+    (export "2" (func 5))
+  )
+  (core instance (;6;) (instantiate 3
+      (with "" (instance 5))
+    )
+  )
+)
\ No newline at end of file
@@ -0,0 +1,6 @@
+(module
+  (import "foo" "read" (func (param i32 i32)))
+  (import "foo" "check" (func (result i32)))
+  (import "foo" "thunk" (func))
+  (memory (export "memory") 1)
+)
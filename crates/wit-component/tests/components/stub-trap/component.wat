@@ -0,0 +1,50 @@
+(component
+  (core module (;0;)
+    (type (;0;) (func (param i32)))
+    (func (;0;) (type 0) (param i32)
+      unreachable
+    )
+    (export "read" (func 0))
+  )
+  (core module (;1;)
+    (type (;0;) (func (param i32)))
+    (import "foo" "read" (func (;0;) (type 0)))
+  )
+  (core module (;2;)
+    (type (;0;) (func (param i32)))
+    (func (;0;) (type 0) (param i32)
+      local.get 0
+      i32.const 0
+      call_indirect (type 0)
+    )
+    (table (;0;) 1 1 funcref)
+    (export "0" (func 0))
+    (export "$imports" (table 0))
+  )
+  (core module (;3;)
+    (type (;0;) (func (param i32)))
+    (import "" "0" (func (;0;) (type 0)))
+    (import "" "$imports" (table (;0;) 1 1 funcref))
+    (elem (;0;) (i32.const 0) func 0)
+  )
+  (core instance (;0;) (instantiate 2))
+  (alias core export 0 "0" (core func (;0;)))
+  (core instance (;1;) 
+    (export "read" (func 0))
+  )
+  (core instance (;2;) (instantiate 1
+      (with "foo" (instance 1))
+    )
+  )
+  (core instance (;3;) (instantiate 0))
+  (alias core export 0 "$imports" (core table (;0;)))
+  (alias core export 3 "read" (core func (;1;)))
+  (core instance (;4;) 
+    (export "$imports" (table 0))
+    (export "0" (func 1))
+  )
+  (core instance (;5;) (instantiate 3
+      (with "" (instance 4))
+    )
+  )
+)
\ No newline at end of file
@@ -0,0 +1,3 @@
+(module
+  (import "foo" "read" (func (param i32)))
+)
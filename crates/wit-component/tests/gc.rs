@@ -0,0 +1,257 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use wasmparser::{Parser, Payload};
+use wit_component::{
+    embed_module_interfaces, embed_module_options, gc_module, CanonicalOptions, ComponentEncoder,
+    ComponentInterfaces,
+};
+use wit_parser::Interface;
+
+/// A summary of the items defined in a module used to check the output of the
+/// gc pass.
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    imports: Vec<String>,
+    exports: Vec<String>,
+    funcs: u32,
+    globals: u32,
+    tables: u32,
+    elements: u32,
+    data: u32,
+    start: bool,
+}
+
+fn summarize(wasm: &[u8]) -> Result<Summary> {
+    wasmparser::validate(wasm)?;
+    let mut ret = Summary::default();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::ImportSection(s) => {
+                for i in s {
+                    let i = i?;
+                    ret.imports.push(format!("{}::{}", i.module, i.name));
+                }
+            }
+            Payload::ExportSection(s) => {
+                for e in s {
+                    ret.exports.push(e?.name.to_string());
+                }
+            }
+            Payload::FunctionSection(s) => ret.funcs += s.get_count(),
+            Payload::GlobalSection(s) => ret.globals += s.get_count(),
+            Payload::TableSection(s) => ret.tables += s.get_count(),
+            Payload::ElementSection(s) => ret.elements += s.get_count(),
+            Payload::DataSection(s) => ret.data += s.get_count(),
+            Payload::StartSection { .. } => ret.start = true,
+            _ => {}
+        }
+    }
+    Ok(ret)
+}
+
+fn gc(wat: &str, keep: &[&str]) -> Result<Summary> {
+    let wasm = wat::parse_str(wat)?;
+    summarize(&gc_module(&wasm, keep)?)
+}
+
+#[test]
+fn removes_unused_exports_and_imports() -> Result<()> {
+    let summary = gc(
+        r#"
+            (module
+                (import "env" "used" (func $used))
+                (import "env" "unused" (func $unused))
+                (global $g (mut i32) (i32.const 0))
+                (global $h (mut i32) (i32.const 0))
+                (func (export "a")
+                    call $used
+                    global.get $g
+                    drop)
+                (func (export "b")
+                    call $unused
+                    global.get $h
+                    drop)
+                (memory (export "memory") 1)
+            )
+        "#,
+        &["a", "memory"],
+    )?;
+    assert_eq!(
+        summary,
+        Summary {
+            imports: vec!["env::used".to_string()],
+            exports: vec!["a".to_string(), "memory".to_string()],
+            funcs: 1,
+            globals: 1,
+            ..Summary::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn keeps_active_segments_and_start() -> Result<()> {
+    let summary = gc(
+        r#"
+            (module
+                (import "env" "f" (func $f))
+                (table 1 funcref)
+                (elem (i32.const 0) $in_table)
+                (memory 1)
+                (data (i32.const 0) "hello")
+                (func $in_table)
+                (func $start call $f)
+                (func $unused)
+                (start $start)
+            )
+        "#,
+        &[],
+    )?;
+    assert_eq!(
+        summary,
+        Summary {
+            imports: vec!["env::f".to_string()],
+            funcs: 2,
+            tables: 1,
+            elements: 1,
+            data: 1,
+            start: true,
+            ..Summary::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn removes_unreferenced_passive_segments() -> Result<()> {
+    let summary = gc(
+        r#"
+            (module
+                (memory 1)
+                (table 1 funcref)
+                (data $used "used")
+                (data $unused "unused")
+                (elem $used_elem func $a)
+                (elem $unused_elem func $b)
+                (func $a)
+                (func $b)
+                (func (export "init")
+                    (memory.init $used (i32.const 0) (i32.const 0) (i32.const 4))
+                    (table.init $used_elem (i32.const 0) (i32.const 0) (i32.const 1)))
+                (func (export "drop")
+                    (data.drop $unused)
+                    (elem.drop $unused_elem))
+            )
+        "#,
+        &["init"],
+    )?;
+    assert_eq!(
+        summary,
+        Summary {
+            exports: vec!["init".to_string()],
+            funcs: 2,
+            tables: 1,
+            elements: 1,
+            data: 1,
+            ..Summary::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn filters_declared_function_references() -> Result<()> {
+    let summary = gc(
+        r#"
+            (module
+                (func $a)
+                (func $b)
+                (func (export "a") (result funcref) ref.func $a)
+                (func (export "b") (result funcref) ref.func $b)
+            )
+        "#,
+        &["a"],
+    )?;
+    assert_eq!(
+        summary,
+        Summary {
+            exports: vec!["a".to_string()],
+            funcs: 2,
+            elements: 1,
+            ..Summary::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_export() -> Result<()> {
+    let wasm = wat::parse_str("(module)")?;
+    let err = gc_module(&wasm, &["foo"]).unwrap_err();
+    assert_eq!(err.to_string(), "module does not have export `foo`");
+    Ok(())
+}
+
+#[test]
+fn keeps_component_sections() -> Result<()> {
+    let mut wasm = wat::parse_str(
+        r#"
+            (module
+                (import "env" "unused" (func))
+                (memory (export "memory") 1)
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+                (func (export "utf16_realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+                (func (export "foo#a") (param i32 i32) unreachable)
+                (func (export "unused") unreachable)
+            )
+        "#,
+    )?;
+    // Debug info refers to code offsets which the gc pass changes, so it's
+    // removed.
+    let (name, data) = (".debug_info", b"\x01\x02\x03");
+    wasm.push(0);
+    wasm.push((1 + name.len() + data.len()) as u8);
+    wasm.push(name.len() as u8);
+    wasm.extend_from_slice(name.as_bytes());
+    wasm.extend_from_slice(data);
+
+    let mut interfaces = ComponentInterfaces::default();
+    let foo = Interface::parse("foo", "a: func(s: string)")?;
+    interfaces.exports.insert(foo.name.clone(), foo);
+    let wasm = embed_module_interfaces(&wasm, &interfaces)?;
+    let mut options = IndexMap::new();
+    options.insert(
+        "foo".to_string(),
+        "encoding=utf16,realloc=utf16_realloc".parse::<CanonicalOptions>()?,
+    );
+    let wasm = embed_module_options(&wasm, &options)?;
+
+    let wasm = gc_module(&wasm, &["memory", "cabi_realloc", "utf16_realloc", "foo#a"])?;
+    assert_eq!(
+        summarize(&wasm)?,
+        Summary {
+            exports: vec![
+                "memory".to_string(),
+                "cabi_realloc".to_string(),
+                "utf16_realloc".to_string(),
+                "foo#a".to_string(),
+            ],
+            funcs: 3,
+            ..Summary::default()
+        }
+    );
+    let mut custom_sections = Vec::new();
+    for payload in Parser::new(0).parse_all(&wasm) {
+        if let Payload::CustomSection(s) = payload? {
+            custom_sections.push(s.name().to_string());
+        }
+    }
+    assert_eq!(custom_sections, ["component-type", "component-options"]);
+
+    // The embedded interfaces and options are still found by the encoder.
+    ComponentEncoder::default()
+        .module(&wasm)?
+        .validate(true)
+        .encode()?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use wasmparser::{Parser, Payload};
+use wit_component::{gc_module, ComponentEncoder};
+use wit_parser::Interface;
+
+/// Returns the name and contents of every custom section within `wasm`,
+/// including those of nested modules.
+fn custom_sections(wasm: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut ret = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection(s) = payload? {
+            ret.push((s.name().to_string(), s.data().to_vec()));
+        }
+    }
+    Ok(ret)
+}
+
+fn contains(data: &[u8], needle: &str) -> bool {
+    data.windows(needle.len()).any(|w| w == needle.as_bytes())
+}
+
+#[test]
+fn component_names_and_debug_info() -> Result<()> {
+    let module = wat::parse_str(
+        r#"
+            (module
+                (import "foo" "a" (func (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+                (func (export "bar#b"))
+                (@custom ".debug_info" "dwarf")
+            )
+        "#,
+    )?;
+    let mut foo = Interface::parse("foo", "a: func(s: string)")?;
+    foo.name = "foo".to_string();
+    let mut bar = Interface::parse("bar", "b: func()")?;
+    bar.name = "bar".to_string();
+
+    let bytes = ComponentEncoder::default()
+        .module(&module)?
+        .imports([foo])?
+        .exports([bar])?
+        .validate(true)
+        .encode()?;
+
+    let sections = custom_sections(&bytes)?;
+    let debug = sections.iter().find(|(name, _)| name == ".debug_info");
+    assert_eq!(debug.map(|(_, data)| &data[..]), Some(&b"dwarf"[..]));
+
+    let (_, names) = sections
+        .iter()
+        .find(|(name, _)| name == "component-name")
+        .expect("component should have a `component-name` section");
+    for name in [
+        "main",
+        "wit-component:shim",
+        "wit-component:fixups",
+        "foo#a",
+        "bar#b",
+    ] {
+        assert!(contains(names, name), "missing name `{name}`");
+    }
+    Ok(())
+}
+
+#[test]
+fn gc_preserves_names() -> Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+            (module $m
+                (import "env" "unused" (func $unused))
+                (global $dead_global (mut i32) (i32.const 0))
+                (global $g (mut i32) (i32.const 0))
+                (func $dead
+                    global.get $dead_global
+                    drop
+                    call $unused)
+                (func $live (export "f") (param $x i32) (local $y i32)
+                    global.get $g
+                    drop)
+            )
+        "#,
+    )?;
+    let wasm = gc_module(&wasm, &["f"])?;
+    let text = wasmprinter::print_bytes(&wasm)?;
+    for expected in [
+        "(module $m",
+        "(global $g (;0;)",
+        "(func $live (;0;)",
+        "(param $x i32)",
+        "(local $y i32)",
+    ] {
+        assert!(text.contains(expected), "missing `{expected}` in:\n{text}");
+    }
+    assert!(!text.contains("$dead"), "dead names remain in:\n{text}");
+    Ok(())
+}
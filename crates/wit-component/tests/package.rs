@@ -0,0 +1,124 @@
+use anyhow::Result;
+use wit_component::{is_wit_package, read_interface, ComponentEncoder, WitPackage};
+use wit_parser::{Interface, TypeDefKind};
+
+fn interface(name: &str, wit: &str) -> Result<Interface> {
+    let mut interface = Interface::parse(name, wit)?;
+    interface.name = name.to_string();
+    Ok(interface)
+}
+
+#[test]
+fn round_trip_with_docs() -> Result<()> {
+    let foo = interface(
+        "foo",
+        r#"
+            /// A point in space.
+            record point {
+                /// The x coordinate.
+                x: u32,
+                y: u32,
+            }
+
+            /// Moves a point.
+            move: func(p: point) -> point
+        "#,
+    )?;
+    let bar = interface("bar", "bar: func(s: string)")?;
+
+    let bytes = WitPackage::new([foo, bar])?.encode()?;
+    assert!(is_wit_package(&bytes));
+
+    let package = WitPackage::decode(&bytes)?;
+    assert_eq!(
+        package.interfaces.keys().collect::<Vec<_>>(),
+        ["foo", "bar"]
+    );
+
+    let foo = package.interface(Some("foo"))?;
+    assert_eq!(foo.functions[0].name, "move");
+    assert_eq!(
+        foo.functions[0].docs.contents.as_deref(),
+        Some("Moves a point.\n")
+    );
+    let (_, point) = foo
+        .types
+        .iter()
+        .find(|(_, ty)| ty.name.as_deref() == Some("point"))
+        .unwrap();
+    assert_eq!(point.docs.contents.as_deref(), Some("A point in space.\n"));
+    match &point.kind {
+        TypeDefKind::Record(r) => {
+            assert_eq!(
+                r.fields[0].docs.contents.as_deref(),
+                Some("The x coordinate.\n")
+            );
+            assert_eq!(r.fields[1].docs.contents, None);
+        }
+        _ => panic!("expected a record"),
+    }
+
+    let bar = package.interface(Some("bar"))?;
+    assert_eq!(bar.functions[0].docs.contents, None);
+    Ok(())
+}
+
+#[test]
+fn select_interface() -> Result<()> {
+    let single = WitPackage::new([interface("foo", "a: func()")?])?;
+    assert_eq!(single.interface(None)?.name, "foo");
+    assert_eq!(single.interface(Some("foo"))?.name, "foo");
+    assert_eq!(
+        single.interface(Some("other")).unwrap_err().to_string(),
+        "WIT package does not contain an interface named `other`"
+    );
+
+    let multiple = WitPackage::new([
+        interface("foo", "a: func()")?,
+        interface("bar", "b: func()")?,
+    ])?;
+    assert_eq!(multiple.interface(Some("bar"))?.name, "bar");
+    assert_eq!(
+        multiple.interface(Some("baz")).unwrap_err().to_string(),
+        "WIT package does not contain an interface named `baz`"
+    );
+    assert_eq!(
+        multiple.interface(None).unwrap_err().to_string(),
+        "WIT package contains multiple interfaces and one must be named"
+    );
+    Ok(())
+}
+
+#[test]
+fn read_interface_from_package() -> Result<()> {
+    let dir = std::env::temp_dir().join("wit-component-read-interface-from-package");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("foo.wasm");
+    std::fs::write(
+        &path,
+        WitPackage::new([interface("foo", "a: func()")?])?.encode()?,
+    )?;
+
+    let iface = read_interface(&path, None)?;
+    assert_eq!(iface.name, "foo");
+    assert_eq!(iface.functions[0].name, "a");
+
+    let iface = read_interface(&path, Some("foo"))?;
+    assert_eq!(iface.name, "foo");
+    assert!(read_interface(&path, Some("renamed")).is_err());
+    Ok(())
+}
+
+#[test]
+fn not_a_package() -> Result<()> {
+    let bytes = ComponentEncoder::default()
+        .types_only(true)
+        .exports([interface("foo", "a: func()")?])?
+        .encode()?;
+    assert!(!is_wit_package(&bytes));
+    assert_eq!(
+        WitPackage::decode(&bytes).unwrap_err().to_string(),
+        "component is not a WIT package"
+    );
+    Ok(())
+}
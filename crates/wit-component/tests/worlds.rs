@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::path::Path;
+use wit_component::read_world;
+
+#[test]
+fn read_world_file_and_dir() -> Result<()> {
+    for path in ["tests/worlds/basic/world.wit", "tests/worlds/basic"] {
+        let world = read_world(path, None)?;
+        assert_eq!(world.name, "my-world");
+
+        let interfaces = &world.interfaces;
+        assert_eq!(interfaces.imports.keys().collect::<Vec<_>>(), ["host"]);
+        assert_eq!(interfaces.exports.keys().collect::<Vec<_>>(), ["http"]);
+        assert_eq!(interfaces.exports["http"].name, "http");
+        assert_eq!(interfaces.exports["http"].functions[0].name, "handle");
+        assert_eq!(interfaces.default.as_ref().unwrap().name, "main");
+
+        let dir = Path::new("tests/worlds/basic");
+        let files = ["world", "host", "types", "handler", "main"]
+            .iter()
+            .map(|f| dir.join(f).with_extension("wit"))
+            .collect::<Vec<_>>();
+        assert_eq!(world.files, files);
+    }
+    Ok(())
+}
+
+#[test]
+fn select_world_by_name() -> Result<()> {
+    let world = read_world("tests/worlds/many", Some("b"))?;
+    assert_eq!(world.name, "b");
+    assert_eq!(
+        world.interfaces.exports.keys().collect::<Vec<_>>(),
+        ["host"]
+    );
+
+    let err = read_world("tests/worlds/many", None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "directory `tests/worlds/many` contains multiple worlds and one must be named"
+    );
+    let err = read_world("tests/worlds/many", Some("c")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "directory `tests/worlds/many` does not contain a world named `c`"
+    );
+    let err = read_world("tests/worlds/many/a.wit", Some("b")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "world file `tests/worlds/many/a.wit` defines world `a`, not `b`"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_file_in_dir() {
+    let err = read_world("tests/worlds/invalid", None).unwrap_err();
+    assert_eq!(
+        format!("{err:#}"),
+        "failed to tokenize: tests/worlds/invalid/host.wit: unterminated block comment"
+    );
+}
@@ -2,8 +2,8 @@ use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 use std::path::{Path, PathBuf};
 use wit_bindgen_core::component::ComponentGenerator;
-use wit_bindgen_core::{wit_parser, Files, Generator, WorldGenerator};
-use wit_component::ComponentInterfaces;
+use wit_bindgen_core::{wit_parser, Files, Filter, Generator, WorldGenerator};
+use wit_component::{read_interface, ComponentInterfaces};
 use wit_parser::Interface;
 
 /// Helper for passing VERSION to opt.
@@ -107,6 +107,9 @@ struct LegacyWorld {
     /// specified multiple times.
     #[clap(long, short)]
     exports: Vec<PathBuf>,
+
+    #[clap(flatten)]
+    filter: FilterOpts,
 }
 
 #[derive(Debug, Parser)]
@@ -127,13 +130,66 @@ struct World {
     /// naming modules/files/etc.
     #[clap(long, short)]
     name: String,
+
+    #[clap(flatten)]
+    filter: FilterOpts,
+}
+
+#[derive(Debug, Parser)]
+struct FilterOpts {
+    /// Only generate bindings for the given functions of an interface, plus
+    /// the types they use. Can be specified multiple times.
+    #[clap(long = "allow-function", value_name = "INTERFACE#FUNC", value_parser = parse_item)]
+    allow_functions: Vec<(String, String)>,
+
+    /// Don't generate bindings for the given function. Can be specified
+    /// multiple times.
+    #[clap(long = "deny-function", value_name = "INTERFACE#FUNC", value_parser = parse_item)]
+    deny_functions: Vec<(String, String)>,
+
+    /// Generate bindings for the given type of an interface even if no
+    /// generated function uses it. Can be specified multiple times.
+    #[clap(long = "allow-type", value_name = "INTERFACE#TYPE", value_parser = parse_item)]
+    allow_types: Vec<(String, String)>,
+
+    /// Don't generate bindings for the given type, which must not be used by
+    /// any generated function. Can be specified multiple times.
+    #[clap(long = "deny-type", value_name = "INTERFACE#TYPE", value_parser = parse_item)]
+    deny_types: Vec<(String, String)>,
+}
+
+impl FilterOpts {
+    fn build(&self) -> Filter {
+        let mut filter = Filter::default();
+        for (interface, name) in self.allow_functions.iter() {
+            filter.allow_function(interface, name);
+        }
+        for (interface, name) in self.deny_functions.iter() {
+            filter.deny_function(interface, name);
+        }
+        for (interface, name) in self.allow_types.iter() {
+            filter.allow_type(interface, name);
+        }
+        for (interface, name) in self.deny_types.iter() {
+            filter.deny_type(interface, name);
+        }
+        filter
+    }
+}
+
+fn parse_item(s: &str) -> Result<(String, String)> {
+    let (interface, name) = Filter::parse_item(s)?;
+    Ok((interface.to_string(), name.to_string()))
 }
 
 fn parse_named_interface(s: &str) -> Result<Interface> {
     let mut parts = s.splitn(2, '=');
     let name_or_path = parts.next().unwrap();
     let (name, path) = match parts.next() {
-        Some(path) => (name_or_path, path),
+        Some(path) => (Some(name_or_path), path),
+        // A WIT package names its interfaces itself, so without an explicit
+        // name its only interface is used.
+        None if name_or_path.ends_with(".wasm") => (None, name_or_path),
         None => {
             // Take only the name *before* the first '.' in the filename.
             // Unfortunately, file_stem gives the name before the final '.' in
@@ -147,7 +203,7 @@ fn parse_named_interface(s: &str) -> Result<Interface> {
                 .to_str()
                 .unwrap();
             let name = name.split('.').next().unwrap();
-            (name, name_or_path)
+            (Some(name), name_or_path)
         }
     };
     let path = Path::new(path);
@@ -155,12 +211,8 @@ fn parse_named_interface(s: &str) -> Result<Interface> {
         bail!("interface file `{}` does not exist", path.display());
     }
 
-    let mut interface = Interface::parse_file(&path)
-        .with_context(|| format!("failed to parse interface file `{}`", path.display()))?;
-
-    interface.name = name.to_string();
-
-    Ok(interface)
+    read_interface(path, name)
+        .with_context(|| format!("failed to parse interface file `{}`", path.display()))
 }
 
 #[derive(Debug, Parser)]
@@ -232,6 +284,10 @@ fn main() -> Result<()> {
             Some(path) => path.join(name),
             None => name.into(),
         };
+        if files.is_stub(name) && dst.exists() {
+            println!("Skipping {:?}, which already exists", dst);
+            continue;
+        }
         println!("Generating {:?}", dst);
         if let Some(parent) = dst.parent() {
             std::fs::create_dir_all(parent)
@@ -248,16 +304,20 @@ fn gen_legacy_world(
     world: LegacyWorld,
     files: &mut Files,
 ) -> Result<()> {
-    let imports = world
+    let mut imports = world
         .imports
         .iter()
-        .map(|wit| Interface::parse_file(wit))
+        .map(wit_bindgen_core::load)
         .collect::<Result<Vec<_>>>()?;
-    let exports = world
+    let mut exports = world
         .exports
         .iter()
-        .map(|wit| Interface::parse_file(wit))
+        .map(wit_bindgen_core::load)
         .collect::<Result<Vec<_>>>()?;
+    world
+        .filter
+        .build()
+        .apply_all(imports.iter_mut().chain(exports.iter_mut()))?;
 
     generator.generate_all(&imports, &exports, files);
     Ok(())
@@ -278,11 +338,13 @@ fn gen_world(
         .into_iter()
         .map(|i| (i.name.clone(), i))
         .collect();
-    let interfaces = ComponentInterfaces {
+    let mut interfaces = ComponentInterfaces {
         imports,
         exports,
         default: world.default,
     };
+    world.filter.build().apply(&mut interfaces)?;
+    generator.validate(&interfaces)?;
     generator.generate(&world.name, &interfaces, files);
     Ok(())
 }
@@ -0,0 +1,34 @@
+use anyhow::Result;
+
+wit_bindgen_host_wasmtime_rust::generate!({
+    default: "../../tests/runtime/borrowed_results/exports.wit",
+    name: "exports",
+});
+
+fn run(wasm: &str) -> Result<()> {
+    let (exports, mut store) = crate::instantiate::<(), _>(
+        wasm,
+        |_linker| Ok(()),
+        |store, module, linker| Exports::instantiate(store, module, linker),
+    )?;
+
+    let bytes = exports.allocated_bytes(&mut store)?;
+
+    // Owned results are freed by the post-return function, so nothing is
+    // leaked by returning them.
+    for _ in 0..2 {
+        assert_eq!(exports.greeting(&mut store, true)?, "hello");
+        assert_eq!(exports.bytes(&mut store, true)?, [1, 2, 3]);
+    }
+    assert_eq!(bytes, exports.allocated_bytes(&mut store)?);
+
+    // Borrowed results aren't freed, which would poison the static data they
+    // point to and show up as corrupted results the second time around.
+    for _ in 0..2 {
+        assert_eq!(exports.greeting(&mut store, false)?, "hello");
+        assert_eq!(exports.bytes(&mut store, false)?, [1, 2, 3]);
+    }
+    assert_eq!(bytes, exports.allocated_bytes(&mut store)?);
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::borrow::Cow;
+
+wit_bindgen_guest_rust::generate!({
+    default: "../../tests/runtime/borrowed_results/exports.wit",
+    name: "exports",
+    borrowed_export_results,
+});
+
+struct Component;
+
+export_exports!(Component);
+
+impl exports::Exports for Component {
+    fn allocated_bytes() -> u32 {
+        test_rust_wasm::get() as u32
+    }
+
+    fn greeting(owned: bool) -> Cow<'static, str> {
+        if owned {
+            Cow::Owned("hello".to_string())
+        } else {
+            Cow::Borrowed("hello")
+        }
+    }
+
+    fn bytes(owned: bool) -> Cow<'static, [u8]> {
+        if owned {
+            Cow::Owned(vec![1, 2, 3])
+        } else {
+            Cow::Borrowed(&[1, 2, 3])
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// The `smoke` test again, with `call_errors` and a failing import, to check
+// that the failure is reported as an error of the host.
+use wit_bindgen_host_wasmtime_rust::CallErrorKind;
+
+wit_bindgen_host_wasmtime_rust::generate!({
+    import: "../../tests/runtime/smoke/imports.wit",
+    default: "../../tests/runtime/smoke/exports.wit",
+    name: "exports",
+    call_errors: true,
+});
+
+#[derive(Default)]
+pub struct MyImports;
+
+impl imports::Imports for MyImports {
+    fn thunk(&mut self) -> anyhow::Result<()> {
+        Err(anyhow::Error::msg("host failure"))
+    }
+}
+
+fn run(wasm: &str) -> anyhow::Result<()> {
+    let (exports, mut store) = crate::instantiate(
+        wasm,
+        |linker| {
+            imports::add_to_linker(
+                linker,
+                |cx: &mut crate::Context<MyImports>| -> &mut MyImports { &mut cx.imports },
+            )
+        },
+        |store, module, linker| Exports::instantiate(store, module, linker),
+    )?;
+
+    let err = exports.thunk(&mut store).unwrap_err();
+    assert_eq!(err.kind(), CallErrorKind::Host);
+    assert_eq!(err.interface(), None);
+    assert_eq!(err.function(), "thunk");
+    let host_error = err.host_error().unwrap();
+    assert_eq!(host_error.interface, "imports");
+    assert_eq!(host_error.function, "thunk");
+    assert!(format!("{:?}", err.into_inner()).contains("host failure"));
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// The `results` test again, with the `e` error type of imports mapped to a
+// host error type.
+wit_bindgen_host_wasmtime_rust::generate!({
+    import: "../../tests/runtime/results/imports.wit",
+    default: "../../tests/runtime/results/exports.wit",
+    name: "exports",
+    error_types: {
+        "imports.e": super::MyError => super::to_e,
+    },
+});
+
+#[derive(Debug)]
+pub enum MyError {
+    Wit(imports::E),
+    Fatal,
+}
+
+// Host errors which have a WIT equivalent are returned to the guest, and
+// others trap.
+pub fn to_e(err: MyError) -> anyhow::Result<imports::E> {
+    match err {
+        MyError::Wit(e) => Ok(e),
+        MyError::Fatal => Err(anyhow::Error::msg("fatal host error")),
+    }
+}
+
+#[derive(Default)]
+pub struct MyImports {}
+
+impl imports::Imports for MyImports {
+    fn string_error(&mut self, a: f32) -> anyhow::Result<Result<f32, String>> {
+        Ok(Ok(a))
+    }
+
+    fn enum_error(&mut self, a: f64) -> Result<f64, MyError> {
+        if a == 0.0 {
+            Err(MyError::Wit(imports::E::B))
+        } else if a == 1.0 {
+            Err(MyError::Fatal)
+        } else {
+            Ok(a)
+        }
+    }
+
+    fn record_error(&mut self, a: f64) -> wit_bindgen_host_wasmtime_rust::Result<f64, imports::E2> {
+        Ok(a)
+    }
+
+    fn variant_error(
+        &mut self,
+        a: f64,
+    ) -> wit_bindgen_host_wasmtime_rust::Result<f64, imports::E3> {
+        Ok(a)
+    }
+
+    fn empty_error(&mut self, a: u32) -> anyhow::Result<Result<u32, ()>> {
+        Ok(Ok(a))
+    }
+}
+
+fn run(wasm: &str) -> anyhow::Result<()> {
+    let create = || {
+        crate::instantiate(
+            wasm,
+            |linker| {
+                imports::add_to_linker(
+                    linker,
+                    |cx: &mut crate::Context<MyImports>| -> &mut MyImports { &mut cx.imports },
+                )
+            },
+            |store, module, linker| Exports::instantiate(store, module, linker),
+        )
+    };
+
+    let (exports, mut store) = create()?;
+    assert_eq!(exports.enum_error(&mut store, 0.0)?, Err(E::B));
+    assert_eq!(exports.enum_error(&mut store, 2.0)?, Ok(2.0));
+
+    let e = exports.enum_error(&mut store, 1.0);
+    assert!(e
+        .err()
+        .unwrap()
+        .to_string()
+        .starts_with("fatal host error\nwasm backtrace:\n"));
+
+    Ok(())
+}